@@ -1,3 +1,14 @@
+CREATE TABLE IF NOT EXISTS locations (
+  id SERIAL PRIMARY KEY,
+  name VARCHAR(100) NOT NULL,
+  country VARCHAR(2),
+  region TEXT,
+  population BIGINT,
+  elevation_m INTEGER
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_locations_name ON locations(LOWER(name));
+
 CREATE TABLE IF NOT EXISTS weather_data (
   id SERIAL PRIMARY KEY,
   city VARCHAR(100),
@@ -5,14 +16,58 @@ CREATE TABLE IF NOT EXISTS weather_data (
   feels_like DOUBLE PRECISION,
   humidity INTEGER NOT NULL,
   pressure INTEGER,
+  sea_level_pressure INTEGER,
+  ground_level_pressure INTEGER,
   wind_speed DOUBLE PRECISION NOT NULL,
   wind_direction DOUBLE PRECISION,
   weather_main VARCHAR(50),
   weather_description VARCHAR(100),
   weather_icon VARCHAR(10),
+  weather_id INTEGER,
   timestamp BIGINT NOT NULL,
   timezone INTEGER,
+  timezone_name TEXT,
+  uv_index DOUBLE PRECISION,
+  dew_point DOUBLE PRECISION,
+  wind_chill DOUBLE PRECISION,
+  minutes_to_precip INTEGER,
+  temperature_ema DOUBLE PRECISION,
+  day_high DOUBLE PRECISION,
+  day_low DOUBLE PRECISION,
+  comfort_category TEXT,
+  pressure_trend TEXT,
+  api_latency_ms INTEGER,
+  source TEXT,
+  station_base TEXT,
+  station_id BIGINT,
+  station_type INTEGER,
+  units TEXT,
+  timestamp_suspect BOOLEAN NOT NULL DEFAULT FALSE,
+  labels JSONB NOT NULL DEFAULT '{}',
+  location_id INTEGER REFERENCES locations(id),
   created_at TIMESTAMP DEFAULT NOW()
 );
 
 CREATE INDEX IF NOT EXISTS idx_weather_timestamp on weather_data(timestamp);
+
+CREATE TABLE IF NOT EXISTS dead_letter (
+  id SERIAL PRIMARY KEY,
+  city VARCHAR(100),
+  payload JSONB NOT NULL,
+  error TEXT NOT NULL,
+  attempts INTEGER NOT NULL,
+  created_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS fetch_log (
+  id BIGSERIAL PRIMARY KEY,
+  city VARCHAR(100) NOT NULL,
+  provider TEXT NOT NULL,
+  http_status INTEGER,
+  api_code INTEGER,
+  error TEXT,
+  latency_ms INTEGER NOT NULL,
+  attempted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fetch_log_city_attempted_at ON fetch_log(city, attempted_at);
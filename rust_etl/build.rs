@@ -0,0 +1,106 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    // Rebuild when a migration is added so `sqlx::migrate!` embeds it.
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-changed=src/config/app_config.rs");
+
+    let docs = config_docs("src/config/app_config.rs");
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("config_docs.rs");
+    fs::write(out, docs).unwrap();
+}
+
+/// Generates `CONFIG_DOCS`, the config key and first doc comment sentence of
+/// each `AppConfig` field in declaration order, for the config template.
+fn config_docs(path: &str) -> String {
+    let source = fs::read_to_string(path).unwrap();
+    let file = syn::parse_file(&source).unwrap();
+    let fields = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            syn::Item::Struct(item) if item.ident == "AppConfig" => Some(&item.fields),
+            _ => None,
+        })
+        .expect("AppConfig struct in app_config.rs");
+
+    let mut entries = String::new();
+    for field in fields {
+        let mut key = field.ident.as_ref().unwrap().to_string().to_uppercase();
+        let mut skip = false;
+        let mut doc = Vec::new();
+        for attr in &field.attrs {
+            if attr.path().is_ident("doc") {
+                if let syn::Meta::NameValue(syn::MetaNameValue {
+                    value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(line), .. }),
+                    ..
+                }) = &attr.meta
+                {
+                    doc.push(line.value().trim().to_string());
+                }
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        key = meta.value()?.parse::<syn::LitStr>()?.value();
+                    } else if meta.path.is_ident("skip") {
+                        skip = true;
+                    } else if meta.input.peek(syn::Token![=]) {
+                        meta.value()?.parse::<syn::Expr>()?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
+        }
+        if skip {
+            continue;
+        }
+
+        let paragraph = doc.iter().take_while(|line| !line.is_empty()).cloned().collect::<Vec<_>>().join(" ");
+        // A sentence ends at a period followed by a capital, so "e.g. a" doesn't end the sentence
+        let end = paragraph
+            .match_indices(". ")
+            .map(|(index, _)| index)
+            .find(|index| paragraph[index + 2..].starts_with(|c: char| c.is_uppercase()));
+        let sentence = match end {
+            Some(end) => &paragraph[..=end],
+            None => &paragraph,
+        };
+        entries.push_str(&format!("    ({:?}, {:?}),\n", key, strip_links(sentence)));
+    }
+
+    format!("pub static CONFIG_DOCS: &[(&str, &str)] = &[\n{}];\n", entries)
+}
+
+/// Rewrites rustdoc links as their text, e.g. "[`X`](Self::x)" or "[`X`]"
+/// as "`X`", since the template's comments are plain text. Other brackets,
+/// such as "weather[0]", are kept.
+fn strip_links(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find(']') else {
+            rest = &rest[start..];
+            break;
+        };
+        let label = &after[..end];
+        let mut tail = &after[end + 1..];
+        let target = tail.strip_prefix('(').and_then(|target| target.find(')').map(|close| &target[close + 1..]));
+        if let Some(after_target) = target {
+            tail = after_target;
+        }
+        if target.is_some() || (label.len() > 1 && label.starts_with('`') && label.ends_with('`')) {
+            out.push_str(label);
+            rest = tail;
+        } else {
+            out.push('[');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
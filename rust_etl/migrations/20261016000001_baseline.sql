@@ -0,0 +1,47 @@
+-- Baseline schema, matching postgres/init.sql. Safe on databases created
+-- from any earlier init.sql: existing tables are kept and missing columns
+-- are added.
+
+CREATE TABLE IF NOT EXISTS weather_data (
+  id SERIAL PRIMARY KEY,
+  city VARCHAR(100),
+  temperature DOUBLE PRECISION NOT NULL,
+  feels_like DOUBLE PRECISION,
+  humidity INTEGER NOT NULL,
+  pressure INTEGER,
+  wind_speed DOUBLE PRECISION NOT NULL,
+  wind_direction DOUBLE PRECISION,
+  weather_main VARCHAR(50),
+  weather_description VARCHAR(100),
+  weather_icon VARCHAR(10),
+  timestamp BIGINT NOT NULL,
+  timezone INTEGER,
+  created_at TIMESTAMP DEFAULT NOW()
+);
+
+ALTER TABLE weather_data
+  ADD COLUMN IF NOT EXISTS timezone_name TEXT,
+  ADD COLUMN IF NOT EXISTS uv_index DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS dew_point DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS temperature_ema DOUBLE PRECISION,
+  ADD COLUMN IF NOT EXISTS comfort_category TEXT,
+  ADD COLUMN IF NOT EXISTS pressure_trend TEXT,
+  ADD COLUMN IF NOT EXISTS api_latency_ms INTEGER,
+  ADD COLUMN IF NOT EXISTS source TEXT,
+  ADD COLUMN IF NOT EXISTS station_base TEXT,
+  ADD COLUMN IF NOT EXISTS station_id BIGINT,
+  ADD COLUMN IF NOT EXISTS station_type INTEGER,
+  ADD COLUMN IF NOT EXISTS units TEXT,
+  ADD COLUMN IF NOT EXISTS timestamp_suspect BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD COLUMN IF NOT EXISTS labels JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_weather_timestamp on weather_data(timestamp);
+
+CREATE TABLE IF NOT EXISTS dead_letter (
+  id SERIAL PRIMARY KEY,
+  city VARCHAR(100),
+  payload JSONB NOT NULL,
+  error TEXT NOT NULL,
+  attempts INTEGER NOT NULL,
+  created_at TIMESTAMP DEFAULT NOW()
+);
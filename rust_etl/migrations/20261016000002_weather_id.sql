@@ -0,0 +1 @@
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS weather_id INTEGER;
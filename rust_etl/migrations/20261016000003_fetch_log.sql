@@ -0,0 +1,14 @@
+-- One row per weather API fetch attempt, whether or not the observation
+-- was stored.
+CREATE TABLE IF NOT EXISTS fetch_log (
+  id BIGSERIAL PRIMARY KEY,
+  city VARCHAR(100) NOT NULL,
+  provider TEXT NOT NULL,
+  http_status INTEGER,
+  api_code INTEGER,
+  error TEXT,
+  latency_ms INTEGER NOT NULL,
+  attempted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_fetch_log_city_attempted_at ON fetch_log(city, attempted_at);
@@ -0,0 +1,6 @@
+-- Databases created by hand or from older tooling may declare humidity and
+-- pressure as SMALLINT, where an out-of-range value fails the whole insert.
+-- A no-op when they are already INTEGER.
+ALTER TABLE weather_data
+  ALTER COLUMN humidity TYPE INTEGER,
+  ALTER COLUMN pressure TYPE INTEGER;
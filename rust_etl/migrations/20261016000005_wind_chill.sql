@@ -0,0 +1,2 @@
+-- Environment Canada wind chill index, stored when WIND_CHILL is enabled.
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS wind_chill DOUBLE PRECISION;
@@ -0,0 +1,3 @@
+-- Minutes until precipitation starts (positive) or stops (negative), stored
+-- when COLLECT_MINUTELY_PRECIP is enabled.
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS minutes_to_precip INTEGER;
@@ -0,0 +1,4 @@
+-- Running daily high and low temperature, stored when DAY_EXTREMES is
+-- enabled.
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS day_high DOUBLE PRECISION;
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS day_low DOUBLE PRECISION;
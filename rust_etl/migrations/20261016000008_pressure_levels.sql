@@ -0,0 +1,4 @@
+-- Sea-level and ground-level pressure, as reported by OpenWeatherMap next
+-- to `pressure`.
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS sea_level_pressure INTEGER;
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS ground_level_pressure INTEGER;
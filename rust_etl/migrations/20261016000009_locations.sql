@@ -0,0 +1,14 @@
+-- City metadata maintained alongside the observations, and the location
+-- each observation was resolved to (LOCATION_IDS).
+CREATE TABLE IF NOT EXISTS locations (
+  id SERIAL PRIMARY KEY,
+  name VARCHAR(100) NOT NULL,
+  country VARCHAR(2),
+  region TEXT,
+  population BIGINT,
+  elevation_m INTEGER
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_locations_name ON locations(LOWER(name));
+
+ALTER TABLE weather_data ADD COLUMN IF NOT EXISTS location_id INTEGER REFERENCES locations(id);
@@ -0,0 +1,25 @@
+//! AWS Lambda entry point: each invocation runs one collection cycle and
+//! returns its summary. Built with `--features lambda`, and deployed as the
+//! `bootstrap` executable of an OS-only runtime such as `provided.al2023`.
+
+use anyhow::Context;
+use lambda_runtime::{service_fn, Error, LambdaEvent};
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::lambda::Handler;
+use rust_etl::utils::logging;
+use serde_json::Value;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    logging::init_logger();
+
+    let config = AppConfig::from_env().context("Failed to load application configuration")?;
+    let handler = Handler::new(config).await?;
+    let handler = &handler;
+
+    // The event's content is not used: every invocation collects every city
+    lambda_runtime::run(service_fn(move |_: LambdaEvent<Value>| async move {
+        handler.invoke().await.map_err(|e| Error::from(format!("{:#}", e)))
+    }))
+    .await
+}
@@ -0,0 +1,92 @@
+use crate::config::app_config::AppConfig;
+use crate::models::weather::ComputedColumns;
+use crate::services::database::{DatabaseService, RowScope};
+use anyhow::{Context, Result};
+
+pub const DEFAULT_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillArgs {
+    pub city: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub batch_size: i64,
+}
+
+impl Default for BackfillArgs {
+    fn default() -> Self {
+        Self {
+            city: None,
+            from: None,
+            to: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+/// Recomputes derived columns for stored rows in place, batch by batch, and
+/// only writes rows whose stored values differ from the recomputed ones.
+pub async fn run(config: &AppConfig, args: &BackfillArgs) -> Result<()> {
+    let database = DatabaseService::new(&config.database_url)
+        .await
+        .context("Failed to initialize database connection")?;
+
+    let scope = RowScope {
+        city: args.city.clone(),
+        from: args.from,
+        to: args.to,
+    };
+
+    let total = database.count_rows(&scope).await?;
+    log::info!("🔁 Backfilling computed columns for {} row(s)", total);
+
+    let comfort = config.comfort_thresholds();
+    let mut last_id = 0;
+    let mut processed = 0i64;
+    let mut updated = 0u64;
+
+    loop {
+        let rows = database.rows_after(last_id, &scope, args.batch_size).await?;
+        let Some((max_id, _)) = rows.last() else {
+            break;
+        };
+        last_id = *max_id;
+        processed += rows.len() as i64;
+
+        let changed: Vec<(i32, ComputedColumns)> = rows
+            .iter()
+            .filter_map(|(id, data)| {
+                let mut computed = data.computed(comfort.as_ref(), config.wind_chill);
+                if !config.wind_chill {
+                    // Leave wind chills stored while WIND_CHILL was on in place
+                    computed.wind_chill = data.wind_chill;
+                }
+                if comfort.is_none() {
+                    // Likewise comfort categories stored while COMFORT_CATEGORY was on
+                    computed.comfort_category = data.comfort_category.clone();
+                }
+                let stored = ComputedColumns {
+                    dew_point: data.dew_point,
+                    wind_chill: data.wind_chill,
+                    comfort_category: data.comfort_category.clone(),
+                };
+                (computed != stored).then_some((*id, computed))
+            })
+            .collect();
+
+        if !changed.is_empty() {
+            updated += database.update_computed(&changed).await?;
+        }
+
+        log::info!(
+            "   {}/{} rows processed ({:.1}%), {} updated",
+            processed,
+            total,
+            processed as f64 * 100.0 / total.max(1) as f64,
+            updated
+        );
+    }
+
+    log::info!("✅ Backfill complete: {} row(s) processed, {} updated", processed, updated);
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::config::app_config::AppConfig;
+use crate::config::template;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Writes the config template to `output`, or to standard output.
+pub fn run(config: &AppConfig, output: Option<&Path>) -> Result<()> {
+    let rendered = template::render(config)?;
+    match output {
+        Some(path) => {
+            fs::write(path, rendered)
+                .with_context(|| format!("Failed to write config template to {}", path.display()))?;
+            log::info!("📝 Wrote config template to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
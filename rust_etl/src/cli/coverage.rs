@@ -0,0 +1,93 @@
+use crate::config::app_config::AppConfig;
+use crate::services::database::{Coverage, DatabaseService};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use std::time::Duration;
+
+/// Period reported when `--from` is not given.
+pub const DEFAULT_PERIOD_SECONDS: i64 = 24 * 60 * 60;
+
+/// Most gaps listed per city; the largest is always reported.
+const MAX_LISTED_GAPS: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageArgs {
+    pub city: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// Expected time between observations; defaults to `ETL_INTERVAL`.
+    pub interval: Option<Duration>,
+}
+
+/// Prints, per city, how many of the expected observation slots in the
+/// period hold data and where the gaps are.
+pub async fn run(config: &AppConfig, args: &CoverageArgs) -> Result<()> {
+    let to = args.to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let from = args.from.unwrap_or(to - DEFAULT_PERIOD_SECONDS);
+    if from >= to {
+        return Err(anyhow::anyhow!("--from must be before --to"));
+    }
+    let interval = args.interval.unwrap_or(config.interval);
+    let cities = match &args.city {
+        Some(city) => vec![city.clone()],
+        None => config.cities.clone(),
+    };
+
+    let database = DatabaseService::new(&config.database_url)
+        .await
+        .context("Failed to initialize database connection")?;
+
+    println!("Coverage from {} to {}, one observation every {}", time(from), time(to), span(interval.as_secs() as i64));
+    for city in &cities {
+        let coverage = database.coverage(city, from, to, interval).await?;
+        println!();
+        print_report(city, &coverage);
+    }
+    Ok(())
+}
+
+fn print_report(city: &str, coverage: &Coverage) {
+    println!(
+        "{}: collected {} of {} expected slots ({:.1}%)",
+        city,
+        coverage.collected,
+        coverage.expected,
+        coverage.ratio() * 100.0
+    );
+
+    let Some(largest) = coverage.largest_gap() else {
+        println!("  no gaps");
+        return;
+    };
+    println!(
+        "  {} gap(s); largest {} from {} to {}",
+        coverage.gaps.len(),
+        span(largest.seconds()),
+        time(largest.start),
+        time(largest.end)
+    );
+    for gap in coverage.gaps.iter().take(MAX_LISTED_GAPS) {
+        println!("  - {} to {} ({})", time(gap.start), time(gap.end), span(gap.seconds()));
+    }
+    if coverage.gaps.len() > MAX_LISTED_GAPS {
+        println!("  … and {} more", coverage.gaps.len() - MAX_LISTED_GAPS);
+    }
+}
+
+fn time(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%SZ").to_string())
+        .unwrap_or_else(|| seconds.to_string())
+}
+
+/// Formats a length of time as e.g. `2h 5m` or `45s`.
+fn span(seconds: i64) -> String {
+    let (hours, minutes, secs) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    match (hours, minutes, secs) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, 0, 0) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
@@ -0,0 +1,252 @@
+use crate::config::app_config::AppConfig;
+use crate::services::database::{DatabaseService, REQUIRED_COLUMNS};
+use crate::services::weather_service::WeatherService;
+use crate::utils::redact;
+use chrono::Utc;
+use std::env;
+
+/// Clock difference against the API's `Date` header above which timestamps
+/// are considered unreliable.
+const MAX_CLOCK_SKEW_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<&'static str>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: Status::Pass, detail: detail.into(), hint: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self { name, status: Status::Warn, detail: detail.into(), hint: Some(hint) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self { name, status: Status::Fail, detail: detail.into(), hint: Some(hint) }
+    }
+
+    fn skip(name: &'static str, reason: &str) -> Self {
+        Self { name, status: Status::Skip, detail: reason.to_string(), hint: None }
+    }
+
+    fn print(&self) {
+        let label = match self.status {
+            Status::Pass => "✅ PASS",
+            Status::Warn => "⚠️  WARN",
+            Status::Fail => "❌ FAIL",
+            Status::Skip => "⏭️  SKIP",
+        };
+        println!("{}  {}: {}", label, self.name, self.detail);
+        if let Some(hint) = self.hint {
+            println!("         → {}", hint);
+        }
+    }
+}
+
+/// Runs every setup check, prints a report and returns `true` when no
+/// critical check failed.
+pub async fn run() -> bool {
+    let mut checks = Vec::new();
+
+    checks.extend(check_env());
+
+    match AppConfig::from_env() {
+        Ok(config) => {
+            checks.push(Check::pass("Configuration", "loaded from environment"));
+            checks.extend(check_database(&config).await);
+            checks.extend(check_api(&config).await);
+        }
+        Err(e) => {
+            checks.push(Check::fail(
+                "Configuration",
+                redact::mask_secrets(&format!("{:#}", e), env_secrets().iter().map(String::as_str)),
+                "Fix the environment variables above (see .env.example)",
+            ));
+            for name in ["Database connection", "Schema", "API key", "Clock skew"] {
+                checks.push(Check::skip(name, "configuration did not load"));
+            }
+        }
+    }
+
+    println!("Montreal Weather ETL — doctor");
+    println!();
+    for check in &checks {
+        check.print();
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    println!("{} check(s), {} failed, {} warning(s)", checks.len(), failed, warned);
+
+    failed == 0
+}
+
+/// `e` with its causes, with the configured passwords and API keys masked:
+/// driver and HTTP errors can echo connection strings and request URLs.
+fn describe(e: &anyhow::Error, config: &AppConfig) -> String {
+    let text = redact::mask_url_in(&format!("{:#}", e), &config.database_url);
+    let secrets = [&config.postgres_password, &config.api_key, &config.weatherapi_key, &config.mapped_provider_api_key]
+        .into_iter()
+        .chain(&config.api_keys)
+        .map(String::as_str);
+    redact::mask_secrets(&text, secrets)
+}
+
+/// Secret values from the environment, for errors raised before a
+/// configuration exists.
+fn env_secrets() -> Vec<String> {
+    ["POSTGRES_PASSWORD", "OPENWEATHER_API_KEY", "OPENWEATHER_API_KEYS", "WEATHERAPI_KEY", "MAPPED_PROVIDER_API_KEY"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok())
+        .flat_map(|value| value.split(',').map(|secret| secret.trim().to_string()).collect::<Vec<_>>())
+        .collect()
+}
+
+fn check_env() -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let has_key = |name| env::var(name).is_ok_and(|keys| keys.split(',').any(|key| !key.trim().is_empty()));
+    match (has_key("OPENWEATHER_API_KEY"), has_key("OPENWEATHER_API_KEYS")) {
+        (true, _) => checks.push(Check::pass("OPENWEATHER_API_KEY", "set")),
+        (false, true) => checks.push(Check::pass("OPENWEATHER_API_KEY", "set via OPENWEATHER_API_KEYS")),
+        _ => checks.push(Check::fail(
+            "OPENWEATHER_API_KEY",
+            "missing",
+            "Create a key at https://openweathermap.org/api and add it to .env",
+        )),
+    }
+
+    let missing: Vec<&str> = ["POSTGRES_USER", "POSTGRES_PASSWORD", "POSTGRES_HOST", "POSTGRES_PORT", "POSTGRES_DB"]
+        .into_iter()
+        .filter(|name| env::var(name).is_err())
+        .collect();
+    if missing.is_empty() {
+        checks.push(Check::pass("PostgreSQL settings", "all POSTGRES_* variables set"));
+    } else {
+        checks.push(Check::warn(
+            "PostgreSQL settings",
+            format!("using built-in defaults for {}", missing.join(", ")),
+            "Set these in .env if your database differs from the docker-compose defaults",
+        ));
+    }
+
+    checks
+}
+
+async fn check_database(config: &AppConfig) -> Vec<Check> {
+    let database = match DatabaseService::new(&config.database_url).await {
+        Ok(database) => database,
+        Err(e) => {
+            return vec![
+                Check::fail(
+                    "Database connection",
+                    describe(&e, config),
+                    "Check POSTGRES_HOST/POSTGRES_PORT and that PostgreSQL is running (docker compose up postgres)",
+                ),
+                Check::skip("Schema", "database unreachable"),
+            ];
+        }
+    };
+
+    let mut checks = vec![Check::pass(
+        "Database connection",
+        format!("connected to {}", redact::mask_url(&config.database_url)),
+    )];
+
+    match database.missing_columns("weather_data", REQUIRED_COLUMNS).await {
+        Ok(missing) if missing.len() == REQUIRED_COLUMNS.len() => checks.push(Check::fail(
+            "Schema",
+            "table weather_data does not exist",
+            "Apply postgres/init.sql to the database",
+        )),
+        Ok(missing) if !missing.is_empty() => checks.push(Check::fail(
+            "Schema",
+            format!("weather_data is missing columns: {}", missing.join(", ")),
+            "Apply the latest postgres/init.sql changes to the existing table",
+        )),
+        Ok(_) => checks.push(Check::pass("Schema", "weather_data has all required columns")),
+        Err(e) => checks.push(Check::fail(
+            "Schema",
+            describe(&e, config),
+            "Make sure the database user can read information_schema",
+        )),
+    }
+
+    checks
+}
+
+async fn check_api(config: &AppConfig) -> Vec<Check> {
+    let weather_service = WeatherService::new(config);
+    // AUTO_LOCATE without CITIES names no city until startup; any city tests the key
+    let city = if config.city.is_empty() { AppConfig::default().city } else { config.city.clone() };
+
+    let probe = match weather_service.probe(&city).await {
+        Ok(probe) => probe,
+        Err(e) => {
+            return vec![
+                Check::fail(
+                    "API key",
+                    format!("request failed: {}", describe(&e, config)),
+                    "Check network access to the weather API and WEATHER_API_BASE_URL",
+                ),
+                Check::skip("Clock skew", "no response from the API"),
+            ];
+        }
+    };
+
+    let mut checks = Vec::new();
+    checks.push(match probe.status.as_u16() {
+        200..=299 => Check::pass("API key", format!("test request for {} succeeded", city)),
+        401 => Check::fail(
+            "API key",
+            "rejected with 401 Unauthorized",
+            "Verify OPENWEATHER_API_KEY; new keys can take up to two hours to activate",
+        ),
+        404 => Check::fail(
+            "API key",
+            format!("city '{}' not found", city),
+            "Check the spelling of CITY (e.g. \"Montreal\" or \"Montreal,CA\")",
+        ),
+        429 => Check::warn(
+            "API key",
+            "rate limited (429)",
+            "The key is valid but over quota; increase ETL_INTERVAL",
+        ),
+        status => Check::fail(
+            "API key",
+            format!("unexpected status {}", status),
+            "Check WEATHER_API_BASE_URL and WEATHER_PATH_TEMPLATE",
+        ),
+    });
+
+    checks.push(match probe.server_date {
+        Some(server_date) => {
+            let skew = (Utc::now() - server_date).num_seconds();
+            if skew.abs() <= MAX_CLOCK_SKEW_SECONDS {
+                Check::pass("Clock skew", format!("{}s relative to the API server", skew))
+            } else {
+                Check::warn(
+                    "Clock skew",
+                    format!("local clock is {}s off the API server", skew),
+                    "Enable NTP on this host; observation ages and staleness checks depend on it",
+                )
+            }
+        }
+        None => Check::skip("Clock skew", "API response had no Date header"),
+    });
+
+    checks
+}
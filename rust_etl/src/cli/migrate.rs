@@ -0,0 +1,79 @@
+use crate::config::app_config::AppConfig;
+use crate::services::database::{DatabaseService, MigrationState, MIGRATOR};
+use anyhow::{Context, Result};
+
+/// What `migrate` does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MigrateMode {
+    /// Apply pending migrations.
+    #[default]
+    Apply,
+    /// List every migration and whether it has been applied.
+    Status,
+    /// Print the SQL of pending migrations without running it.
+    DryRun,
+}
+
+/// Connects and applies, lists or prints migrations according to `mode`.
+pub async fn run(config: &AppConfig, mode: MigrateMode) -> Result<()> {
+    let database = DatabaseService::connect_with_retry(&config.database_url, &config.db_connect_retry_policy())
+        .await
+        .context("Failed to initialize database connection")?;
+    match mode {
+        MigrateMode::Apply => apply(&database).await,
+        MigrateMode::Status => status(&database).await,
+        MigrateMode::DryRun => dry_run(&database).await,
+    }
+}
+
+/// Applies pending migrations on an existing connection and logs each one.
+/// Shared with `AUTO_MIGRATE` at startup.
+pub async fn apply(database: &DatabaseService) -> Result<()> {
+    let applied = database.run_migrations().await?;
+    if applied.is_empty() {
+        log::info!("🗃️  Database schema is up to date");
+    }
+    for (version, description) in &applied {
+        log::info!("🗃️  Applied migration {} ({})", version, description);
+    }
+    Ok(())
+}
+
+async fn status(database: &DatabaseService) -> Result<()> {
+    let statuses = database.migration_status().await?;
+    for status in &statuses {
+        println!("{:<9} {} {}", status.state.name(), status.version, status.description);
+    }
+
+    let pending = statuses.iter().filter(|status| status.state == MigrationState::Pending).count();
+    println!();
+    println!("{} migration(s), {} pending", statuses.len(), pending);
+    if statuses.iter().any(|status| status.state == MigrationState::Modified) {
+        println!("Modified migrations were changed after being applied; `migrate` will refuse to run until they are restored.");
+    }
+    Ok(())
+}
+
+async fn dry_run(database: &DatabaseService) -> Result<()> {
+    let pending: Vec<i64> = database
+        .migration_status()
+        .await?
+        .into_iter()
+        .filter(|status| status.state == MigrationState::Pending)
+        .map(|status| status.version)
+        .collect();
+    if pending.is_empty() {
+        println!("-- Database schema is up to date; nothing would be applied");
+        return Ok(());
+    }
+
+    println!("-- Dry run: {} pending migration(s) would be applied. Nothing has been run.", pending.len());
+    for migration in MIGRATOR.iter().filter(|migration| {
+        !migration.migration_type.is_down_migration() && pending.contains(&migration.version)
+    }) {
+        println!();
+        println!("-- Migration {} ({})", migration.version, migration.description);
+        println!("{}", migration.sql.trim_end());
+    }
+    Ok(())
+}
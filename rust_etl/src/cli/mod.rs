@@ -0,0 +1,155 @@
+pub mod backfill;
+pub mod config_template;
+pub mod coverage;
+pub mod doctor;
+pub mod migrate;
+
+use anyhow::{Context, Result};
+use backfill::BackfillArgs;
+use coverage::CoverageArgs;
+use migrate::MigrateMode;
+use chrono::{DateTime, NaiveDate};
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: rust_etl [COMMAND] [OPTIONS]
+
+Commands:
+  run                 Run the collection loop (default)
+  doctor              Diagnose common setup problems and exit
+  migrate             Apply pending database migrations and exit
+      --status            List applied and pending migrations instead
+      --dry-run           Print the SQL of pending migrations instead
+  backfill-computed   Recompute derived columns (dew point, wind chill,
+                      comfort category) for stored rows
+      --city <CITY>       Only rows for this city
+      --from <TIME>       Only observations at or after TIME
+      --to <TIME>         Only observations at or before TIME
+      --batch-size <N>    Rows per update batch (default 500)
+  coverage            Report the share of expected observations stored
+      --city <CITY>       Only this city (default: every configured city)
+      --from <TIME>       Start of the period (default: 24 hours before --to)
+      --to <TIME>         End of the period (default: now)
+      --interval <SECS>   Expected seconds between observations
+                          (default: ETL_INTERVAL)
+  dump-config-template
+                      Print a commented config file with every setting, its
+                      default and its current value (secrets redacted)
+      --output <PATH>     Write it to PATH instead
+  help                Print this message
+
+TIME is Unix seconds, an RFC 3339 timestamp or a YYYY-MM-DD date (UTC).";
+
+/// Subcommand selected on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run,
+    Doctor,
+    Migrate(MigrateMode),
+    BackfillComputed(BackfillArgs),
+    Coverage(CoverageArgs),
+    /// Output path; standard output when `None`.
+    DumpConfigTemplate(Option<PathBuf>),
+    Help,
+}
+
+impl Command {
+    /// Parses the arguments that follow the program name.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut args = args.into_iter();
+        let command = match args.next().as_deref() {
+            None | Some("run") => Command::Run,
+            Some("doctor") => Command::Doctor,
+            Some("migrate") => {
+                let mode = match args.next().as_deref() {
+                    None => MigrateMode::Apply,
+                    Some("--status") => MigrateMode::Status,
+                    Some("--dry-run") => MigrateMode::DryRun,
+                    Some(other) => return Err(anyhow::anyhow!("unknown option '{}'\n\n{}", other, USAGE)),
+                };
+                Command::Migrate(mode)
+            }
+            Some("backfill-computed") => {
+                let mut backfill = BackfillArgs::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("{} needs a value\n\n{}", flag, USAGE))?;
+                    match flag.as_str() {
+                        "--city" => backfill.city = Some(value),
+                        "--from" => backfill.from = Some(parse_time(&value)?),
+                        "--to" => backfill.to = Some(parse_time(&value)?),
+                        "--batch-size" => {
+                            backfill.batch_size = value
+                                .parse()
+                                .ok()
+                                .filter(|n| *n > 0)
+                                .ok_or_else(|| anyhow::anyhow!("--batch-size must be a positive integer"))?;
+                        }
+                        other => return Err(anyhow::anyhow!("unknown option '{}'\n\n{}", other, USAGE)),
+                    }
+                }
+                return Ok(Command::BackfillComputed(backfill));
+            }
+            Some("coverage") => {
+                let mut coverage = CoverageArgs::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("{} needs a value\n\n{}", flag, USAGE))?;
+                    match flag.as_str() {
+                        "--city" => coverage.city = Some(value),
+                        "--from" => coverage.from = Some(parse_time(&value)?),
+                        "--to" => coverage.to = Some(parse_time(&value)?),
+                        "--interval" => {
+                            let seconds = value
+                                .parse()
+                                .ok()
+                                .filter(|n| *n > 0)
+                                .ok_or_else(|| anyhow::anyhow!("--interval must be a positive number of seconds"))?;
+                            coverage.interval = Some(Duration::from_secs(seconds));
+                        }
+                        other => return Err(anyhow::anyhow!("unknown option '{}'\n\n{}", other, USAGE)),
+                    }
+                }
+                return Ok(Command::Coverage(coverage));
+            }
+            Some("dump-config-template") | Some("--dump-config-template") => {
+                let mut output = None;
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::anyhow!("{} needs a value\n\n{}", flag, USAGE))?;
+                    match flag.as_str() {
+                        "--output" => output = Some(PathBuf::from(value)),
+                        other => return Err(anyhow::anyhow!("unknown option '{}'\n\n{}", other, USAGE)),
+                    }
+                }
+                return Ok(Command::DumpConfigTemplate(output));
+            }
+            Some("help") | Some("-h") | Some("--help") => Command::Help,
+            Some(other) => return Err(anyhow::anyhow!("unknown command '{}'\n\n{}", other, USAGE)),
+        };
+
+        if let Some(extra) = args.next() {
+            return Err(anyhow::anyhow!("unexpected argument '{}'\n\n{}", extra, USAGE));
+        }
+
+        Ok(command)
+    }
+}
+
+/// Parses Unix seconds, an RFC 3339 timestamp or a `YYYY-MM-DD` date (UTC
+/// midnight) into Unix seconds.
+pub fn parse_time(value: &str) -> Result<i64> {
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .with_context(|| format!("invalid time '{}': expected Unix seconds, RFC 3339 or YYYY-MM-DD", value))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp())
+}
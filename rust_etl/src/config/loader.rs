@@ -0,0 +1,215 @@
+//! Layered configuration loading.
+//!
+//! A config struct is the single source of truth: its `Default` impl supplies
+//! the defaults and its serde field names are the environment variable names.
+//! Layers are merged in increasing precedence:
+//!
+//! 1. `T::default()`
+//! 2. an optional config file of flat `KEY = value` lines (a TOML subset:
+//!    quoted strings, numbers, booleans and single-line arrays)
+//! 3. environment variables
+//!
+//! Environment values are strings, so each one is coerced to the JSON type of
+//! the default for that key: numbers and booleans are parsed, arrays are split
+//! on commas and objects are parsed as JSON. Keys whose default is `null`
+//! (optional settings) are taken as strings, with an empty value meaning unset.
+//!
+//! The `config` and `figment` environment providers infer a value's type
+//! from its text rather than from the field, so a comma list such as
+//! `CITIES=Paris,Lyon` would need a custom deserializer on every list field.
+//! Taking the type from the default keeps the existing variable names and
+//! comma lists working, and adding a setting is still one field plus its
+//! default.
+
+use anyhow::{Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Loads `T` from its defaults, the optional `file` and the environment.
+pub fn load<T>(file: Option<&Path>) -> Result<T>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    let defaults = match serde_json::to_value(T::default()).context("Failed to serialize config defaults")? {
+        Value::Object(map) => map,
+        _ => return Err(anyhow::anyhow!("config defaults must serialize to an object")),
+    };
+
+    let mut merged = defaults.clone();
+
+    if let Some(path) = file {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let values = parse_file(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        for (key, value) in values {
+            if !defaults.contains_key(&key) {
+                log::warn!("⚠️  Ignoring unknown config key '{}' in {}", key, path.display());
+                continue;
+            }
+            merged.insert(key, value);
+        }
+    }
+
+    let mut from_env = Vec::new();
+    for (key, default) in &defaults {
+        if let Ok(raw) = env::var(key) {
+            merged.insert(key.clone(), coerce(&raw, default));
+            from_env.push(key.clone());
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).map_err(|e| {
+        if from_env.is_empty() {
+            anyhow::anyhow!("Invalid configuration: {}", e)
+        } else {
+            anyhow::anyhow!(
+                "Invalid configuration: {} (values taken from the environment: {})",
+                e,
+                from_env.join(", ")
+            )
+        }
+    })
+}
+
+/// Converts a raw environment string into the JSON type of `default`. Values
+/// that don't parse are kept as strings so deserialization reports them.
+fn coerce(raw: &str, default: &Value) -> Value {
+    let trimmed = raw.trim();
+    match default {
+        Value::Null if trimmed.is_empty() => Value::Null,
+        Value::Bool(_) => match trimmed.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Value::Bool(true),
+            "0" | "false" | "no" | "off" => Value::Bool(false),
+            _ => Value::String(raw.to_string()),
+        },
+        Value::Number(_) => serde_json::from_str::<serde_json::Number>(trimmed)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(raw.to_string())),
+        Value::Array(_) => Value::Array(
+            trimmed
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        Value::Object(_) => serde_json::from_str(trimmed).unwrap_or_else(|_| Value::String(raw.to_string())),
+        Value::String(_) | Value::Null => Value::String(raw.to_string()),
+    }
+}
+
+/// Parses flat `KEY = value` lines. Blank lines and `#` comments are skipped.
+pub fn parse_file(contents: &str) -> Result<Map<String, Value>> {
+    let mut values = Map::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err(anyhow::anyhow!("line {}: tables are not supported, use flat KEY = value lines", line_no));
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected KEY = value", line_no))?;
+        let value = parse_value(value.trim())
+            .ok_or_else(|| anyhow::anyhow!("line {}: invalid value for {}", line_no, key.trim()))?;
+
+        values.insert(key.trim().to_string(), value);
+    }
+
+    Ok(values)
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    // Single-quoted TOML literal strings have no escapes
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some(Value::String(rest[..end].to_string()));
+    }
+
+    if raw.starts_with('"') || raw.starts_with('[') {
+        // Try the whole value first, then without a trailing comment
+        return serde_json::from_str(raw).ok().or_else(|| {
+            let (value, _) = raw.rsplit_once('#')?;
+            serde_json::from_str(value.trim()).ok()
+        });
+    }
+
+    let value = raw.split('#').next().unwrap_or_default().trim();
+    match value {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => serde_json::from_str::<serde_json::Number>(value).ok().map(Value::Number),
+    }
+}
+
+/// Serializes a `Duration` as whole seconds.
+pub mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_secs)
+    }
+}
+
+/// Like [`duration_secs`], but also accepts a human-readable duration such
+/// as `30s`, `5m` or `1h 30m`. A bare number is still seconds.
+pub mod duration_human {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        super::duration_secs::serialize(duration, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Seconds(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Seconds(seconds) => Ok(Duration::from_secs(seconds)),
+            Raw::Text(text) => {
+                let text = text.trim();
+                match text.parse::<u64>() {
+                    Ok(seconds) => Ok(Duration::from_secs(seconds)),
+                    Err(_) => humantime::parse_duration(text)
+                        .map_err(|e| de::Error::custom(format!("invalid duration '{}': {}", text, e))),
+                }
+            }
+        }
+    }
+}
+
+/// An `IpAddr` whose parse error names the rejected value.
+pub mod ip_addr {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::net::IpAddr;
+
+    pub fn serialize<S: Serializer>(addr: &IpAddr, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(addr)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpAddr, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.trim().parse().map_err(|_| {
+            de::Error::custom(format!("invalid IP address '{}': expected e.g. 127.0.0.1, 0.0.0.0 or ::", text))
+        })
+    }
+}
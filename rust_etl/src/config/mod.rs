@@ -1,3 +1,5 @@
 pub mod app_config;
+pub mod loader;
+pub mod template;
 
 
@@ -0,0 +1,103 @@
+//! Commented config file template generated from [`AppConfig`]: every key
+//! with its description, default and current value, in the flat
+//! `KEY = value` format read through `CONFIG_FILE`.
+
+use crate::config::app_config::AppConfig;
+use crate::utils::redact;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+// `CONFIG_DOCS`: each key and the first sentence of its field's doc comment,
+// extracted from `AppConfig` by build.rs
+include!(concat!(env!("OUT_DIR"), "/config_docs.rs"));
+
+/// Renders the template with `config`'s values. Secrets, including
+/// passwords in connection URLs, are redacted and commented out, so loading
+/// the file as is never sets a placeholder key.
+pub fn render(config: &AppConfig) -> Result<String> {
+    let current = to_map(config)?;
+    let defaults = to_map(&AppConfig::default())?;
+
+    let mut keys: Vec<&str> = CONFIG_DOCS.iter().map(|(key, _)| *key).collect();
+    let mut undocumented: Vec<&str> = current
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !keys.contains(key))
+        .collect();
+    undocumented.sort_unstable();
+    keys.extend(undocumented);
+
+    let mut out = String::from(
+        "# rust_etl configuration, generated by `rust_etl dump-config-template`.\n\
+         # Load it with CONFIG_FILE=<path>; environment variables still take precedence.\n\
+         # Values are the settings in effect when it was generated. Secrets are\n\
+         # redacted and commented out: fill them in or keep them in the environment.\n",
+    );
+
+    for key in keys {
+        let Some(value) = current.get(key) else {
+            continue;
+        };
+        out.push('\n');
+        if let Some((_, doc)) = CONFIG_DOCS.iter().find(|(name, _)| *name == key) {
+            if !doc.is_empty() {
+                out.push_str(&format!("# {}\n", doc));
+            }
+        }
+        if let Some(default) = defaults.get(key) {
+            out.push_str(&format!("# Default: {}\n", display(key, default)));
+        }
+
+        let masked = mask_urls(value);
+        let line = match value {
+            Value::Null => format!("# {} =", key),
+            _ if is_secret(key) => format!("# {} = {}", key, redacted(value)),
+            _ if masked != *value => format!("# {} = {}", key, masked),
+            // The file format has no tables; objects are only read from the environment
+            Value::Object(_) => format!("# {} = '{}' (environment only)", key, value),
+            _ => format!("{} = {}", key, value),
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+fn to_map(config: &AppConfig) -> Result<Map<String, Value>> {
+    match serde_json::to_value(config).context("Failed to serialize config")? {
+        Value::Object(map) => Ok(map),
+        _ => Err(anyhow::anyhow!("config must serialize to an object")),
+    }
+}
+
+/// Keys whose values must not be written out: passwords, API keys and the
+/// request signing secret.
+fn is_secret(key: &str) -> bool {
+    ["PASSWORD", "SECRET", "_KEY", "_KEYS"].iter().any(|marker| key.ends_with(marker))
+}
+
+fn redacted(value: &Value) -> Value {
+    match value {
+        Value::String(text) if !text.is_empty() => Value::String(redact::MASK.to_string()),
+        Value::Array(items) => Value::Array(items.iter().map(redacted).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Masks passwords in connection URLs, e.g. in `DATABASE_SINK_URLS`.
+fn mask_urls(value: &Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(redact::mask_url(text)),
+        Value::Array(items) => Value::Array(items.iter().map(mask_urls).collect()),
+        other => other.clone(),
+    }
+}
+
+fn display(key: &str, default: &Value) -> String {
+    match default {
+        Value::Null => "unset".to_string(),
+        _ if is_secret(key) => redacted(default).to_string(),
+        _ => default.to_string(),
+    }
+}
@@ -0,0 +1,399 @@
+//! The collection loop as a library: [`run_etl`] for embedding it in
+//! another application, and [`Etl`] for finer control, as used by the
+//! `rust_etl` binary.
+
+use crate::cli;
+use crate::config::app_config::AppConfig;
+use crate::models::units::{Units, UnitsMismatchAction};
+use crate::services::collect_trigger::{CollectTrigger, CycleReport};
+use crate::services::collector::{Collector, CycleResult};
+use crate::services::database::DatabaseService;
+use crate::services::geolocation;
+use crate::services::insert_writer::{InsertWriter, InsertedObservation, QueueHealth};
+use crate::services::metrics::Metrics;
+use crate::services::runtime_state::RuntimeState;
+use crate::sinks;
+use anyhow::{Context, Result};
+use futures_util::Stream;
+use log::{error, info, warn};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Runs the collection loop for `config` in a background task and yields
+/// each cycle's result. Normalizes `config` as [`AppConfig::from_env`]
+/// does, checks it with [`AppConfig::validate`] and resolves the location
+/// with `AUTO_LOCATE` first, then connects to the database (retrying per
+/// `DB_CONNECT_RETRIES`) and applies migrations with `AUTO_MIGRATE` before
+/// returning, so setup errors are reported here rather than in the stream.
+///
+/// The stream ends once `shutdown` completes, e.g. a `oneshot::Receiver`
+/// or `CancellationToken::cancelled()`, after queued rows are written and
+/// the sinks closed. When collection stops on its own instead, after a
+/// cycle with a `fatal` error or once `MAX_CONSECUTIVE_FAILURES` is
+/// reached, the last item is that error. Collection stops when the stream
+/// is dropped, and waits for a consumer that falls behind.
+pub async fn run_etl<S>(mut config: AppConfig, shutdown: S) -> Result<impl Stream<Item = Result<CycleResult>>>
+where
+    S: Future + Send + 'static,
+{
+    let (database, metrics) = prepare(&mut config).await?;
+    let etl = Etl::new(&config, database, metrics).await?;
+
+    let (results_tx, results_rx) = mpsc::channel(1);
+    let (stopped_tx, stopped_rx) = oneshot::channel();
+    // Manual triggers only come from the binary's HTTP server
+    let (_, triggers) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let outcome = etl.run(shutdown, triggers, Some(results_tx)).await;
+        if let Err(e) = &outcome {
+            warn!("⚠️  Collection stopped: {:#}", e);
+        }
+        let _ = stopped_tx.send(outcome);
+    });
+
+    Ok(futures_util::stream::unfold(
+        (results_rx, Some(stopped_rx)),
+        |(mut results, stopped)| async move {
+            if let Some(result) = results.recv().await {
+                return Some((Ok(result), (results, stopped)));
+            }
+            match stopped?.await {
+                Ok(Err(e)) => Some((Err(e), (results, None))),
+                _ => None,
+            }
+        },
+    ))
+}
+
+/// The setup shared by [`run_etl`] and the Lambda handler: normalizes and
+/// checks `config`, resolves the location with `AUTO_LOCATE`, prepares the
+/// database and sets up metrics.
+pub async fn prepare(config: &mut AppConfig) -> Result<(Arc<DatabaseService>, Arc<Metrics>)> {
+    config.normalize();
+    config.validate().context("Invalid configuration")?;
+    geolocation::apply(config).await?;
+    let database = prepare_database(config).await?;
+    let metrics = Arc::new(Metrics::from_config(config).context("Failed to initialize metrics")?);
+    Ok((database, metrics))
+}
+
+/// Connects to the database, applies pending migrations with
+/// `AUTO_MIGRATE`, and checks the stored rows against the configuration:
+/// refusing mixed units unless `UNITS_MISMATCH_ACTION=warn`, and logging how
+/// stale each city's data is.
+pub async fn prepare_database(config: &AppConfig) -> Result<Arc<DatabaseService>> {
+    let database = Arc::new(
+        DatabaseService::connect_with_retry(&config.database_url, &config.db_connect_retry_policy())
+            .await
+            .context("Failed to initialize database connection")?,
+    );
+
+    database.health_check()
+        .await
+        .context("Database health check failed")?;
+
+    if config.auto_migrate {
+        cli::migrate::apply(&database).await?;
+    }
+
+    check_stored_units(&database, config).await?;
+    for city in &config.cities {
+        report_staleness(&database, config, city).await;
+    }
+
+    Ok(database)
+}
+
+/// A collector wired to its insert writer and sinks, ready to
+/// [`run`](Self::run).
+pub struct Etl {
+    config: AppConfig,
+    metrics: Arc<Metrics>,
+    collector: Collector,
+    writer_task: JoinHandle<()>,
+    writer_health: Arc<QueueHealth>,
+    inserted: broadcast::Sender<InsertedObservation>,
+}
+
+impl Etl {
+    /// Opens the configured sinks and starts the insert writer.
+    pub async fn new(config: &AppConfig, database: Arc<DatabaseService>, metrics: Arc<Metrics>) -> Result<Self> {
+        let sinks = sinks::from_config(config)
+            .context("Failed to initialize output sinks")?;
+        for sink in &sinks {
+            info!("   📤 Output sink: {} ({:?})", sink.name(), config.sink_format);
+        }
+
+        // Confirmed inserts, fanned out to live subscribers such as /events; a
+        // subscriber more than this many events behind skips ahead
+        let (inserted, _) = broadcast::channel(256);
+        let (insert_writer, writer_task) =
+            InsertWriter::spawn(Arc::clone(&database), Arc::clone(&metrics), inserted.clone(), config);
+        let writer_health = insert_writer.health();
+        let mut collector = Collector::new(config, database, Arc::clone(&metrics), insert_writer, sinks)?;
+        if let Some(path) = &config.state_file {
+            match RuntimeState::load(Path::new(path)).await {
+                Ok(Some(state)) => {
+                    info!("   💾 Restoring state saved at {} from {}", saved_at(&state), path);
+                    collector.restore(state);
+                }
+                Ok(None) => info!("   💾 No saved state in {} yet", path),
+                Err(e) => warn!("⚠️  Not restoring saved state: {:#}", e),
+            }
+        }
+        if config.diff_only_insert {
+            collector.seed_change_detector().await;
+            info!("   🔍 Diff-only insert mode enabled");
+        }
+
+        Ok(Self {
+            config: config.clone(),
+            metrics,
+            collector,
+            writer_task,
+            writer_health,
+            inserted,
+        })
+    }
+
+    /// Observations as the insert writer confirms them.
+    pub fn inserted(&self) -> broadcast::Sender<InsertedObservation> {
+        self.inserted.clone()
+    }
+
+    /// Insert queue depth and lag, as reported by `GET /health`.
+    pub fn writer_health(&self) -> Arc<QueueHealth> {
+        Arc::clone(&self.writer_health)
+    }
+
+    /// Runs a cycle every `ETL_INTERVAL`, or early on a `triggers` request,
+    /// until `shutdown` completes, then writes the queued rows and closes the
+    /// sinks. Each cycle's result is sent to `results` when given. Returns
+    /// `Err` when collection had to stop: a fatal error, or
+    /// `MAX_CONSECUTIVE_FAILURES` cycles in a row without a successful fetch.
+    pub async fn run<S: Future>(
+        mut self,
+        shutdown: S,
+        mut triggers: mpsc::Receiver<CollectTrigger>,
+        results: Option<mpsc::Sender<CycleResult>>,
+    ) -> Result<()> {
+        tokio::pin!(shutdown);
+        let config = &self.config;
+        let mut exit_error = None;
+        let mut pending_triggers: Vec<CollectTrigger> = Vec::new();
+        let mut consecutive_failures = 0u32;
+
+        // Cycles start on fixed boundaries from the first one, however long each
+        // takes. A cycle that overruns the interval skips the boundaries it missed
+        let mut schedule = tokio::time::interval(config.interval);
+        schedule.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        schedule.tick().await;
+
+        loop {
+            let cycle_started = Instant::now();
+            let mut result = tokio::select! {
+                // One collection cycle; yields an error when collection must stop
+                result = self.collector.run_cycle() => result,
+                _ = &mut shutdown => break,
+            };
+
+            result.report = CycleReport { finished_at: chrono::Utc::now(), ..result.report };
+            let cycle_time = cycle_started.elapsed();
+            self.metrics.timing("cycle.duration", cycle_time, &[]);
+            if cycle_time >= config.interval {
+                self.metrics.incr("cycle.overrun", &[]);
+                warn!(
+                    "🐢 Collection cycle took {:.1}s, longer than the {} interval; skipping the missed run(s)",
+                    cycle_time.as_secs_f64(),
+                    humantime::format_duration(config.interval)
+                );
+            }
+            self.save_state().await;
+            for trigger in pending_triggers.drain(..) {
+                let _ = trigger.respond.send(result.report.clone());
+            }
+
+            let next_run = result.next_run;
+            let all_failed = result.report.fetched == 0 && result.report.failed > 0;
+            let mut fatal = result.fatal.take();
+            if let Some(results) = &results {
+                // The consumer gets the error itself; the loop keeps its message
+                let stop = fatal.as_ref().map(|e| anyhow::anyhow!("{:#}", e));
+                result.fatal = fatal;
+                fatal = stop;
+                if results.send(result).await.is_err() && fatal.is_none() {
+                    info!("🛑 Result stream dropped; stopping collection");
+                    break;
+                }
+            }
+
+            if let Some(e) = fatal {
+                error!("❌ Stopping collection: {}", e);
+                exit_error = Some(e);
+                break;
+            }
+
+            if all_failed {
+                consecutive_failures += 1;
+                if config.max_consecutive_failures > 0 && consecutive_failures >= config.max_consecutive_failures {
+                    error!(
+                        "❌ Stopping collection: {} consecutive cycles failed for every city (MAX_CONSECUTIVE_FAILURES={})",
+                        consecutive_failures, config.max_consecutive_failures
+                    );
+                    exit_error = Some(anyhow::anyhow!("{} consecutive cycles failed", consecutive_failures));
+                    break;
+                }
+            } else {
+                consecutive_failures = 0;
+            }
+
+            // A quota cooldown pushes the schedule back; it resumes from there
+            if next_run > config.interval {
+                schedule.reset_after(next_run);
+            }
+
+            // Wait for the next cycle, or start it early on a manual trigger
+            tokio::select! {
+                _ = schedule.tick() => {}
+                Some(trigger) = triggers.recv() => {
+                    info!("▶️  Manual collection triggered");
+                    pending_triggers.push(trigger);
+                    while let Ok(trigger) = triggers.try_recv() {
+                        pending_triggers.push(trigger);
+                    }
+                }
+                _ = &mut shutdown => break,
+            }
+        }
+
+        self.finish().await;
+
+        match exit_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs a single cycle, then writes its queued rows and closes the sinks,
+    /// e.g. for one scheduled invocation. A `fatal` error is left in the
+    /// result.
+    pub async fn run_once(mut self) -> CycleResult {
+        let cycle_started = Instant::now();
+        let mut result = self.collector.run_cycle().await;
+        result.report = CycleReport { finished_at: chrono::Utc::now(), ..result.report };
+        self.metrics.timing("cycle.duration", cycle_started.elapsed(), &[]);
+        self.finish().await;
+        result
+    }
+
+    /// Saves the state, waits for the queued rows to be written and closes
+    /// the sinks.
+    async fn finish(self) {
+        self.save_state().await;
+        let queued = self.collector.pending_inserts();
+        if queued > 0 {
+            info!("⏳ Writing {} queued observation(s) before exit", queued);
+        }
+        let (insert_writer, sinks) = self.collector.into_outputs();
+        drop(insert_writer);
+        if let Err(e) = self.writer_task.await {
+            error!("❌ Insert writer task failed: {}", e);
+        }
+
+        sinks::close_all(&sinks, self.config.sink_close_timeout).await;
+    }
+
+    /// Writes the collector's state to `STATE_FILE`, if set.
+    async fn save_state(&self) {
+        let Some(path) = &self.config.state_file else { return };
+        if let Err(e) = self.collector.snapshot().save(Path::new(path)).await {
+            self.metrics.incr("state.save_failure", &[]);
+            warn!("⚠️  Could not save state: {:#}", e);
+        }
+    }
+}
+
+fn saved_at(state: &RuntimeState) -> String {
+    chrono::DateTime::from_timestamp(state.saved_at, 0)
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_else(|| state.saved_at.to_string())
+}
+
+/// Compares `UNITS` with the units of each configured city's latest stored
+/// row, so a changed setting doesn't silently mix °C and °F in one column.
+/// Cities no longer in `CITIES` are not checked.
+async fn check_stored_units(database: &DatabaseService, config: &AppConfig) -> Result<()> {
+    let stored = match database.latest_units_by_city(&config.cities).await {
+        Ok(stored) => stored,
+        Err(e) => {
+            warn!("⚠️  Could not check stored units: {:#}", e);
+            return Ok(());
+        }
+    };
+
+    let mismatched: Vec<String> = stored
+        .into_iter()
+        .filter_map(|(city, units)| {
+            let units = units.as_deref().and_then(Units::parse).unwrap_or(Units::Metric);
+            (units != config.units).then(|| format!("{} ({})", city, units))
+        })
+        .collect();
+
+    if mismatched.is_empty() {
+        return Ok(());
+    }
+
+    let message = format!(
+        "UNITS={} but the latest stored rows use other units: {}",
+        config.units,
+        mismatched.join(", ")
+    );
+    match config.units_mismatch_action {
+        UnitsMismatchAction::Refuse => Err(anyhow::anyhow!(
+            "{}; set UNITS to match or UNITS_MISMATCH_ACTION=warn to continue",
+            message
+        )),
+        UnitsMismatchAction::Warn => {
+            warn!("⚠️  {}", message);
+            warn!("⚠️  New rows will be tagged with units={} in the units column", config.units);
+            Ok(())
+        }
+    }
+}
+
+/// Logs how old the newest stored observation is, warning past
+/// `STALE_DATA_THRESHOLD_SECONDS`, so downtime is visible right at boot.
+async fn report_staleness(database: &DatabaseService, config: &AppConfig, city: &str) {
+    let latest = match database.get_latest_weather(city).await {
+        Ok(Some(latest)) => latest,
+        Ok(None) => {
+            info!("   🕰️  No stored observations for {} yet", city);
+            return;
+        }
+        Err(e) => {
+            warn!("⚠️  Could not check data staleness for {}: {:#}", city, e);
+            return;
+        }
+    };
+
+    let age = Duration::from_secs((chrono::Utc::now().timestamp() - latest.timestamp).max(0) as u64);
+    let hours = age.as_secs() / 3600;
+    let minutes = age.as_secs() % 3600 / 60;
+
+    if age > config.stale_data_threshold {
+        warn!(
+            "⚠️  Newest stored observation for {} is {}h {}m old (threshold {}s); collection was down or failing",
+            city,
+            hours,
+            minutes,
+            config.stale_data_threshold.as_secs()
+        );
+    } else {
+        info!("   🕰️  Newest stored observation for {} is {}h {}m old", city, hours, minutes);
+    }
+}
@@ -0,0 +1,94 @@
+//! One collection cycle per invocation, for running on AWS Lambda from a
+//! schedule such as an EventBridge rule. Served by the `lambda` binary.
+//!
+//! The database connection is made once per cold start and shared by warm
+//! invocations; running state such as the temperature average only carries
+//! over between invocations through `STATE_FILE` (e.g. under `/tmp`).
+
+use crate::config::app_config::AppConfig;
+use crate::etl::{self, Etl};
+use crate::services::collect_trigger::CycleReport;
+use crate::services::collector::{CityOutcome, CityStatus, CycleResult};
+use crate::services::database::DatabaseService;
+use crate::services::metrics::Metrics;
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// What one invocation did, returned as its JSON response.
+#[derive(Debug, Serialize)]
+pub struct CycleSummary {
+    #[serde(flatten)]
+    pub report: CycleReport,
+    pub outcomes: Vec<CitySummary>,
+}
+
+/// One city's [`CityOutcome`].
+#[derive(Debug, Serialize)]
+pub struct CitySummary {
+    pub city: String,
+    /// `queued`, `skipped_sampled`, `skipped_unchanged`, `not_queued`,
+    /// `fetch_failed` or `outside_collect_hours`.
+    pub status: &'static str,
+    /// Why the city was not queued or not fetched.
+    pub error: Option<String>,
+    pub fetch_ms: u64,
+    pub failed_sinks: Vec<String>,
+}
+
+impl From<CityOutcome> for CitySummary {
+    fn from(outcome: CityOutcome) -> Self {
+        let (status, error) = match outcome.status {
+            CityStatus::Queued => ("queued", None),
+            CityStatus::SkippedSampled => ("skipped_sampled", None),
+            CityStatus::SkippedUnchanged => ("skipped_unchanged", None),
+            CityStatus::NotQueued(reason) => ("not_queued", Some(reason)),
+            CityStatus::FetchFailed(reason) => ("fetch_failed", Some(reason)),
+            CityStatus::OutsideCollectHours => ("outside_collect_hours", None),
+        };
+        Self {
+            city: outcome.city,
+            status,
+            error,
+            fetch_ms: outcome.fetch_duration.as_millis() as u64,
+            failed_sinks: outcome.failed_sinks,
+        }
+    }
+}
+
+/// Runs a cycle per invocation with the configuration it was created with.
+pub struct Handler {
+    config: AppConfig,
+    database: Arc<DatabaseService>,
+    metrics: Arc<Metrics>,
+}
+
+impl Handler {
+    /// Checks `config` and prepares the database as
+    /// [`run_etl`](crate::etl::run_etl) does.
+    pub async fn new(mut config: AppConfig) -> Result<Self> {
+        let (database, metrics) = etl::prepare(&mut config).await?;
+        Ok(Self {
+            config,
+            database,
+            metrics,
+        })
+    }
+
+    /// Runs one cycle and waits for its rows to be written. A fatal error,
+    /// such as a rejected API key, fails the invocation once the rows
+    /// collected before it are written.
+    pub async fn invoke(&self) -> Result<CycleSummary> {
+        let etl = Etl::new(&self.config, Arc::clone(&self.database), Arc::clone(&self.metrics)).await?;
+        let CycleResult {
+            fatal, report, cities, ..
+        } = etl.run_once().await;
+        if let Some(e) = fatal {
+            return Err(e);
+        }
+        Ok(CycleSummary {
+            report,
+            outcomes: cities.into_iter().map(CitySummary::from).collect(),
+        })
+    }
+}
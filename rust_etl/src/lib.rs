@@ -1,5 +1,12 @@
+pub mod cli;
+pub mod etl;
+#[cfg(feature = "lambda")]
+pub mod lambda;
 pub mod models;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod services;
+pub mod sinks;
 pub mod config;
 pub mod utils;
 
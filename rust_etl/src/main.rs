@@ -1,104 +1,220 @@
-mod models;
-mod services;
-mod config;
-mod utils;
-
-use crate::{
+use rust_etl::{
+    cli::{self, Command},
     config::app_config::AppConfig,
-    services::{database::DatabaseService, weather_service::WeatherService},
-    utils::{logging, setup_panic_hook},
+    etl::{self, Etl},
+    services::{collect_trigger::CollectTrigger, geolocation, metrics::Metrics, provider::ProviderKind},
+    utils::{logging, redact, setup_panic_hook, PanicBehavior},
 };
 use anyhow::{Result, Context};
-use log::{info, warn, error};
+use log::{info, warn};
+use rand::Rng;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::signal::unix::{signal, SignalKind};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::mpsc;
 use tokio::time::sleep;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    setup_panic_hook();
+    // Exit on panic until the configured behavior is known
+    setup_panic_hook(PanicBehavior::Exit);
+
+    let command = match Command::parse(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
 
     // Initialize logging
     logging::init_logger();
 
+    match command {
+        Command::Run => {}
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Command::Doctor => {
+            let healthy = cli::doctor::run().await;
+            std::process::exit(if healthy { 0 } else { 1 });
+        }
+        Command::Migrate(mode) => {
+            let config = AppConfig::from_env()
+                .context("Failed to load application configuration")?;
+            return cli::migrate::run(&config, mode).await;
+        }
+        Command::BackfillComputed(args) => {
+            let config = AppConfig::from_env()
+                .context("Failed to load application configuration")?;
+            return cli::backfill::run(&config, &args).await;
+        }
+        Command::Coverage(args) => {
+            let config = AppConfig::from_env()
+                .context("Failed to load application configuration")?;
+            return cli::coverage::run(&config, &args).await;
+        }
+        Command::DumpConfigTemplate(output) => {
+            let config = AppConfig::from_env()
+                .context("Failed to load application configuration")?;
+            return cli::config_template::run(&config, output.as_deref());
+        }
+    }
+
     info!("🚀 Starting Montreal Weather ETL Service v1.0.0");
 
     // Load configuration
-    let config = AppConfig::from_env()
+    let mut config = AppConfig::from_env()
         .context("Failed to load application configuration")?;
+    setup_panic_hook(config.panic_behavior);
+
+    // Registered before the slow startup steps so a shutdown requested while
+    // waiting for the database (e.g. a pod cancelled mid-start) ends promptly
+    let mut signals = Signals::register()?;
+
+    let Some(located) = signals.unless_received("location lookup", geolocation::apply(&mut config)).await else {
+        return Ok(());
+    };
+    located?;
 
     info!("⚙️  Configuration loaded:");
-    info!("   📍 City: {}", config.city);
-    info!("   ⏱️  Collection interval: {} seconds", config.interval_seconds);
+    info!("   📍 Cities: {}", config.cities.join(", "));
+    info!("   🗄️  Database: {}", redact::mask_url(&config.database_url));
+    info!("   📏 Units: {}", config.units);
+    info!("   ⏱️  Collection interval: {}", humantime::format_duration(config.interval));
     info!("   📊 Log level: {}", config.log_level);
+    match config.fallback_provider {
+        Some(fallback) => info!("   🌐 Provider: {} (fallback: {})", config.weather_provider, fallback),
+        None => info!("   🌐 Provider: {}", config.weather_provider),
+    }
+    if config.uses_provider(ProviderKind::OpenWeatherMap) {
+        let tier = config.openweather_tier;
+        info!("   🎫 OpenWeatherMap tier: {} ({})", tier, tier.endpoint());
+        let unavailable = tier.unavailable_fields(&config);
+        if !unavailable.is_empty() {
+            info!("      Not populated on this tier: {}", unavailable.join(", "));
+        }
+    }
+    if config.insecure_skip_tls_verify {
+        warn!("🚨 INSECURE_SKIP_TLS_VERIFY is enabled: TLS certificates from the weather APIs are NOT verified.");
+        warn!("🚨 Responses can be intercepted or forged. Use this only for local testing, never in production.");
+    }
+    if let Some(addr) = &config.statsd_addr {
+        info!("   📈 StatsD: {} (prefix '{}')", addr, config.statsd_prefix);
+    }
 
     // Initialize services
-    let database = DatabaseService::new(&config.database_url)
-        .await
-        .context("Failed to initialize database connection")?;
+    let Some(database) = signals.unless_received("database startup", etl::prepare_database(&config)).await else {
+        return Ok(());
+    };
+    let database = database?;
 
-    let weather_service = WeatherService::new(config.api_key.clone());
+    let metrics = Arc::new(Metrics::from_config(&config)
+        .context("Failed to initialize metrics")?);
 
-    // Health check
-    database.health_check()
-        .await
-        .context("Database health check failed")?;
+    if config.store_every_n > 1 {
+        info!("   🧮 Storing every {} successful fetches", config.store_every_n);
+    }
+    if !config.storage_resolution.is_zero() {
+        info!(
+            "   🧮 Storing at most one observation per {} per city",
+            humantime::format_duration(config.storage_resolution)
+        );
+    }
+
+    let Some(etl) = signals.unless_received("startup", Etl::new(&config, Arc::clone(&database), metrics)).await else {
+        return Ok(());
+    };
+    let etl = etl?;
+
+    // Manual collection requests from POST /collect
+    let (collect_tx, collect_rx) = mpsc::channel::<CollectTrigger>(16);
+
+    #[cfg(feature = "server")]
+    let http_task = if config.http_server {
+        let state = Arc::new(rust_etl::server::ServerState::new(
+            etl.inserted(),
+            collect_tx.clone(),
+            config.collect_idempotency_window,
+            Arc::clone(&database),
+            config.stale_data_threshold,
+            etl.writer_health(),
+        ));
+        Some(rust_etl::server::spawn(config.http_bind_addr(), state)
+            .await
+            .context("Failed to start HTTP server")?)
+    } else {
+        None
+    };
+    #[cfg(not(feature = "server"))]
+    if config.http_server {
+        warn!("⚠️  HTTP_SERVER is set but this build has no `server` feature; HTTP endpoints are disabled");
+    }
+    drop(collect_tx);
 
     info!("✅ All services initialized successfully");
     info!("🔄 Starting weather data collection loop...");
 
-    // Setup graceful shutdown
-    let mut sigterm = signal(SignalKind::terminate())
-        .context("Failed to register SIGTERM handler")?;
+    // Spread the first fetch of simultaneously started replicas
+    if !config.startup_splay.is_zero() {
+        let splay_ms = rand::thread_rng().gen_range(0..=config.startup_splay.as_millis() as u64);
+        info!("⏳ Startup splay: delaying first collection by {:.1}s", splay_ms as f64 / 1000.0);
 
-    let mut sigint = signal(SignalKind::interrupt())
-        .context("Failed to register SIGINT handler")?;
-
-    loop {
-        tokio::select! {
-            // Main ETL loop
-            _ = async {
-                match weather_service.fetch_weather(&config.city).await {
-                    Ok(weather_data) => {
-                        match database.insert_weather_data(&weather_data).await {
-                            Ok(_) => {
-                                info!(
-                                    "✅ Weather data inserted: {} - 🌡️ {:.1}°C (feels {:.1}°C), 💧 {}%, 🌬️ {:.1}km/h, ☁️ {} ({})",
-                                    weather_data.city.as_deref().unwrap_or("Unknown"),
-                                    weather_data.temperature,
-                                    weather_data.feels_like.unwrap_or(0.0),
-                                    weather_data.humidity,
-                                    weather_data.wind_speed,
-                                    weather_data.weather_main.as_deref().unwrap_or("Unknown"),
-                                    weather_data.weather_description.as_deref().unwrap_or("Unknown")
-                                );
-                            }
-                            Err(e) => {
-                                error!("❌ Database insert failed: {}", e);
-                            }
-                }
-            }
-                    Err(e) => {
-                        warn!("⚠️  Failed to fetch weather data: {}", e);
-                        warn!("   Will retry in {} seconds...", config.interval_seconds);
+        if signals.unless_received("startup splay", sleep(Duration::from_millis(splay_ms))).await.is_none() {
+            return Ok(());
         }
     }
 
-                sleep(Duration::from_secs(config.interval_seconds)).await;
-            } => {}
+    let shutdown = async {
+        let name = signals.recv().await;
+        info!("🛑 Received {} signal", name);
+    };
+    let result = etl.run(shutdown, collect_rx, None).await;
 
-            // Handle shutdown signals
-            _ = sigterm.recv() => {
-                info!("🛑 Received SIGTERM signal");
-                break;
-            }
-            _ = sigint.recv() => {
-                info!("🛑 Received SIGINT signal");
-                break;
-            }
-        }
+    #[cfg(feature = "server")]
+    if let Some(http_task) = http_task {
+        http_task.abort();
     }
 
+    result?;
+
     info!("👋 Montreal Weather ETL Service stopped gracefully");
     Ok(())
 }
+
+/// SIGTERM and SIGINT, either of which requests a graceful shutdown.
+struct Signals {
+    sigterm: Signal,
+    sigint: Signal,
+}
+
+impl Signals {
+    fn register() -> Result<Self> {
+        Ok(Self {
+            sigterm: signal(SignalKind::terminate()).context("Failed to register SIGTERM handler")?,
+            sigint: signal(SignalKind::interrupt()).context("Failed to register SIGINT handler")?,
+        })
+    }
+
+    /// Waits for either signal and returns its name.
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.sigterm.recv() => "SIGTERM",
+            _ = self.sigint.recv() => "SIGINT",
+        }
+    }
+
+    /// Runs `step`, or abandons it and returns `None` when a signal arrives
+    /// first.
+    async fn unless_received<F: Future>(&mut self, name: &str, step: F) -> Option<F::Output> {
+        tokio::select! {
+            output = step => Some(output),
+            signal = self.recv() => {
+                info!("🛑 Received {} signal during {}; stopping", signal, name);
+                None
+            }
+        }
+    }
+}
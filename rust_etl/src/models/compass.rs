@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Languages with localized compass abbreviations, selected with `LANG`.
+/// Accepts locale strings such as `fr_CA.UTF-8`; anything unrecognized
+/// (including `C` and `POSIX`) falls back to English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Language {
+    En,
+    Fr,
+}
+
+impl Language {
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Fr => "fr",
+        }
+    }
+
+    /// Reads the language part of a locale (`fr`, `fr_CA`, `fr-CA.UTF-8`).
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .trim()
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "fr" => Language::Fr,
+            _ => Language::En,
+        }
+    }
+
+    fn points(self) -> &'static [&'static str; 16] {
+        match self {
+            Language::En => &[
+                "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+                "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+            ],
+            // West is "Ouest" in French
+            Language::Fr => &[
+                "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+                "S", "SSO", "SO", "OSO", "O", "ONO", "NO", "NNO",
+            ],
+        }
+    }
+}
+
+impl From<String> for Language {
+    fn from(locale: String) -> Self {
+        Language::from_locale(&locale)
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        language.code().to_string()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// 16-point compass abbreviation for a meteorological wind direction in
+/// degrees (the direction the wind blows from), e.g. 225 → "SW" / "SO".
+pub fn wind_compass(degrees: f64, language: Language) -> &'static str {
+    let index = (degrees.rem_euclid(360.0) / 22.5).round() as usize % 16;
+    language.points()[index]
+}
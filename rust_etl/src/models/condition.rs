@@ -0,0 +1,105 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// OpenWeatherMap condition group, the values of `weather_main`. Parsing is
+/// case-insensitive and never fails: anything unrecognized is kept as
+/// `Other`, so a new group from the API is stored rather than dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WeatherCondition {
+    Clear,
+    Clouds,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+    Mist,
+    Smoke,
+    Haze,
+    Dust,
+    Fog,
+    Sand,
+    Ash,
+    Squall,
+    Tornado,
+    Other(String),
+}
+
+impl WeatherCondition {
+    /// Canonical spelling, as OpenWeatherMap returns it.
+    pub fn as_str(&self) -> &str {
+        match self {
+            WeatherCondition::Clear => "Clear",
+            WeatherCondition::Clouds => "Clouds",
+            WeatherCondition::Drizzle => "Drizzle",
+            WeatherCondition::Rain => "Rain",
+            WeatherCondition::Snow => "Snow",
+            WeatherCondition::Thunderstorm => "Thunderstorm",
+            WeatherCondition::Mist => "Mist",
+            WeatherCondition::Smoke => "Smoke",
+            WeatherCondition::Haze => "Haze",
+            WeatherCondition::Dust => "Dust",
+            WeatherCondition::Fog => "Fog",
+            WeatherCondition::Sand => "Sand",
+            WeatherCondition::Ash => "Ash",
+            WeatherCondition::Squall => "Squall",
+            WeatherCondition::Tornado => "Tornado",
+            WeatherCondition::Other(value) => value,
+        }
+    }
+
+    /// Maps a WeatherAPI.com condition code onto the OpenWeatherMap group,
+    /// keeping `text` for codes it doesn't know. Sleet and ice pellets are
+    /// Snow, as in OpenWeatherMap's 6xx group.
+    pub fn from_weatherapi(code: i32, text: &str) -> Self {
+        match code {
+            1000 => WeatherCondition::Clear,
+            1003 | 1006 | 1009 => WeatherCondition::Clouds,
+            1030 => WeatherCondition::Mist,
+            1135 | 1147 => WeatherCondition::Fog,
+            1072 | 1150..=1171 => WeatherCondition::Drizzle,
+            1063 | 1180..=1201 | 1240..=1246 => WeatherCondition::Rain,
+            1066 | 1069 | 1114 | 1117 | 1204..=1237 | 1249..=1264 => WeatherCondition::Snow,
+            1087 | 1273..=1282 => WeatherCondition::Thunderstorm,
+            _ => WeatherCondition::Other(text.to_string()),
+        }
+    }
+}
+
+impl From<&str> for WeatherCondition {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "clear" => WeatherCondition::Clear,
+            "clouds" => WeatherCondition::Clouds,
+            "drizzle" => WeatherCondition::Drizzle,
+            "rain" => WeatherCondition::Rain,
+            "snow" => WeatherCondition::Snow,
+            "thunderstorm" => WeatherCondition::Thunderstorm,
+            "mist" => WeatherCondition::Mist,
+            "smoke" => WeatherCondition::Smoke,
+            "haze" => WeatherCondition::Haze,
+            "dust" => WeatherCondition::Dust,
+            "fog" => WeatherCondition::Fog,
+            "sand" => WeatherCondition::Sand,
+            "ash" => WeatherCondition::Ash,
+            "squall" => WeatherCondition::Squall,
+            "tornado" => WeatherCondition::Tornado,
+            _ => WeatherCondition::Other(value.to_string()),
+        }
+    }
+}
+
+impl FromStr for WeatherCondition {
+    type Err = Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(value))
+    }
+}
+
+impl fmt::Display for WeatherCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,3 +1,4 @@
+pub mod compass;
+pub mod condition;
+pub mod units;
 pub mod weather;
-
-
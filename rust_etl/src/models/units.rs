@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Unit systems supported by OpenWeatherMap's `units` parameter. Stored per
+/// row so readings taken under different settings can be told apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// °C and m/s.
+    Metric,
+    /// °F and mph.
+    Imperial,
+    /// Kelvin and m/s.
+    Standard,
+}
+
+impl Units {
+    pub fn name(self) -> &'static str {
+        match self {
+            Units::Metric => "metric",
+            Units::Imperial => "imperial",
+            Units::Standard => "standard",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "metric" => Some(Units::Metric),
+            "imperial" => Some(Units::Imperial),
+            "standard" => Some(Units::Standard),
+            _ => None,
+        }
+    }
+
+    pub fn temperature_symbol(self) -> &'static str {
+        match self {
+            Units::Metric => "°C",
+            Units::Imperial => "°F",
+            Units::Standard => "K",
+        }
+    }
+
+    pub fn wind_speed_symbol(self) -> &'static str {
+        match self {
+            Units::Imperial => "mph",
+            Units::Metric | Units::Standard => "m/s",
+        }
+    }
+
+    pub fn to_celsius(self, temperature: f64) -> f64 {
+        match self {
+            Units::Metric => temperature,
+            Units::Imperial => (temperature - 32.0) * 5.0 / 9.0,
+            Units::Standard => temperature - 273.15,
+        }
+    }
+
+    pub fn from_celsius(self, celsius: f64) -> f64 {
+        match self {
+            Units::Metric => celsius,
+            Units::Imperial => celsius * 9.0 / 5.0 + 32.0,
+            Units::Standard => celsius + 273.15,
+        }
+    }
+
+    /// Converts a wind speed in this system's unit to m/s.
+    pub fn to_meters_per_second(self, speed: f64) -> f64 {
+        match self {
+            Units::Imperial => speed / 2.236_936,
+            Units::Metric | Units::Standard => speed,
+        }
+    }
+
+    /// Converts a wind speed in m/s to this system's wind speed unit.
+    pub fn from_meters_per_second(self, speed: f64) -> f64 {
+        match self {
+            Units::Imperial => speed * 2.236_936,
+            Units::Metric | Units::Standard => speed,
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What to do at startup when stored rows use different units than `UNITS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitsMismatchAction {
+    Warn,
+    Refuse,
+}
@@ -1,53 +1,796 @@
+use crate::models::condition::WeatherCondition;
+use crate::models::units::Units;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct WeatherData {
     pub city: Option<String>,
     pub temperature: f64,
     pub feels_like: Option<f64>,
     pub humidity: i32,
     pub pressure: Option<i32>,
+    /// OpenWeatherMap `main.sea_level`, in hPa, when the response has it.
+    #[serde(default)]
+    pub sea_level_pressure: Option<i32>,
+    /// OpenWeatherMap `main.grnd_level`: pressure at the station's
+    /// altitude, in hPa, when the response has it.
+    #[serde(default)]
+    pub ground_level_pressure: Option<i32>,
     pub wind_speed: f64,
     pub wind_direction: Option<f64>,
     pub weather_main: Option<String>,
     pub weather_description: Option<String>,
     pub weather_icon: Option<String>,
+    /// OpenWeatherMap condition code, e.g. 500 for light rain. Unlike
+    /// `weather_main` it does not depend on the language; `None` for
+    /// providers with their own code set.
+    pub weather_id: Option<i32>,
     pub timestamp: i64,
     pub timezone: Option<i32>,
+    /// IANA zone name such as `America/Toronto`, when it could be resolved.
+    pub timezone_name: Option<String>,
+    pub uv_index: Option<f64>,
+    /// Dew point from temperature and humidity, see
+    /// [`WeatherData::dew_point`]; one of the [`ComputedColumns`].
+    pub dew_point: Option<f64>,
+    /// Environment Canada wind chill index, in the record's temperature
+    /// unit; see [`WeatherData::wind_chill`].
+    pub wind_chill: Option<f64>,
+    /// Minutes until precipitation starts (positive) or stops (negative)
+    /// within the next hour, from the One Call `minutely` nowcast; see
+    /// [`OneCallResponse::minutes_to_precip`].
+    pub minutes_to_precip: Option<i32>,
+    /// Exponential moving average of `temperature` for the city, see
+    /// `EMA_ALPHA`.
+    pub temperature_ema: Option<f64>,
+    /// Highest temperature for the city since local midnight, including
+    /// this reading; see `DAY_EXTREMES`.
+    pub day_high: Option<f64>,
+    /// Lowest temperature for the city since local midnight, including this
+    /// reading.
+    pub day_low: Option<f64>,
+    /// [`ComfortCategory`] label, e.g. "Comfortable".
+    pub comfort_category: Option<String>,
+    /// [`PressureTrend`] against the previous stored reading for the city.
+    pub pressure_trend: Option<String>,
+    /// Duration of the upstream current-weather request, in milliseconds.
+    pub api_latency_ms: Option<i32>,
+    /// Provider the observation came from, e.g. `openweathermap`.
+    pub source: Option<String>,
+    /// OpenWeatherMap's internal data source for the reading (`base`, e.g.
+    /// "stations"); a change here explains discontinuities in a series.
+    pub station_base: Option<String>,
+    /// `sys.id` of the reporting station.
+    pub station_id: Option<i64>,
+    /// `sys.type` of the reporting station.
+    pub station_type: Option<i32>,
+    /// Unit system of the measurements (`metric`, `imperial` or `standard`).
+    /// Rows stored before this column existed are metric.
+    pub units: Option<String>,
+    /// Set when the timestamp was too far in the future but kept anyway.
+    #[serde(default)]
+    pub timestamp_suspect: bool,
+    /// Labels configured for the city in `CITY_LABELS`, stored as JSONB.
+    #[serde(default)]
+    #[sqlx(json)]
+    pub labels: BTreeMap<String, String>,
+    /// Row of the `locations` table the city resolved to, with
+    /// `LOCATION_IDS`.
+    #[serde(default)]
+    pub location_id: Option<i32>,
+    #[sqlx(skip)]
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl WeatherData {
-    pub fn from_api_response(response: &ApiResponse) -> Self {
+    /// Builds a record from a response requested with `units`.
+    pub fn from_api_response(response: &ApiResponse, units: Units) -> Self {
         let weather = response.weather.first();
         let weather_main = weather.map(|w| w.main.clone()).unwrap_or_else(|| "Unknown".to_string());
         let weather_description = weather.map(|w| w.description.clone()).unwrap_or_else(|| "Unknown".to_string());
         let weather_icon = weather.map(|w| w.icon.clone()).unwrap_or_else(|| "01d".to_string());
 
-        Self {
+        let mut data = Self {
             city: Some(response.name.clone()),
             temperature: response.main.temp,
             feels_like: Some(response.main.feels_like),
             humidity: response.main.humidity,
             pressure: Some(response.main.pressure),
+            sea_level_pressure: response.main.sea_level,
+            ground_level_pressure: response.main.grnd_level,
             wind_speed: response.wind.speed,
             wind_direction: response.wind.deg,
             weather_main: Some(weather_main),
             weather_description: Some(weather_description),
             weather_icon: Some(weather_icon),
+            weather_id: weather.map(|w| w.id),
             timestamp: response.dt,
             timezone: Some(response.timezone),
+            timezone_name: None,
+            uv_index: None,
+            dew_point: None,
+            wind_chill: None,
+            minutes_to_precip: None,
+            temperature_ema: None,
+            day_high: None,
+            day_low: None,
+            comfort_category: None,
+            pressure_trend: None,
+            api_latency_ms: None,
+            source: None,
+            station_base: Some(response.base.clone()).filter(|base| !base.is_empty()),
+            station_id: response.sys.id,
+            station_type: response.sys.sys_type,
+            units: Some(units.name().to_string()),
+            timestamp_suspect: false,
+            labels: BTreeMap::new(),
+            location_id: None,
+            created_at: None,
+        };
+        data.apply_computed(Some(&ComfortThresholds::default()), false);
+        data
+    }
+
+    /// Maps a WeatherAPI.com response onto the OpenWeatherMap-shaped record,
+    /// converting to the OpenWeatherMap units for `units`.
+    pub fn from_weatherapi_response(response: &WeatherApiResponse, units: Units) -> Self {
+        let current = &response.current;
+        let wind_speed = match units {
+            Units::Imperial => current.wind_mph,
+            Units::Metric | Units::Standard => current.wind_kph / 3.6,
+        };
+
+        let mut data = Self {
+            city: Some(response.location.name.clone()),
+            temperature: units.from_celsius(current.temp_c),
+            feels_like: Some(units.from_celsius(current.feelslike_c)),
+            humidity: current.humidity,
+            pressure: Some(current.pressure_mb.round() as i32),
+            sea_level_pressure: None,
+            ground_level_pressure: None,
+            wind_speed,
+            wind_direction: Some(current.wind_degree),
+            weather_main: Some(current.condition.text.clone()),
+            weather_description: Some(current.condition.text.to_lowercase()),
+            weather_icon: Some(current.condition.icon.clone()),
+            weather_id: None,
+            timestamp: current.last_updated_epoch,
+            timezone: None,
+            timezone_name: response.location.tz_id.clone(),
+            uv_index: current.uv,
+            dew_point: None,
+            wind_chill: None,
+            minutes_to_precip: None,
+            temperature_ema: None,
+            day_high: None,
+            day_low: None,
+            comfort_category: None,
+            pressure_trend: None,
+            api_latency_ms: None,
+            source: None,
+            station_base: None,
+            station_id: None,
+            station_type: None,
+            units: Some(units.name().to_string()),
+            timestamp_suspect: false,
+            labels: BTreeMap::new(),
+            location_id: None,
             created_at: None,
+        };
+        data.apply_computed(Some(&ComfortThresholds::default()), false);
+        data
+    }
+
+    /// Builds a record from a One Call response for `city`, requested with
+    /// `units`. One Call has no sea/ground-level pressure or station details;
+    /// the UV index and precipitation nowcast come with it. `None` when the
+    /// response carries no current temperature, humidity or wind speed, as
+    /// when `current` is excluded.
+    pub fn from_onecall_response(response: &OneCallResponse, city: &GeocodedCity, units: Units) -> Option<Self> {
+        let current = &response.current;
+
+        let mut builder = WeatherData::builder()
+            .city(city.name.clone())
+            .temperature(current.temp?)
+            .humidity(current.humidity?)
+            .wind(current.wind_speed?, current.wind_deg)
+            .timestamp(current.dt)
+            .units(units);
+        if let Some(weather) = current.weather.first() {
+            builder = builder.condition(weather.id, &weather.main, &weather.description, &weather.icon);
+        }
+        if let Some(feels_like) = current.feels_like {
+            builder = builder.feels_like(feels_like);
+        }
+        if let Some(pressure) = current.pressure {
+            builder = builder.pressure(pressure);
+        }
+        if let Some(offset) = response.timezone_offset {
+            builder = builder.timezone(offset);
+        }
+        if let Some(uv_index) = current.uvi {
+            builder = builder.uv_index(uv_index);
+        }
+        if let Some(minutes) = response.minutes_to_precip() {
+            builder = builder.minutes_to_precip(minutes);
+        }
+
+        let mut data = builder.build();
+        data.apply_computed(Some(&ComfortThresholds::default()), false);
+        Some(data)
+    }
+
+    /// Parsed `weather_main`.
+    pub fn condition(&self) -> Option<WeatherCondition> {
+        self.weather_main.as_deref().map(WeatherCondition::from)
+    }
+
+    /// Unit system of the record, defaulting to metric for older rows.
+    pub fn units(&self) -> Units {
+        self.units.as_deref().and_then(Units::parse).unwrap_or(Units::Metric)
+    }
+
+    /// Dew point, in the record's temperature unit, from temperature and
+    /// relative humidity using the Magnus formula (Sonntag constants); `None`
+    /// for 0% humidity.
+    pub fn dew_point(&self) -> Option<f64> {
+        const A: f64 = 17.62;
+        const B: f64 = 243.12;
+
+        if self.humidity <= 0 {
+            return None;
+        }
+        let units = self.units();
+        let celsius = units.to_celsius(self.temperature);
+        let gamma = (self.humidity as f64 / 100.0).ln() + A * celsius / (B + celsius);
+        Some(units.from_celsius(B * gamma / (A - gamma)))
+    }
+
+    /// Wind chill, in the record's temperature unit, by the Environment
+    /// Canada formula. Only defined at or below 10 °C with wind of at least
+    /// 4.8 km/h; `None` otherwise. Unlike `feels_like` it never reflects
+    /// humidity or sun.
+    pub fn wind_chill(&self) -> Option<f64> {
+        self.wind_chill_celsius().map(|celsius| self.units().from_celsius(celsius))
+    }
+
+    fn wind_chill_celsius(&self) -> Option<f64> {
+        let units = self.units();
+        let celsius = units.to_celsius(self.temperature);
+        let wind_kmh = units.to_meters_per_second(self.wind_speed) * 3.6;
+        if celsius > 10.0 || wind_kmh < 4.8 {
+            return None;
+        }
+        let v = wind_kmh.powf(0.16);
+        Some(13.12 + 0.6215 * celsius - 11.37 * v + 0.3965 * celsius * v)
+    }
+
+    /// Derives every computed column from the raw fields. `comfort` is
+    /// `None` when the comfort category is disabled; `wind_chill` is `None`
+    /// unless enabled.
+    pub fn computed(&self, comfort: Option<&ComfortThresholds>, wind_chill: bool) -> ComputedColumns {
+        ComputedColumns {
+            dew_point: self.dew_point(),
+            wind_chill: if wind_chill { self.wind_chill() } else { None },
+            comfort_category: comfort.map(|thresholds| self.comfort_category(thresholds).to_string()),
+        }
+    }
+
+    /// Fills the computed columns from the raw fields.
+    pub fn apply_computed(&mut self, comfort: Option<&ComfortThresholds>, wind_chill: bool) {
+        let computed = self.computed(comfort, wind_chill);
+        self.dew_point = computed.dew_point;
+        self.wind_chill = computed.wind_chill;
+        self.comfort_category = computed.comfort_category;
+    }
+
+    /// Apparent temperature in °C: the wind chill when it is cold and windy,
+    /// the heat index when it is hot, otherwise the air temperature.
+    pub fn apparent_temperature_celsius(&self) -> f64 {
+        let celsius = self.units().to_celsius(self.temperature);
+
+        if let Some(wind_chill) = self.wind_chill_celsius() {
+            wind_chill
+        } else if celsius >= 27.0 && self.humidity >= 40 {
+            // NWS heat index (Rothfusz regression), defined in °F
+            let t = celsius * 9.0 / 5.0 + 32.0;
+            let rh = self.humidity as f64;
+            let hi = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+                - 0.224_755_41 * t * rh
+                - 0.006_837_83 * t * t
+                - 0.054_817_17 * rh * rh
+                + 0.001_228_74 * t * t * rh
+                + 0.000_852_82 * t * rh * rh
+                - 0.000_001_99 * t * t * rh * rh;
+            (hi - 32.0) * 5.0 / 9.0
+        } else {
+            celsius
+        }
+    }
+
+    /// Comfort band for the apparent temperature and dew point; see
+    /// [`ComfortCategory`].
+    pub fn comfort_category(&self, thresholds: &ComfortThresholds) -> ComfortCategory {
+        let dew_point = self.dew_point().map(|dew_point| self.units().to_celsius(dew_point));
+        ComfortCategory::classify(self.apparent_temperature_celsius(), dew_point, thresholds)
+    }
+
+    /// WHO exposure band of the UV index, if collected; see [`UvRisk`].
+    pub fn uv_risk_category(&self) -> Option<UvRisk> {
+        self.uv_index.map(UvRisk::from_index)
+    }
+
+    /// Starts a metric record dated now with zeroed readings; see
+    /// [`WeatherDataBuilder`].
+    pub fn builder() -> WeatherDataBuilder {
+        WeatherDataBuilder::default()
+    }
+}
+
+/// Fluent construction of a [`WeatherData`], for tests and for code that
+/// builds records without an API response. Fields left unset keep the
+/// defaults of [`WeatherData::builder`]. [`build`](Self::build) returns the
+/// record as is, with computed columns empty unless set;
+/// [`try_build`](Self::try_build) checks it and fills them under the given
+/// settings, as records from an API are before they are stored.
+#[derive(Debug, Clone)]
+pub struct WeatherDataBuilder {
+    data: WeatherData,
+}
+
+impl Default for WeatherDataBuilder {
+    fn default() -> Self {
+        Self {
+            data: WeatherData {
+                city: None,
+                temperature: 0.0,
+                feels_like: None,
+                humidity: 0,
+                pressure: None,
+                sea_level_pressure: None,
+                ground_level_pressure: None,
+                wind_speed: 0.0,
+                wind_direction: None,
+                weather_main: None,
+                weather_description: None,
+                weather_icon: None,
+                weather_id: None,
+                timestamp: chrono::Utc::now().timestamp(),
+                timezone: None,
+                timezone_name: None,
+                uv_index: None,
+                dew_point: None,
+                wind_chill: None,
+                minutes_to_precip: None,
+                temperature_ema: None,
+                day_high: None,
+                day_low: None,
+                comfort_category: None,
+                pressure_trend: None,
+                api_latency_ms: None,
+                source: None,
+                station_base: None,
+                station_id: None,
+                station_type: None,
+                units: Some(Units::Metric.name().to_string()),
+                timestamp_suspect: false,
+                labels: BTreeMap::new(),
+                location_id: None,
+                created_at: None,
+            },
+        }
+    }
+}
+
+impl From<WeatherData> for WeatherDataBuilder {
+    fn from(data: WeatherData) -> Self {
+        Self { data }
+    }
+}
+
+impl WeatherDataBuilder {
+    pub fn city(mut self, city: impl Into<String>) -> Self {
+        self.data.city = Some(city.into());
+        self
+    }
+
+    /// In the unit system set with [`units`](Self::units).
+    pub fn temperature(mut self, temperature: f64) -> Self {
+        self.data.temperature = temperature;
+        self
+    }
+
+    pub fn feels_like(mut self, feels_like: f64) -> Self {
+        self.data.feels_like = Some(feels_like);
+        self
+    }
+
+    pub fn humidity(mut self, humidity: i32) -> Self {
+        self.data.humidity = humidity;
+        self
+    }
+
+    pub fn pressure(mut self, pressure: i32) -> Self {
+        self.data.pressure = Some(pressure);
+        self
+    }
+
+    /// Sea-level and ground-level pressure in hPa, as OpenWeatherMap reports
+    /// them alongside `pressure`.
+    pub fn pressure_levels(mut self, sea_level: Option<i32>, ground_level: Option<i32>) -> Self {
+        self.data.sea_level_pressure = sea_level;
+        self.data.ground_level_pressure = ground_level;
+        self
+    }
+
+    pub fn wind(mut self, speed: f64, direction: Option<f64>) -> Self {
+        self.data.wind_speed = speed;
+        self.data.wind_direction = direction;
+        self
+    }
+
+    /// OpenWeatherMap condition code with its `main` group, description and
+    /// icon.
+    pub fn condition(mut self, id: i32, main: &str, description: &str, icon: &str) -> Self {
+        self.data.weather_id = Some(id);
+        self.data.weather_main = Some(main.to_string());
+        self.data.weather_description = Some(description.to_string());
+        self.data.weather_icon = Some(icon.to_string());
+        self
+    }
+
+    /// Observation time, in Unix seconds.
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.data.timestamp = timestamp;
+        self
+    }
+
+    /// UTC offset in seconds.
+    pub fn timezone(mut self, offset: i32) -> Self {
+        self.data.timezone = Some(offset);
+        self
+    }
+
+    pub fn timezone_name(mut self, name: impl Into<String>) -> Self {
+        self.data.timezone_name = Some(name.into());
+        self
+    }
+
+    pub fn uv_index(mut self, uv_index: f64) -> Self {
+        self.data.uv_index = Some(uv_index);
+        self
+    }
+
+    pub fn minutes_to_precip(mut self, minutes: i32) -> Self {
+        self.data.minutes_to_precip = Some(minutes);
+        self
+    }
+
+    pub fn api_latency_ms(mut self, latency_ms: i32) -> Self {
+        self.data.api_latency_ms = Some(latency_ms);
+        self
+    }
+
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.data.source = Some(source.into());
+        self
+    }
+
+    pub fn station(mut self, base: impl Into<String>, id: Option<i64>, station_type: Option<i32>) -> Self {
+        self.data.station_base = Some(base.into());
+        self.data.station_id = id;
+        self.data.station_type = station_type;
+        self
+    }
+
+    pub fn units(mut self, units: Units) -> Self {
+        self.data.units = Some(units.name().to_string());
+        self
+    }
+
+    pub fn timestamp_suspect(mut self, suspect: bool) -> Self {
+        self.data.timestamp_suspect = suspect;
+        self
+    }
+
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.data.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn location_id(mut self, location_id: i32) -> Self {
+        self.data.location_id = Some(location_id);
+        self
+    }
+
+    /// Defaults to the time of [`try_build`](Self::try_build). The database
+    /// assigns its own on insert.
+    pub fn created_at(mut self, created_at: chrono::DateTime<chrono::Utc>) -> Self {
+        self.data.created_at = Some(created_at);
+        self
+    }
+
+    pub fn build(self) -> WeatherData {
+        self.data
+    }
+
+    /// Checks the record for values no provider would report, then fills
+    /// the computed columns and `created_at`. `comfort` and `wind_chill` are
+    /// as for [`WeatherData::apply_computed`]; pass
+    /// `config.comfort_thresholds().as_ref()` and `config.wind_chill` to match
+    /// collected rows. Use this for records meant to be inserted.
+    pub fn try_build(self, comfort: Option<&ComfortThresholds>, wind_chill: bool) -> Result<WeatherData, InvalidWeatherData> {
+        let mut data = self.data;
+        if data.city.as_deref().is_none_or(|city| city.trim().is_empty()) {
+            return Err(InvalidWeatherData::MissingCity);
+        }
+        for (field, value) in [
+            ("temperature", Some(data.temperature)),
+            ("feels_like", data.feels_like),
+            ("wind_speed", Some(data.wind_speed)),
+            ("uv_index", data.uv_index),
+        ] {
+            if value.is_some_and(|value| !value.is_finite()) {
+                return Err(InvalidWeatherData::NotFinite(field));
+            }
+        }
+        if !(0..=100).contains(&data.humidity) {
+            return Err(InvalidWeatherData::Humidity(data.humidity));
+        }
+        if data.wind_speed < 0.0 {
+            return Err(InvalidWeatherData::NegativeWindSpeed(data.wind_speed));
+        }
+        if let Some(direction) = data.wind_direction.filter(|direction| !(0.0..=360.0).contains(direction)) {
+            return Err(InvalidWeatherData::WindDirection(direction));
+        }
+        if data.timestamp <= 0 {
+            return Err(InvalidWeatherData::Timestamp(data.timestamp));
+        }
+
+        data.apply_computed(comfort, wind_chill);
+        data.created_at.get_or_insert_with(chrono::Utc::now);
+        Ok(data)
+    }
+}
+
+/// Why [`WeatherDataBuilder::try_build`] rejected a record.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum InvalidWeatherData {
+    #[error("city is required")]
+    MissingCity,
+    #[error("{0} is not a finite number")]
+    NotFinite(&'static str),
+    #[error("humidity {0}% is outside 0-100")]
+    Humidity(i32),
+    #[error("wind speed {0} is negative")]
+    NegativeWindSpeed(f64),
+    #[error("wind direction {0}° is outside 0-360")]
+    WindDirection(f64),
+    #[error("timestamp {0} is not after 1970-01-01")]
+    Timestamp(i64),
+}
+
+/// Columns derived from the raw observation rather than read from the API:
+/// dew point, wind chill and comfort category. `rust_etl backfill-computed`
+/// recomputes them for rows stored before a column existed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComputedColumns {
+    pub dew_point: Option<f64>,
+    pub wind_chill: Option<f64>,
+    pub comfort_category: Option<String>,
+}
+
+/// Lower bounds, in °C of apparent temperature, of each [`ComfortCategory`]
+/// band above Dangerous Cold, plus the dew point at which a comfortable or
+/// warm reading counts as Humid. Bounds are inclusive and must ascend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComfortThresholds {
+    pub very_cold: f64,
+    pub cold: f64,
+    pub cool: f64,
+    pub comfortable: f64,
+    pub warm: f64,
+    pub hot: f64,
+    pub dangerous_heat: f64,
+    pub extreme_heat: f64,
+    pub humid_dew_point: f64,
+}
+
+impl ComfortThresholds {
+    /// Whether every band starts above the one before it.
+    pub fn is_ascending(&self) -> bool {
+        let bounds = [
+            self.very_cold,
+            self.cold,
+            self.cool,
+            self.comfortable,
+            self.warm,
+            self.hot,
+            self.dangerous_heat,
+            self.extreme_heat,
+        ];
+        bounds.windows(2).all(|pair| pair[0] < pair[1])
+    }
+}
+
+impl Default for ComfortThresholds {
+    fn default() -> Self {
+        Self {
+            very_cold: -27.0,
+            cold: -10.0,
+            cool: 10.0,
+            comfortable: 18.0,
+            warm: 27.0,
+            hot: 32.0,
+            dangerous_heat: 41.0,
+            extreme_heat: 54.0,
+            humid_dew_point: 18.0,
+        }
+    }
+}
+
+/// How the weather feels, from the apparent temperature (wind chill or heat
+/// index, in °C). By default cold bands follow Environment Canada's wind
+/// chill risk levels, warm bands the NWS heat index caution levels:
+///
+/// | Category       | Apparent temperature |
+/// |----------------|----------------------|
+/// | Dangerous Cold | below -27            |
+/// | Very Cold      | -27 to -10           |
+/// | Cold           | -10 to 10            |
+/// | Cool           | 10 to 18             |
+/// | Comfortable    | 18 to 27             |
+/// | Warm           | 27 to 32             |
+/// | Hot            | 32 to 41             |
+/// | Dangerous Heat | 41 to 54             |
+/// | Extreme Heat   | 54 and above         |
+///
+/// A Comfortable or Warm reading with a dew point of 18 °C or more is Humid
+/// instead. Every bound is configurable through [`ComfortThresholds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComfortCategory {
+    DangerousCold,
+    VeryCold,
+    Cold,
+    Cool,
+    Comfortable,
+    Humid,
+    Warm,
+    Hot,
+    DangerousHeat,
+    ExtremeHeat,
+}
+
+impl ComfortCategory {
+    pub fn from_apparent_celsius(apparent: f64, thresholds: &ComfortThresholds) -> Self {
+        match apparent {
+            t if t < thresholds.very_cold => ComfortCategory::DangerousCold,
+            t if t < thresholds.cold => ComfortCategory::VeryCold,
+            t if t < thresholds.cool => ComfortCategory::Cold,
+            t if t < thresholds.comfortable => ComfortCategory::Cool,
+            t if t < thresholds.warm => ComfortCategory::Comfortable,
+            t if t < thresholds.hot => ComfortCategory::Warm,
+            t if t < thresholds.dangerous_heat => ComfortCategory::Hot,
+            t if t < thresholds.extreme_heat => ComfortCategory::DangerousHeat,
+            _ => ComfortCategory::ExtremeHeat,
+        }
+    }
+
+    /// Bands the apparent temperature, then marks muggy comfortable or warm
+    /// readings as Humid.
+    pub fn classify(apparent: f64, dew_point_celsius: Option<f64>, thresholds: &ComfortThresholds) -> Self {
+        let category = Self::from_apparent_celsius(apparent, thresholds);
+        let muggy = dew_point_celsius.is_some_and(|dew_point| dew_point >= thresholds.humid_dew_point);
+        match category {
+            ComfortCategory::Comfortable | ComfortCategory::Warm if muggy => ComfortCategory::Humid,
+            category => category,
         }
     }
 }
 
+impl std::fmt::Display for ComfortCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            ComfortCategory::DangerousCold => "Dangerous Cold",
+            ComfortCategory::VeryCold => "Very Cold",
+            ComfortCategory::Cold => "Cold",
+            ComfortCategory::Cool => "Cool",
+            ComfortCategory::Comfortable => "Comfortable",
+            ComfortCategory::Humid => "Humid",
+            ComfortCategory::Warm => "Warm",
+            ComfortCategory::Hot => "Hot",
+            ComfortCategory::DangerousHeat => "Dangerous Heat",
+            ComfortCategory::ExtremeHeat => "Extreme Heat",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Direction of the pressure change since the previous stored reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PressureTrend {
+    Rising,
+    Steady,
+    Falling,
+    /// No previous reading (or no pressure) to compare against.
+    Unknown,
+}
+
+impl PressureTrend {
+    /// Classifies the change from `previous` to `current` hPa; changes
+    /// smaller than `threshold` count as steady.
+    pub fn between(previous: Option<i32>, current: Option<i32>, threshold: i32) -> Self {
+        match (previous, current) {
+            (Some(previous), Some(current)) if current - previous >= threshold => PressureTrend::Rising,
+            (Some(previous), Some(current)) if previous - current >= threshold => PressureTrend::Falling,
+            (Some(_), Some(_)) => PressureTrend::Steady,
+            _ => PressureTrend::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for PressureTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            PressureTrend::Rising => "rising",
+            PressureTrend::Steady => "steady",
+            PressureTrend::Falling => "falling",
+            PressureTrend::Unknown => "unknown",
+        };
+        f.write_str(label)
+    }
+}
+
+/// WHO UV index exposure categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UvRisk {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+    Extreme,
+}
+
+impl UvRisk {
+    /// Maps a UV index to its category; the index is rounded first, as the
+    /// WHO bands (0-2, 3-5, 6-7, 8-10, 11+) are defined on whole numbers.
+    pub fn from_index(uv_index: f64) -> Self {
+        match uv_index.round() {
+            i if i < 3.0 => UvRisk::Low,
+            i if i < 6.0 => UvRisk::Moderate,
+            i if i < 8.0 => UvRisk::High,
+            i if i < 11.0 => UvRisk::VeryHigh,
+            _ => UvRisk::Extreme,
+        }
+    }
+}
+
+impl std::fmt::Display for UvRisk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            UvRisk::Low => "Low",
+            UvRisk::Moderate => "Moderate",
+            UvRisk::High => "High",
+            UvRisk::VeryHigh => "Very High",
+            UvRisk::Extreme => "Extreme",
+        };
+        f.write_str(label)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WeatherMain {
     pub temp: f64,
     pub feels_like: f64,
     pub humidity: i32,
     pub pressure: i32,
+    #[serde(default)]
+    pub sea_level: Option<i32>,
+    #[serde(default)]
+    pub grnd_level: Option<i32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -103,3 +846,116 @@ pub struct Sys {
     pub sunset: Option<i64>,
 }
 
+
+/// Subset of the One Call API response used to enrich observations, or as
+/// the observation itself under `OPENWEATHER_TIER=onecall`.
+#[derive(Debug, Deserialize)]
+pub struct OneCallResponse {
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub timezone_offset: Option<i32>,
+    pub current: OneCallCurrent,
+    /// Per-minute precipitation for the next hour; absent when excluded or
+    /// not available for the location.
+    #[serde(default)]
+    pub minutely: Vec<OneCallMinute>,
+}
+
+impl OneCallResponse {
+    /// Minutes from the first `minutely` entry to the next change between
+    /// dry and precipitating: positive when precipitation starts, negative
+    /// when it stops. `None` when nothing changes within the nowcast or
+    /// there is no nowcast.
+    pub fn minutes_to_precip(&self) -> Option<i32> {
+        let (first, rest) = self.minutely.split_first()?;
+        let wet = first.is_wet();
+        let change = rest.iter().find(|minute| minute.is_wet() != wet)?;
+        let minutes = ((change.dt - first.dt) / 60).max(1) as i32;
+        Some(if wet { -minutes } else { minutes })
+    }
+}
+
+/// Conditions are optional because enrichment only needs `dt` and `uvi`;
+/// One Call always sends them.
+#[derive(Debug, Deserialize)]
+pub struct OneCallCurrent {
+    pub dt: i64,
+    #[serde(default)]
+    pub uvi: Option<f64>,
+    #[serde(default)]
+    pub temp: Option<f64>,
+    #[serde(default)]
+    pub feels_like: Option<f64>,
+    #[serde(default)]
+    pub pressure: Option<i32>,
+    #[serde(default)]
+    pub humidity: Option<i32>,
+    #[serde(default)]
+    pub wind_speed: Option<f64>,
+    #[serde(default)]
+    pub wind_deg: Option<f64>,
+    #[serde(default)]
+    pub weather: Vec<Weather>,
+}
+
+/// One match from the OpenWeatherMap geocoding API (`/geo/1.0/direct`),
+/// which One Call needs to turn a city name into coordinates.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeocodedCity {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OneCallMinute {
+    pub dt: i64,
+    /// Precipitation in mm/h.
+    pub precipitation: f64,
+}
+
+impl OneCallMinute {
+    fn is_wet(&self) -> bool {
+        self.precipitation > 0.0
+    }
+}
+
+/// Subset of the WeatherAPI.com `current.json` response.
+#[derive(Debug, Deserialize)]
+pub struct WeatherApiResponse {
+    pub location: WeatherApiLocation,
+    pub current: WeatherApiCurrent,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeatherApiLocation {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub tz_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeatherApiCurrent {
+    pub last_updated_epoch: i64,
+    pub temp_c: f64,
+    pub feelslike_c: f64,
+    pub humidity: i32,
+    pub pressure_mb: f64,
+    pub wind_kph: f64,
+    pub wind_mph: f64,
+    pub wind_degree: f64,
+    pub condition: WeatherApiCondition,
+    #[serde(default)]
+    pub uv: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WeatherApiCondition {
+    pub text: String,
+    pub icon: String,
+    pub code: i32,
+}
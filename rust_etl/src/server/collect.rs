@@ -0,0 +1,104 @@
+use super::http::{self, Request};
+use super::ServerState;
+use crate::services::collect_trigger::CollectTrigger;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::OnceCell;
+
+/// Longest `Idempotency-Key` accepted.
+const MAX_KEY_LEN: usize = 255;
+
+/// A finished (or in-flight) `/collect` response: status and JSON body.
+type Outcome = Arc<OnceCell<(u16, String)>>;
+
+/// Recent `/collect` outcomes by `Idempotency-Key`. A request whose key is
+/// already present waits for (or reuses) the first request's outcome instead
+/// of triggering another cycle. Entries expire after `window` and are
+/// evicted whenever a key is added; failed (5xx) outcomes are dropped as
+/// soon as they finish, so a retry with the same key runs a fresh cycle.
+pub struct IdempotencyCache {
+    window: Duration,
+    entries: Mutex<HashMap<String, (Instant, Outcome)>>,
+}
+
+impl IdempotencyCache {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, (Instant, Outcome)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The outcome slot for `key`, and whether it was created by this call.
+    fn slot(&self, key: &str) -> (Outcome, bool) {
+        let mut entries = self.entries();
+        let now = Instant::now();
+
+        match entries.get(key) {
+            Some((created, outcome)) if now.duration_since(*created) < self.window => (Arc::clone(outcome), false),
+            _ => {
+                entries.retain(|_, (created, _)| now.duration_since(*created) < self.window);
+                let outcome = Outcome::default();
+                entries.insert(key.to_string(), (now, Arc::clone(&outcome)));
+                (outcome, true)
+            }
+        }
+    }
+
+    /// Drops `key` if it still holds `outcome`, so the next request with it
+    /// starts over.
+    fn forget(&self, key: &str, outcome: &Outcome) {
+        let mut entries = self.entries();
+        if entries.get(key).is_some_and(|(_, current)| Arc::ptr_eq(current, outcome)) {
+            entries.remove(key);
+        }
+    }
+}
+
+/// `POST /collect`: runs a collection cycle now and responds with its
+/// report. With an `Idempotency-Key` header, repeats of the same key within
+/// `COLLECT_IDEMPOTENCY_WINDOW_SECONDS` get the first request's response.
+pub async fn trigger(stream: &mut TcpStream, request: &Request, state: &ServerState) -> std::io::Result<()> {
+    let key = request.header("idempotency-key").map(str::trim).filter(|key| !key.is_empty());
+
+    let (status, body) = match key {
+        Some(key) if key.len() > MAX_KEY_LEN => {
+            (400, format!("{{\"error\":\"Idempotency-Key longer than {} bytes\"}}", MAX_KEY_LEN))
+        }
+        Some(key) => {
+            let (outcome, created) = state.collect_results.slot(key);
+            if !created {
+                log::info!("🔁 /collect repeated with Idempotency-Key {}; returning the first result", key);
+            }
+            let (status, body) = outcome.get_or_init(|| run(state)).await.clone();
+            if status >= 500 {
+                state.collect_results.forget(key, &outcome);
+            }
+            (status, body)
+        }
+        None => run(state).await,
+    };
+
+    http::respond(stream, status, "application/json", &body).await
+}
+
+async fn run(state: &ServerState) -> (u16, String) {
+    let (trigger, report) = CollectTrigger::new();
+    if state.collect.send(trigger).await.is_err() {
+        return (503, "{\"error\":\"collection has stopped\"}".to_string());
+    }
+
+    match report.await {
+        Ok(report) => match serde_json::to_string(&report) {
+            Ok(json) => (200, json),
+            Err(e) => (500, serde_json::json!({ "error": e.to_string() }).to_string()),
+        },
+        Err(_) => (503, "{\"error\":\"collection stopped before the cycle finished\"}".to_string()),
+    }
+}
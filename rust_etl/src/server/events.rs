@@ -0,0 +1,54 @@
+use super::http::{self, Request};
+use super::ServerState;
+use crate::services::insert_writer::InsertedObservation;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Comment line sent while idle so proxies keep the stream open and dead
+/// clients are noticed.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// `GET /events[?city=...]`: streams each newly inserted observation as a
+/// Server-Sent Event (`event: observation`, JSON `data` with the row's `id`
+/// and `created_at`). The optional `city` filter matches case-insensitively.
+pub async fn stream(stream: &mut TcpStream, request: &Request, state: &ServerState) -> std::io::Result<()> {
+    let city = request.query.get("city").map(|city| city.to_lowercase());
+    let mut inserted = state.inserted.subscribe();
+
+    http::respond_streaming(stream, "text/event-stream").await?;
+    stream.write_all(b": connected\n\n").await?;
+
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    keepalive.tick().await;
+
+    loop {
+        let frame = tokio::select! {
+            received = inserted.recv() => match received {
+                Ok(data) if matches(&data, city.as_deref()) => match serde_json::to_string(&data) {
+                    Ok(json) => format!("event: observation\ndata: {}\n\n", json),
+                    Err(e) => {
+                        log::warn!("⚠️  Failed to serialize observation for /events: {}", e);
+                        continue;
+                    }
+                },
+                Ok(_) => continue,
+                // A slow client missed some events; tell it and carry on
+                Err(RecvError::Lagged(skipped)) => format!(": skipped {} events\n\n", skipped),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+        };
+
+        stream.write_all(frame.as_bytes()).await?;
+        stream.flush().await?;
+    }
+}
+
+fn matches(inserted: &InsertedObservation, city: Option<&str>) -> bool {
+    match city {
+        Some(city) => inserted.observation.city.as_deref().is_some_and(|c| c.to_lowercase() == city),
+        None => true,
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+/// Longest request head (request line plus headers) accepted, in bytes.
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// The parts of an HTTP/1.1 request the endpoints look at. Bodies are not
+/// read; no endpoint takes one.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// Header values keyed by lowercased name.
+    pub headers: HashMap<String, String>,
+}
+
+impl Request {
+    /// Reads the request line and headers from `stream`. Returns `None` when
+    /// the client closes the connection without sending anything.
+    pub async fn read(stream: &mut TcpStream) -> Result<Option<Self>> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(None);
+        }
+
+        let mut headers = HashMap::new();
+        let mut head_bytes = request_line.len();
+        loop {
+            let mut header = String::new();
+            let read = reader.read_line(&mut header).await?;
+            head_bytes += read;
+            if head_bytes > MAX_HEAD_BYTES {
+                return Err(anyhow::anyhow!("request head exceeds {} bytes", MAX_HEAD_BYTES));
+            }
+            if read == 0 || header == "\r\n" || header == "\n" {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().context("empty request line")?.to_string();
+        let target = parts.next().context("request line has no target")?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        Ok(Some(Self {
+            method,
+            path: path.to_string(),
+            query: parse_query(query),
+            headers,
+        }))
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+}
+
+/// Decodes `a=1&b=two` into a map; later duplicates win.
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn decode(component: &str) -> String {
+    let component = component.replace('+', " ");
+    urlencoding::decode(&component)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or(component)
+}
+
+/// Writes a complete response and leaves the connection to be closed.
+pub async fn respond<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status),
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.flush().await
+}
+
+/// Writes the head of a response whose body streams until the connection
+/// closes.
+pub async fn respond_streaming<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    content_type: &str,
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        content_type
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.flush().await
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
@@ -0,0 +1,56 @@
+use super::http::{self, Request};
+use super::ServerState;
+use crate::models::weather::WeatherData;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// A stored observation with its freshness, computed when served rather
+/// than stored.
+#[derive(Debug, Serialize)]
+pub struct LatestObservation {
+    #[serde(flatten)]
+    pub observation: WeatherData,
+    /// Seconds from the observation's `timestamp` to `now`; 0 for a
+    /// timestamp ahead of the clock.
+    pub age_seconds: i64,
+    /// Older than `STALE_DATA_THRESHOLD_SECONDS`.
+    pub is_stale: bool,
+}
+
+impl LatestObservation {
+    /// Freshness of `observation` at `now` (Unix seconds).
+    pub fn new(observation: WeatherData, now: i64, stale_after: Duration) -> Self {
+        let age_seconds = now.saturating_sub(observation.timestamp).max(0);
+        Self {
+            observation,
+            age_seconds,
+            is_stale: age_seconds as u64 > stale_after.as_secs(),
+        }
+    }
+}
+
+/// `GET /latest?city=...`: the newest stored observation for the city, as a
+/// [`LatestObservation`].
+pub async fn get(stream: &mut TcpStream, request: &Request, state: &ServerState) -> std::io::Result<()> {
+    let Some(city) = request.query.get("city").map(|city| city.trim()).filter(|city| !city.is_empty()) else {
+        return http::respond(stream, 400, "application/json", "{\"error\":\"missing city parameter\"}").await;
+    };
+
+    let (status, body) = match state.database.get_latest_weather(city).await {
+        Ok(Some(observation)) => {
+            let latest = LatestObservation::new(observation, chrono::Utc::now().timestamp(), state.stale_after);
+            match serde_json::to_string(&latest) {
+                Ok(json) => (200, json),
+                Err(e) => (500, serde_json::json!({ "error": e.to_string() }).to_string()),
+            }
+        }
+        Ok(None) => (404, serde_json::json!({ "error": format!("no observations for {}", city) }).to_string()),
+        Err(e) => {
+            log::warn!("⚠️  /latest query failed: {:#}", e);
+            (503, "{\"error\":\"observations are unavailable\"}".to_string())
+        }
+    };
+
+    http::respond(stream, status, "application/json", &body).await
+}
@@ -0,0 +1,146 @@
+//! Small HTTP/1.1 server for monitoring endpoints, enabled with
+//! `HTTP_SERVER=true`. One task per connection; every response closes the
+//! connection.
+
+pub mod collect;
+pub mod events;
+pub mod http;
+pub mod latest;
+pub mod openapi;
+
+use crate::services::collect_trigger::CollectTrigger;
+use crate::services::database::DatabaseService;
+use crate::services::insert_writer::{InsertedObservation, QueueHealth};
+use anyhow::{Context, Result};
+use collect::IdempotencyCache;
+use http::Request;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+/// Shared with every connection.
+pub struct ServerState {
+    /// Observations confirmed by the insert writer.
+    pub inserted: broadcast::Sender<InsertedObservation>,
+    /// Asks the collection loop to run a cycle now.
+    pub collect: mpsc::Sender<CollectTrigger>,
+    pub collect_results: IdempotencyCache,
+    /// Probed by `GET /ready` and read by `GET /latest`.
+    pub database: Arc<DatabaseService>,
+    /// Age past which `GET /latest` flags an observation as stale.
+    pub stale_after: Duration,
+    /// Insert queue depth and lag, reported by `GET /health`.
+    pub writer: Arc<QueueHealth>,
+    /// Whether the last `GET /health` found the writer degraded, so only
+    /// changes are logged.
+    writer_degraded: AtomicBool,
+}
+
+impl ServerState {
+    pub fn new(
+        inserted: broadcast::Sender<InsertedObservation>,
+        collect: mpsc::Sender<CollectTrigger>,
+        idempotency_window: Duration,
+        database: Arc<DatabaseService>,
+        stale_after: Duration,
+        writer: Arc<QueueHealth>,
+    ) -> Self {
+        Self {
+            inserted,
+            collect,
+            collect_results: IdempotencyCache::new(idempotency_window),
+            database,
+            stale_after,
+            writer,
+            writer_degraded: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Binds `addr` and serves requests until the returned task is aborted.
+pub async fn spawn(addr: SocketAddr, state: Arc<ServerState>) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind HTTP server to {}", addr))?;
+    log::info!("🌐 HTTP server listening on {}", listener.local_addr()?);
+
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle(stream, Arc::clone(&state)));
+                }
+                Err(e) => log::warn!("⚠️  Failed to accept HTTP connection: {}", e),
+            }
+        }
+    }))
+}
+
+async fn handle(mut stream: TcpStream, state: Arc<ServerState>) {
+    let request = match Request::read(&mut stream).await {
+        Ok(Some(request)) => request,
+        Ok(None) => return,
+        Err(e) => {
+            log::debug!("Rejected malformed HTTP request: {:#}", e);
+            let _ = http::respond(&mut stream, 400, "text/plain", "bad request\n").await;
+            return;
+        }
+    };
+
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/events") => events::stream(&mut stream, &request, &state).await,
+        ("POST", "/collect") => collect::trigger(&mut stream, &request, &state).await,
+        ("GET", "/ready") => ready(&mut stream, &state).await,
+        ("GET", "/health") => health(&mut stream, &state).await,
+        ("GET", "/latest") => latest::get(&mut stream, &request, &state).await,
+        ("GET", "/openapi.json") => {
+            http::respond(&mut stream, 200, "application/json", &openapi::document().to_string()).await
+        }
+        (_, "/events" | "/collect" | "/ready" | "/health" | "/latest" | "/openapi.json") => http::respond(&mut stream, 405, "text/plain", "method not allowed\n").await,
+        _ => http::respond(&mut stream, 404, "text/plain", "not found\n").await,
+    };
+
+    if let Err(e) = result {
+        log::debug!("HTTP connection for {} ended: {}", request.path, e);
+    }
+}
+
+/// 200 once the database accepts writes, 503 otherwise. A read-only or full
+/// database passes a plain connection check while every insert fails.
+async fn ready(stream: &mut TcpStream, state: &ServerState) -> std::io::Result<()> {
+    match state.database.health_check_writable().await {
+        Ok(()) => http::respond(stream, 200, "text/plain", "ready\n").await,
+        Err(e) => {
+            log::warn!("⚠️  Readiness check failed: {:#}", e);
+            http::respond(stream, 503, "text/plain", "not ready\n").await
+        }
+    }
+}
+
+/// Insert writer backpressure: `degraded` once the queue has stayed at or
+/// above its high-water mark for `WRITER_QUEUE_DEGRADED_AFTER_SECONDS`.
+/// Always 200, so a liveness probe pointed here doesn't restart a writer that
+/// is catching up; alert on the body instead.
+async fn health(stream: &mut TcpStream, state: &ServerState) -> std::io::Result<()> {
+    let writer = state.writer.report();
+    let status = if writer.degraded { "degraded" } else { "ok" };
+    if state.writer_degraded.swap(writer.degraded, Ordering::Relaxed) != writer.degraded {
+        if writer.degraded {
+            log::warn!(
+                "⚠️  Insert queue backed up for {}s ({}/{} jobs, {} rows)",
+                writer.backed_up_seconds.unwrap_or_default(),
+                writer.depth,
+                writer.capacity,
+                writer.rows
+            );
+        } else {
+            log::info!("✅ Insert queue caught up ({}/{} jobs)", writer.depth, writer.capacity);
+        }
+    }
+    let body = serde_json::json!({ "status": status, "writer": writer });
+    http::respond(stream, 200, "application/json", &body.to_string()).await
+}
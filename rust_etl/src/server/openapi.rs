@@ -0,0 +1,262 @@
+//! Hand-written OpenAPI 3 description of the HTTP API, served at
+//! `GET /openapi.json`. Keep it in step with the routes in `server::handle`
+//! and with the serialized fields of [`WeatherData`] and [`CycleReport`].
+//!
+//! [`WeatherData`]: crate::models::weather::WeatherData
+//! [`CycleReport`]: crate::services::collect_trigger::CycleReport
+
+use serde_json::{json, Value};
+
+pub fn document() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Montreal Weather ETL",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Monitoring and control endpoints of the weather collector, enabled with HTTP_SERVER=true."
+        },
+        "paths": {
+            "/events": {
+                "get": {
+                    "summary": "Stream newly stored observations",
+                    "description": "Server-Sent Events stream with one `observation` event per inserted row; `data` is an InsertedObservation object, the row with its `id` and `created_at`. Comment lines report skipped events and keep the connection alive.",
+                    "parameters": [{
+                        "name": "city",
+                        "in": "query",
+                        "required": false,
+                        "description": "Only observations for this city (case-insensitive).",
+                        "schema": { "type": "string" }
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "Event stream; `data` of each `observation` event is an InsertedObservation object.",
+                            "content": { "text/event-stream": { "schema": { "type": "string" } } }
+                        }
+                    }
+                }
+            },
+            "/collect": {
+                "post": {
+                    "summary": "Run a collection cycle now",
+                    "description": "Starts a cycle immediately and responds once it has finished. Requests repeating an Idempotency-Key within COLLECT_IDEMPOTENCY_WINDOW_SECONDS get the first request's response without starting another cycle.",
+                    "parameters": [{
+                        "name": "Idempotency-Key",
+                        "in": "header",
+                        "required": false,
+                        "schema": { "type": "string", "maxLength": 255 }
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "Report of the cycle.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/CycleReport" } } }
+                        },
+                        "400": {
+                            "description": "Idempotency-Key too long.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                        },
+                        "503": {
+                            "description": "Collection has stopped.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                        }
+                    }
+                }
+            },
+            "/ready": {
+                "get": {
+                    "summary": "Readiness probe",
+                    "description": "Checks that the database accepts writes by inserting a row in a transaction that is rolled back, so a read-only or full database is reported as not ready.",
+                    "responses": {
+                        "200": { "description": "The database is writable.", "content": { "text/plain": {} } },
+                        "503": { "description": "The database is unreachable or rejects writes.", "content": { "text/plain": {} } }
+                    }
+                }
+            },
+            "/health": {
+                "get": {
+                    "summary": "Insert writer backpressure",
+                    "description": "Insert queue depth and lag. `status` is `degraded` once the queue has stayed at or above WRITER_QUEUE_HIGH_WATER_PERCENT of INSERT_QUEUE_CAPACITY for WRITER_QUEUE_DEGRADED_AFTER_SECONDS. Always 200; alert on `status`.",
+                    "responses": {
+                        "200": {
+                            "description": "The writer's health.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Health" } } }
+                        }
+                    }
+                }
+            },
+            "/latest": {
+                "get": {
+                    "summary": "Newest stored observation for a city",
+                    "description": "The latest row for the city with its freshness, computed when served: `age_seconds` since the observation time and `is_stale` past STALE_DATA_THRESHOLD_SECONDS.",
+                    "parameters": [{
+                        "name": "city",
+                        "in": "query",
+                        "required": true,
+                        "description": "City name as stored.",
+                        "schema": { "type": "string" }
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "The observation.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/LatestObservation" } } }
+                        },
+                        "400": {
+                            "description": "No city given.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                        },
+                        "404": {
+                            "description": "No observations stored for the city.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                        },
+                        "503": {
+                            "description": "The database query failed.",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+                        }
+                    }
+                }
+            },
+            "/openapi.json": {
+                "get": {
+                    "summary": "This document",
+                    "responses": {
+                        "200": { "description": "OpenAPI 3 document.", "content": { "application/json": {} } }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "WeatherData": weather_data_schema(),
+                "Health": health_schema(),
+                "LatestObservation": {
+                    "allOf": [
+                        { "$ref": "#/components/schemas/WeatherData" },
+                        {
+                            "type": "object",
+                            "required": ["age_seconds", "is_stale"],
+                            "properties": {
+                                "age_seconds": { "type": "integer", "format": "int64", "description": "Seconds since the observation time." },
+                                "is_stale": { "type": "boolean", "description": "Older than STALE_DATA_THRESHOLD_SECONDS." }
+                            }
+                        }
+                    ]
+                },
+                "InsertedObservation": {
+                    "allOf": [
+                        { "$ref": "#/components/schemas/WeatherData" },
+                        {
+                            "type": "object",
+                            "required": ["id"],
+                            "properties": {
+                                "id": { "type": "integer", "description": "Row id the database assigned." },
+                                "created_at": { "type": "string", "format": "date-time", "description": "When the row was inserted." }
+                            }
+                        }
+                    ]
+                },
+                "CycleReport": {
+                    "type": "object",
+                    "required": ["started_at", "finished_at", "cities", "fetched", "failed", "queued"],
+                    "properties": {
+                        "started_at": { "type": "string", "format": "date-time" },
+                        "finished_at": { "type": "string", "format": "date-time" },
+                        "cities": { "type": "integer", "description": "Cities attempted." },
+                        "fetched": { "type": "integer" },
+                        "failed": { "type": "integer" },
+                        "queued": { "type": "integer", "description": "Observations handed to the insert writer." }
+                    }
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["error"],
+                    "properties": { "error": { "type": "string" } }
+                }
+            }
+        }
+    })
+}
+
+/// Body of `GET /health`; see [`QueueReport`](crate::services::insert_writer::QueueReport).
+fn health_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["status", "writer"],
+        "properties": {
+            "status": { "type": "string", "enum": ["ok", "degraded"] },
+            "writer": {
+                "type": "object",
+                "required": ["depth", "capacity", "high_water", "rows", "last_wait_ms", "degraded"],
+                "properties": {
+                    "depth": { "type": "integer", "description": "Jobs waiting for the writer; a job is one row, or a whole cycle with CYCLE_TRANSACTION or PIPELINE_CYCLES." },
+                    "capacity": { "type": "integer", "description": "INSERT_QUEUE_CAPACITY, in jobs." },
+                    "high_water": { "type": "integer", "description": "Depth, in jobs, at or above which the queue counts as backed up." },
+                    "rows": { "type": "integer", "description": "Rows waiting to be written: those in queued jobs plus the writer's unwritten batch." },
+                    "last_wait_ms": { "type": "integer", "description": "How long the job the writer last picked up had waited." },
+                    "backed_up_seconds": nullable_described("integer", "How long the queue has been backed up, if it is."),
+                    "degraded": { "type": "boolean" }
+                }
+            }
+        }
+    })
+}
+
+/// Schema of [`WeatherData`](crate::models::weather::WeatherData), kept
+/// apart so `document`'s `json!` stays within the macro recursion limit.
+fn weather_data_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["temperature", "humidity", "wind_speed", "timestamp", "timestamp_suspect", "labels"],
+        "properties": {
+            "city": nullable("string"),
+            "temperature": { "type": "number", "description": "In the row's units." },
+            "feels_like": nullable("number"),
+            "humidity": { "type": "integer", "description": "Relative humidity, %." },
+            "pressure": nullable_described("integer", "hPa."),
+            "sea_level_pressure": nullable_described("integer", "Sea-level pressure in hPa, when the provider reports it separately."),
+            "ground_level_pressure": nullable_described("integer", "Pressure at the station's altitude in hPa, when the provider reports it."),
+            "wind_speed": { "type": "number", "description": "m/s, or mph for imperial units." },
+            "wind_direction": nullable_described("number", "Degrees the wind blows from."),
+            "weather_main": nullable("string"),
+            "weather_description": nullable("string"),
+            "weather_icon": nullable("string"),
+            "weather_id": nullable_described("integer", "OpenWeatherMap condition code."),
+            "timestamp": { "type": "integer", "format": "int64", "description": "Observation time, Unix seconds." },
+            "timezone": nullable_described("integer", "UTC offset in seconds."),
+            "timezone_name": nullable_described("string", "IANA zone name."),
+            "uv_index": nullable("number"),
+            "dew_point": nullable("number"),
+            "wind_chill": nullable_described("number", "Environment Canada wind chill index, in the row's units; only at or below 10 °C with wind."),
+            "minutes_to_precip": nullable_described("integer", "Minutes until precipitation starts (positive) or stops (negative) within the next hour."),
+            "temperature_ema": nullable("number"),
+            "day_high": nullable_described("number", "Highest temperature since local midnight, including this reading."),
+            "day_low": nullable_described("number", "Lowest temperature since local midnight, including this reading."),
+            "comfort_category": nullable("string"),
+            "pressure_trend": {
+                "type": "string",
+                "nullable": true,
+                "enum": ["rising", "steady", "falling", "unknown", null]
+            },
+            "api_latency_ms": nullable("integer"),
+            "source": nullable_described("string", "Provider, e.g. openweathermap."),
+            "station_base": nullable("string"),
+            "station_id": nullable("integer"),
+            "station_type": nullable("integer"),
+            "units": {
+                "type": "string",
+                "nullable": true,
+                "enum": ["metric", "imperial", "standard", null]
+            },
+            "timestamp_suspect": { "type": "boolean" },
+            "labels": { "type": "object", "additionalProperties": { "type": "string" } },
+            "location_id": nullable_described("integer", "Row of the locations table the city resolved to (LOCATION_IDS)."),
+            "created_at": { "type": "string", "format": "date-time", "nullable": true }
+        }
+    })
+}
+
+fn nullable(kind: &str) -> Value {
+    json!({ "type": kind, "nullable": true })
+}
+
+fn nullable_described(kind: &str, description: &str) -> Value {
+    json!({ "type": kind, "nullable": true, "description": description })
+}
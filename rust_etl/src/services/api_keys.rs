@@ -0,0 +1,93 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// API keys used one at a time: requests go to the active key until it runs
+/// out of quota or is rejected, then move on to the next usable key. A
+/// rejected key stays out of rotation until restart.
+pub struct ApiKeyRing {
+    keys: Vec<String>,
+    state: Mutex<RingState>,
+}
+
+struct RingState {
+    active: usize,
+    exhausted_until: Vec<Option<Instant>>,
+    rejected: Vec<bool>,
+}
+
+/// Why [`ApiKeyRing::current`] has no key to offer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoUsableKey {
+    /// Every key was rejected by the provider.
+    AllRejected,
+    /// The keys not rejected are cooling down; the first is usable again
+    /// after this long.
+    Exhausted(Duration),
+}
+
+impl ApiKeyRing {
+    pub fn new(keys: Vec<String>) -> Self {
+        let exhausted_until = vec![None; keys.len()];
+        let rejected = vec![false; keys.len()];
+        Self {
+            keys,
+            state: Mutex::new(RingState {
+                active: 0,
+                exhausted_until,
+                rejected,
+            }),
+        }
+    }
+
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// Returns the index and value of the key to use now, rotating past keys
+    /// that were rejected or are still cooling down.
+    pub fn current(&self) -> Result<(usize, &str), NoUsableKey> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let start = state.active;
+
+        for offset in 0..self.keys.len() {
+            let index = (start + offset) % self.keys.len();
+            if state.rejected[index] {
+                continue;
+            }
+            match state.exhausted_until[index] {
+                Some(until) if until > now => continue,
+                _ => {
+                    state.exhausted_until[index] = None;
+                    if index != start {
+                        log::info!("🔑 Switching to API key #{} of {}", index + 1, self.keys.len());
+                    }
+                    state.active = index;
+                    return Ok((index, &self.keys[index]));
+                }
+            }
+        }
+
+        let wait = state
+            .exhausted_until
+            .iter()
+            .zip(&state.rejected)
+            .filter(|(_, rejected)| !**rejected)
+            .filter_map(|(until, _)| *until)
+            .min()
+            .map(|until| until.saturating_duration_since(now));
+        Err(wait.map_or(NoUsableKey::AllRejected, NoUsableKey::Exhausted))
+    }
+
+    /// Takes key `index` out of rotation for `cooldown`.
+    pub fn mark_exhausted(&self, index: usize, cooldown: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.exhausted_until[index] = Some(Instant::now() + cooldown);
+    }
+
+    /// Takes key `index` out of rotation until restart.
+    pub fn mark_rejected(&self, index: usize) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.rejected[index] = true;
+    }
+}
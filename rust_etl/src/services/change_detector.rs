@@ -0,0 +1,105 @@
+use crate::models::weather::WeatherData;
+use std::collections::{HashMap, HashSet};
+
+/// Minimum absolute difference for each tracked field to count as a change.
+#[derive(Debug, Clone)]
+pub struct ChangeTolerances {
+    pub temperature: f64,
+    pub humidity: i32,
+    pub pressure: i32,
+    pub wind_speed: f64,
+    pub wind_direction: f64,
+    /// Treat any change of `weather_main` (e.g. Clouds → Rain) as a change.
+    pub condition: bool,
+}
+
+impl Default for ChangeTolerances {
+    fn default() -> Self {
+        Self {
+            temperature: 0.1,
+            humidity: 1,
+            pressure: 1,
+            wind_speed: 0.1,
+            wind_direction: 10.0,
+            condition: true,
+        }
+    }
+}
+
+/// Remembers the last stored observation per city so unchanged readings can
+/// be skipped instead of inserted.
+pub struct ChangeDetector {
+    tolerances: ChangeTolerances,
+    last: HashMap<String, WeatherData>,
+    seeded: HashSet<String>,
+}
+
+impl ChangeDetector {
+    pub fn new(tolerances: ChangeTolerances) -> Self {
+        Self {
+            tolerances,
+            last: HashMap::new(),
+            seeded: HashSet::new(),
+        }
+    }
+
+    fn key(data: &WeatherData) -> String {
+        data.city.clone().unwrap_or_default()
+    }
+
+    /// Records `data` as the last stored value for its city.
+    pub fn record(&mut self, data: &WeatherData) {
+        self.seeded.insert(Self::key(data));
+        self.last.insert(Self::key(data), data.clone());
+    }
+
+    /// Whether `city` has been seeded from the database (or recorded) yet.
+    pub fn is_seeded(&self, city: &str) -> bool {
+        self.seeded.contains(city)
+    }
+
+    /// Seeds `city` with its latest stored row, if any, so the lookup is not
+    /// repeated for cities with no history.
+    pub fn seed(&mut self, city: &str, latest: Option<WeatherData>) {
+        self.seeded.insert(city.to_string());
+        if let Some(latest) = latest {
+            self.last.insert(city.to_string(), latest);
+        }
+    }
+
+    pub fn last_value(&self, city: &str) -> Option<&WeatherData> {
+        self.last.get(city)
+    }
+
+    /// Returns `true` when there is no previous value for the city or any
+    /// tracked field moved by at least its tolerance.
+    pub fn has_changed(&self, data: &WeatherData) -> bool {
+        let Some(previous) = self.last.get(&Self::key(data)) else {
+            return true;
+        };
+        let t = &self.tolerances;
+
+        (data.temperature - previous.temperature).abs() >= t.temperature
+            || (data.humidity - previous.humidity).abs() >= t.humidity
+            || option_changed(data.pressure, previous.pressure, |a, b| (a - b).abs() >= t.pressure)
+            || (data.wind_speed - previous.wind_speed).abs() >= t.wind_speed
+            || option_changed(data.wind_direction, previous.wind_direction, |a, b| {
+                angular_distance(a, b) >= t.wind_direction
+            })
+            || (t.condition && data.condition() != previous.condition())
+    }
+}
+
+fn option_changed<T: Copy>(current: Option<T>, previous: Option<T>, differs: impl Fn(T, T) -> bool) -> bool {
+    match (current, previous) {
+        (Some(a), Some(b)) => differs(a, b),
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+/// Smallest difference between two bearings, so 355° and 5° are 10° apart.
+fn angular_distance(a: f64, b: f64) -> f64 {
+    let diff = (a - b).rem_euclid(360.0);
+    diff.min(360.0 - diff)
+}
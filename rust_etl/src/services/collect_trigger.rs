@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+/// Request to run a collection cycle now instead of waiting for the interval.
+/// Answered with the report of the cycle it started.
+pub struct CollectTrigger {
+    pub respond: oneshot::Sender<CycleReport>,
+}
+
+impl CollectTrigger {
+    pub fn new() -> (Self, oneshot::Receiver<CycleReport>) {
+        let (respond, receiver) = oneshot::channel();
+        (Self { respond }, receiver)
+    }
+}
+
+/// What one collection cycle did.
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Cities attempted.
+    pub cities: usize,
+    pub fetched: usize,
+    pub failed: usize,
+    /// Observations handed to the insert writer (after sampling and
+    /// diff-only filtering).
+    pub queued: usize,
+}
+
+impl CycleReport {
+    pub fn start(cities: usize) -> Self {
+        let now = Utc::now();
+        Self {
+            started_at: now,
+            finished_at: now,
+            cities,
+            fetched: 0,
+            failed: 0,
+            queued: 0,
+        }
+    }
+}
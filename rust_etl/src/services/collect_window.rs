@@ -0,0 +1,119 @@
+//! `COLLECT_HOURS`: the local hours of the day during which cities are
+//! fetched.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Hours `start` (inclusive) to `end` (exclusive) of the local day, written
+/// `6-22`. A window such as `22-6` wraps past midnight; `0-24` is all day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CollectHours {
+    start: u32,
+    end: u32,
+}
+
+impl CollectHours {
+    /// Whether `timestamp` (Unix seconds) falls in the window at UTC offset
+    /// `offset` (seconds).
+    pub fn contains(&self, timestamp: i64, offset: i32) -> bool {
+        let hour = (timestamp + offset as i64).rem_euclid(86_400) as u32 / 3600;
+        if self.start < self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+impl FromStr for CollectHours {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid collection hours '{}': expected START-END in hours, e.g. 6-22", text);
+        let (start, end) = text.trim().split_once('-').ok_or_else(invalid)?;
+        let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+        let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+        if start > 23 || end > 24 || start == end {
+            return Err(invalid());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl TryFrom<String> for CollectHours {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<CollectHours> for String {
+    fn from(hours: CollectHours) -> Self {
+        hours.to_string()
+    }
+}
+
+impl fmt::Display for CollectHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// A fixed UTC offset written `+HH:MM` or `-HH:MM`, e.g. `-05:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UtcOffset(i32);
+
+impl UtcOffset {
+    pub fn seconds(self) -> i32 {
+        self.0
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid UTC offset '{}': expected e.g. -05:00 or +01:00", text);
+        let trimmed = text.trim();
+        let (sign, rest) = if let Some(rest) = trimmed.strip_prefix('+') {
+            (1, rest)
+        } else if let Some(rest) = trimmed.strip_prefix('-') {
+            (-1, rest)
+        } else {
+            return Err(invalid());
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes > 59 {
+            return Err(invalid());
+        }
+        Ok(Self(sign * (hours * 3600 + minutes * 60)))
+    }
+}
+
+impl TryFrom<String> for UtcOffset {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<UtcOffset> for String {
+    fn from(offset: UtcOffset) -> Self {
+        offset.to_string()
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let seconds = self.0.abs();
+        write!(f, "{}{:02}:{:02}", sign, seconds / 3600, seconds % 3600 / 60)
+    }
+}
@@ -0,0 +1,589 @@
+//! One collection cycle: fetch every configured city, fill in the derived
+//! fields and hand the rows to the sinks and the insert writer. Shared by the
+//! scheduled loop and manual `/collect` triggers.
+
+use crate::config::app_config::AppConfig;
+use crate::models::weather::WeatherData;
+use crate::services::change_detector::ChangeDetector;
+use crate::services::collect_trigger::CycleReport;
+use crate::services::collect_window::CollectHours;
+use crate::services::database::{DatabaseService, FetchAttempt};
+use crate::services::day_extremes::{self, DayExtremes};
+use crate::services::insert_writer::{InsertWriter, InsertedObservation};
+use crate::services::locations::LocationResolver;
+use crate::services::metrics::Metrics;
+use crate::services::pressure_trend::PressureTrendTracker;
+use crate::services::provider::{ProviderKind, WeatherProvider};
+use crate::services::runtime_state::{RuntimeState, STATE_VERSION};
+use crate::services::smoothing::TemperatureEma;
+use crate::services::storage_sampler::StorageSampler;
+use crate::services::weather_service;
+use crate::sinks::{self, WeatherSink};
+use crate::utils::retry::{retry_within, RetryBudget, RetryPolicy};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, oneshot};
+
+/// What happened to one configured city during a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CityStatus {
+    /// Fetched and handed to the insert writer.
+    Queued,
+    /// Fetched but not stored because of `STORE_EVERY_N` or
+    /// `STORAGE_RESOLUTION`.
+    SkippedSampled,
+    /// Fetched but not stored because nothing changed (`DIFF_ONLY_INSERT`).
+    SkippedUnchanged,
+    /// Fetched but the insert writer could not take the row, or a required
+    /// sink (`DATABASE_SINK_REQUIRED`) failed to write it.
+    NotQueued(String),
+    /// Every provider failed to fetch the city.
+    FetchFailed(String),
+    /// Not fetched: outside `COLLECT_HOURS` in the city's local time.
+    OutsideCollectHours,
+}
+
+#[derive(Debug, Clone)]
+pub struct CityOutcome {
+    /// The city as configured, which may be spelled differently from the
+    /// name the API returns.
+    pub city: String,
+    pub status: CityStatus,
+    /// Time spent fetching, including retries and the fallback provider.
+    pub fetch_duration: Duration,
+    /// Sinks, such as `database:<host>/<db>`, that failed to write the
+    /// observation. The others wrote it.
+    pub failed_sinks: Vec<String>,
+}
+
+/// Result of [`Collector::run_cycle`].
+#[derive(Debug)]
+pub struct CycleResult {
+    /// Error that must stop collection, such as a rejected API key. The
+    /// cities after the one that raised it were not attempted.
+    pub fatal: Option<anyhow::Error>,
+    /// Delay before the next cycle; longer than `ETL_INTERVAL` while an API
+    /// quota cools down.
+    pub next_run: Duration,
+    pub report: CycleReport,
+    /// Per-city outcomes, in configured order.
+    pub cities: Vec<CityOutcome>,
+}
+
+/// Fetches and stores observations for the configured cities, keeping the
+/// per-city state (change detection, averages, pressure trend, sampling)
+/// that carries over between cycles.
+pub struct Collector {
+    config: AppConfig,
+    primary: WeatherProvider,
+    fallback: Option<WeatherProvider>,
+    retry_policy: RetryPolicy,
+    database: Arc<DatabaseService>,
+    metrics: Arc<Metrics>,
+    insert_writer: InsertWriter,
+    sinks: Vec<Box<dyn WeatherSink>>,
+    change_detector: ChangeDetector,
+    /// Confirmed inserts, which update `change_detector` in diff-only mode.
+    written: Option<broadcast::Receiver<InsertedObservation>>,
+    temperature_ema: TemperatureEma,
+    day_extremes: DayExtremes,
+    pressure_trend: PressureTrendTracker,
+    storage_sampler: StorageSampler,
+    locations: LocationResolver,
+    /// UTC offset of each configured city's latest observation, for
+    /// `COLLECT_HOURS`.
+    utc_offsets: HashMap<String, i32>,
+    /// With `PIPELINE_CYCLES`, completes once the previous cycle's rows are
+    /// written.
+    previous_cycle_written: Option<oneshot::Receiver<()>>,
+}
+
+impl Collector {
+    pub fn new(
+        config: &AppConfig,
+        database: Arc<DatabaseService>,
+        metrics: Arc<Metrics>,
+        insert_writer: InsertWriter,
+        sinks: Vec<Box<dyn WeatherSink>>,
+    ) -> anyhow::Result<Self> {
+        let written = config.diff_only_insert.then(|| insert_writer.subscribe());
+        Ok(Self {
+            config: config.clone(),
+            primary: WeatherProvider::new(config.weather_provider, config)?,
+            fallback: config.fallback_provider.map(|kind| WeatherProvider::new(kind, config)).transpose()?,
+            retry_policy: config.fetch_retry_policy(),
+            database,
+            metrics,
+            insert_writer,
+            sinks,
+            change_detector: ChangeDetector::new(config.diff_tolerances()),
+            written,
+            temperature_ema: TemperatureEma::new(config.ema_alpha),
+            day_extremes: DayExtremes::new(),
+            pressure_trend: PressureTrendTracker::new(config.pressure_trend_threshold),
+            storage_sampler: StorageSampler::new(config.store_every_n, config.storage_resolution),
+            locations: LocationResolver::new(config.location_ids),
+            utc_offsets: HashMap::new(),
+            previous_cycle_written: None,
+        })
+    }
+
+    /// Loads the last stored row of each configured city for diff-only
+    /// insert mode.
+    pub async fn seed_change_detector(&mut self) {
+        for city in &self.config.cities {
+            match self.database.get_latest_weather(city).await {
+                Ok(latest) => self.change_detector.seed(city, latest),
+                Err(e) => log::warn!("⚠️  Could not seed diff-only cache for {}: {}", city, e),
+            }
+        }
+    }
+
+    /// The per-city running state, for `STATE_FILE`.
+    pub fn snapshot(&self) -> RuntimeState {
+        RuntimeState {
+            version: STATE_VERSION,
+            saved_at: chrono::Utc::now().timestamp(),
+            units: self.config.units,
+            temperature_ema: self.temperature_ema.snapshot(),
+            day_extremes: self.day_extremes.snapshot(),
+            last_pressure: self.pressure_trend.snapshot(),
+            storage_sampler: self.storage_sampler.snapshot(),
+            utc_offsets: self.utc_offsets.clone(),
+        }
+    }
+
+    /// Continues from a [`snapshot`](Self::snapshot) taken before a restart.
+    /// Temperatures saved under other `UNITS` are dropped, and the affected
+    /// cities are seeded from the database as usual.
+    pub fn restore(&mut self, state: RuntimeState) {
+        if state.units == self.config.units {
+            self.temperature_ema.restore(state.temperature_ema);
+            self.day_extremes.restore(state.day_extremes);
+        } else {
+            log::warn!(
+                "⚠️  Saved temperatures are in {} but UNITS={}; not restoring averages or daily ranges",
+                state.units,
+                self.config.units
+            );
+        }
+        self.pressure_trend.restore(state.last_pressure);
+        self.storage_sampler.restore(state.storage_sampler);
+        self.utc_offsets.extend(state.utc_offsets);
+    }
+
+    /// Rows waiting in the insert writer's queue.
+    pub fn pending_inserts(&self) -> usize {
+        self.insert_writer.pending_rows()
+    }
+
+    /// Hands back the insert writer and sinks for shutdown: dropping the
+    /// writer lets its task drain the queue and exit.
+    pub fn into_outputs(self) -> (InsertWriter, Vec<Box<dyn WeatherSink>>) {
+        (self.insert_writer, self.sinks)
+    }
+
+    /// Runs one collection over every configured city.
+    pub async fn run_cycle(&mut self) -> CycleResult {
+        let cities = self.config.cities.clone();
+        let mut result = CycleResult {
+            fatal: None,
+            next_run: self.config.interval,
+            report: CycleReport::start(cities.len()),
+            cities: Vec::with_capacity(cities.len()),
+        };
+        let mut cycle_rows = Vec::new();
+        // Fetch retries across every city; once spent, the rest get one attempt
+        let retry_budget = RetryBudget::new(self.config.cycle_retry_budget);
+
+        for configured in &cities {
+            if let Some(hours) = self.config.collect_hours {
+                if !self.in_collect_hours(configured, hours).await {
+                    self.metrics.incr("fetch.skipped_outside_hours", &[("city", configured)]);
+                    log::debug!("⏸️  Skipping {}: outside COLLECT_HOURS ({})", configured, hours);
+                    result.cities.push(CityOutcome {
+                        city: configured.clone(),
+                        status: CityStatus::OutsideCollectHours,
+                        fetch_duration: Duration::ZERO,
+                        failed_sinks: Vec::new(),
+                    });
+                    continue;
+                }
+            }
+            // Rows already collected this cycle are still queued below
+            if let Err(fatal) = self.collect_city(configured, &retry_budget, &mut result, &mut cycle_rows).await {
+                result.fatal = Some(fatal);
+                break;
+            }
+        }
+        if !cities.is_empty() && result.cities.iter().all(|outcome| outcome.status == CityStatus::OutsideCollectHours) {
+            log::info!(
+                "⏸️  Collection paused: outside COLLECT_HOURS ({}) for every city",
+                self.config.collect_hours.map(|hours| hours.to_string()).unwrap_or_default()
+            );
+        }
+        if retry_budget.is_exhausted() {
+            self.metrics.incr("fetch.retry_budget_exhausted", &[]);
+            log::warn!(
+                "⚠️  Cycle retry budget of {} spent (CYCLE_RETRY_BUDGET); later fetches were not retried",
+                self.config.cycle_retry_budget
+            );
+        }
+
+        // With PIPELINE_CYCLES this cycle fetched while the previous one was
+        // written; its rows are handed over once that finishes
+        if let Some(written) = self.previous_cycle_written.take() {
+            let started = Instant::now();
+            let _ = written.await;
+            self.metrics.timing("insert.pipeline_wait", started.elapsed(), &[]);
+        }
+
+        // With CYCLE_TRANSACTION the cycle's rows are stored all-or-nothing
+        let cycle_len = cycle_rows.len();
+        let queued = if self.config.cycle_transaction {
+            self.insert_writer.enqueue_cycle(cycle_rows.clone()).await
+        } else {
+            self.insert_writer.enqueue_rows(cycle_rows.clone()).await
+        };
+        if queued.is_ok() {
+            for data in &cycle_rows {
+                self.fold_reading(data, true);
+            }
+        }
+        if let Err(e) = queued {
+            self.metrics.incr("insert.failure", &[]);
+            log::error!("❌ Could not queue cycle for insert: {}", e);
+            result.report.queued -= cycle_len;
+            for outcome in &mut result.cities {
+                if outcome.status == CityStatus::Queued {
+                    outcome.status = CityStatus::NotQueued(e.to_string());
+                }
+            }
+        }
+        if self.config.pipeline_cycles {
+            self.previous_cycle_written = self.insert_writer.flush().await.ok();
+        }
+
+        result
+    }
+
+    /// Whether it is now within `hours` for `configured`, by
+    /// `COLLECT_HOURS_UTC_OFFSET` or else the offset of the city's latest
+    /// observation. A city whose offset isn't known yet is fetched, which
+    /// provides it.
+    async fn in_collect_hours(&mut self, configured: &str, hours: CollectHours) -> bool {
+        let offset = match self.config.collect_hours_utc_offset {
+            Some(offset) => Some(offset.seconds()),
+            None => {
+                if !self.utc_offsets.contains_key(configured) {
+                    match self.database.get_latest_weather(configured).await {
+                        Ok(Some(latest)) => {
+                            if let Some(offset) = latest.timezone {
+                                self.utc_offsets.insert(configured.to_string(), offset);
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => log::warn!("⚠️  Could not load the UTC offset of {}: {}", configured, e),
+                    }
+                }
+                self.utc_offsets.get(configured).copied()
+            }
+        };
+
+        offset.is_none_or(|offset| hours.contains(chrono::Utc::now().timestamp(), offset))
+    }
+
+    /// Fetches `configured` and stores or skips the observation, recording
+    /// the outcome in `result`. Returns `Err` only for errors that must stop
+    /// collection.
+    async fn collect_city(
+        &mut self,
+        configured: &str,
+        retry_budget: &RetryBudget,
+        result: &mut CycleResult,
+        cycle_rows: &mut Vec<WeatherData>,
+    ) -> anyhow::Result<()> {
+        let tags = [("city", configured)];
+        let fetch_started = Instant::now();
+        let mut fetched = retry_within(&self.retry_policy, retry_budget, "Weather fetch", weather_service::is_retryable, || {
+            self.fetch(&self.primary, configured)
+        })
+        .await;
+
+        let mut primary_wait = None;
+        let primary_fatal = matches!(&fetched, Err(e) if weather_service::is_fatal(e));
+        if let Err(e) = &fetched {
+            if !primary_fatal {
+                primary_wait = weather_service::cooldown(e, self.config.api_quota_reset);
+            }
+        }
+
+        // Fall back for this cycle only; the next one starts with the primary again
+        if let (Err(e), Some(fallback)) = (&fetched, &self.fallback) {
+            log::warn!("⚠️  {} fetch failed ({}); falling back to {}", self.primary.kind(), e, fallback.kind());
+            self.metrics.incr("fetch.fallback", &tags);
+            let fallen_back = retry_within(&self.retry_policy, retry_budget, "Fallback weather fetch", weather_service::is_retryable, || {
+                self.fetch(fallback, configured)
+            })
+            .await;
+            match fallen_back {
+                Ok(_) => {
+                    primary_wait = None;
+                    fetched = fallen_back;
+                }
+                // Keep the primary's fatal error: with the fallback down too, nothing can collect
+                Err(ref fallback_error) if primary_fatal => {
+                    log::warn!("⚠️  {} fallback failed too: {}", fallback.kind(), fallback_error);
+                }
+                Err(_) => fetched = fallen_back,
+            }
+        }
+        if let Some(wait) = primary_wait {
+            result.next_run = result.next_run.max(wait);
+        }
+        let fetch_duration = fetch_started.elapsed();
+        self.metrics.timing("fetch.duration", fetch_duration, &tags);
+
+        let mut failed_sinks = Vec::new();
+        let status = match fetched {
+            Ok(weather_data) => {
+                self.metrics.incr("fetch.success", &tags);
+                if let Some(offset) = weather_data.timezone {
+                    self.utc_offsets.insert(configured.to_string(), offset);
+                }
+                result.report.fetched += 1;
+                let status = self.store(configured, weather_data, cycle_rows, &mut failed_sinks).await;
+                if status == CityStatus::Queued {
+                    result.report.queued += 1;
+                }
+                status
+            }
+            Err(e) => {
+                self.metrics.incr("fetch.failure", &tags);
+                result.report.failed += 1;
+                log::warn!("⚠️  Failed to fetch weather data: {}", e);
+                if weather_service::is_fatal(&e) {
+                    return Err(e);
+                }
+                if let Some(wait) = weather_service::cooldown(&e, self.config.api_quota_reset) {
+                    result.next_run = result.next_run.max(wait);
+                }
+                log::warn!("   Will retry in {} seconds...", result.next_run.as_secs());
+                CityStatus::FetchFailed(format!("{:#}", e))
+            }
+        };
+
+        result.cities.push(CityOutcome {
+            city: configured.to_string(),
+            status,
+            fetch_duration,
+            failed_sinks,
+        });
+        Ok(())
+    }
+
+    /// Fetches `city` from `provider` once, recording the attempt in
+    /// `fetch_log` when `FETCH_LOG` is set.
+    async fn fetch(&self, provider: &WeatherProvider, city: &str) -> anyhow::Result<WeatherData> {
+        let started = Instant::now();
+        let fetched = provider.fetch_weather(city).await;
+        if !self.config.fetch_log {
+            return fetched;
+        }
+
+        let kind = provider.kind();
+        let attempt = match &fetched {
+            Ok(_) => FetchAttempt {
+                city,
+                provider: kind.name(),
+                http_status: Some(200),
+                api_code: (kind == ProviderKind::OpenWeatherMap).then_some(200),
+                error: None,
+                latency: started.elapsed(),
+            },
+            Err(e) => FetchAttempt {
+                city,
+                provider: kind.name(),
+                http_status: weather_service::http_status(e),
+                api_code: weather_service::api_code(e),
+                error: Some(format!("{:#}", e)),
+                latency: started.elapsed(),
+            },
+        };
+        if let Err(e) = self.database.log_fetch_attempt(&attempt).await {
+            log::warn!("⚠️  Could not record fetch attempt for {}: {:#}", city, e);
+        }
+        fetched
+    }
+
+    /// Takes the rows the insert writer has confirmed since the last call as
+    /// the last stored values for diff-only mode. A row still queued, or one
+    /// whose insert failed, is never compared against.
+    fn record_written(&mut self) {
+        let Some(written) = &mut self.written else { return };
+        loop {
+            match written.try_recv() {
+                Ok(inserted) => self.change_detector.record(&inserted.observation),
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    log::warn!("⚠️  Missed {} confirmed insert(s); diff-only mode may compare against older rows", missed);
+                }
+                Err(_) => return,
+            }
+        }
+    }
+
+    /// Folds a reading into the per-city running state, including the
+    /// storage sampler, once it has been handed to the insert writer or
+    /// skipped on purpose. `stored` readings also become the previous
+    /// pressure for the trend.
+    fn fold_reading(&mut self, data: &WeatherData, stored: bool) {
+        let city = data.city.as_deref().unwrap_or("Unknown");
+        self.storage_sampler.record(city, data.timestamp);
+        self.temperature_ema.update(city, data.temperature);
+        if self.config.day_extremes {
+            self.day_extremes.update(city, data.timestamp, data.timezone.unwrap_or(0), data.temperature);
+        }
+        if stored {
+            self.pressure_trend.record(city, data.pressure);
+        }
+    }
+
+    /// Counts and logs each sink's write, adding the failed ones to
+    /// `failed_sinks`. Returns why the first required sink failed, if one did.
+    fn record_sink_results(
+        &self,
+        results: Vec<(&dyn WeatherSink, anyhow::Result<()>)>,
+        failed_sinks: &mut Vec<String>,
+    ) -> Option<String> {
+        let mut required_failure = None;
+        for (sink, result) in results {
+            match result {
+                Ok(()) => self.metrics.incr("sink.success", &[("sink", sink.name())]),
+                Err(e) => {
+                    self.metrics.incr("sink.failure", &[("sink", sink.name())]);
+                    log::warn!("⚠️  Failed to write to {} sink: {:#}", sink.name(), e);
+                    failed_sinks.push(sink.name().to_string());
+                    if sink.is_required() && required_failure.is_none() {
+                        required_failure = Some(format!("required sink {} failed: {:#}", sink.name(), e));
+                    }
+                }
+            }
+        }
+        required_failure
+    }
+
+    /// Derives the per-city fields of a fetched observation, then writes it
+    /// to the sinks (required ones first) and queues it unless sampling or
+    /// diff-only mode skips it. A skipped observation still goes to the sinks
+    /// that don't store it. Sinks that failed are added to `failed_sinks`.
+    /// The running state, sampling included, only takes the reading in once
+    /// it is queued or skipped, so a failed write leaves it unchanged.
+    async fn store(
+        &mut self,
+        configured: &str,
+        mut weather_data: WeatherData,
+        cycle_rows: &mut Vec<WeatherData>,
+        failed_sinks: &mut Vec<String>,
+    ) -> CityStatus {
+        self.record_written();
+        let config = &self.config;
+        let tags = [("city", configured)];
+        weather_data.labels = config.labels_for(configured);
+        let city = weather_data.city.clone().unwrap_or_else(|| "Unknown".to_string());
+        let city = city.as_str();
+        weather_data.location_id = self.locations.resolve(&self.database, city).await;
+
+        // The API may spell the city differently from CITY; compare
+        // against the last row stored under the returned name
+        if config.diff_only_insert && !self.change_detector.is_seeded(city) {
+            match self.database.get_latest_weather(city).await {
+                Ok(latest) => self.change_detector.seed(city, latest),
+                Err(e) => log::warn!("⚠️  Could not load last stored value for {}: {}", city, e),
+            }
+        }
+
+        if config.ema_seed_from_db && !self.temperature_ema.is_seeded(city) {
+            match self.database.get_latest_weather(city).await {
+                Ok(latest) => {
+                    let previous = latest
+                        .filter(|row| row.units() == weather_data.units())
+                        .map(|row| row.temperature_ema.unwrap_or(row.temperature));
+                    self.temperature_ema.seed(city, previous);
+                }
+                Err(e) => log::warn!("⚠️  Could not load last temperature average for {}: {}", city, e),
+            }
+        }
+        weather_data.temperature_ema = Some(self.temperature_ema.next(city, weather_data.temperature));
+
+        if config.day_extremes {
+            let offset = weather_data.timezone.unwrap_or(0);
+            if !self.day_extremes.is_seeded(city) {
+                let since = day_extremes::local_midnight(weather_data.timestamp, offset);
+                match self.database.temperature_range_since(city, since, weather_data.units()).await {
+                    Ok(range) => self.day_extremes.seed(city, weather_data.timestamp, offset, range),
+                    Err(e) => log::warn!("⚠️  Could not load today's stored temperatures for {}: {}", city, e),
+                }
+            }
+            let (low, high) = self.day_extremes.range(city, weather_data.timestamp, offset, weather_data.temperature);
+            weather_data.day_low = Some(low);
+            weather_data.day_high = Some(high);
+        }
+
+        if !self.pressure_trend.is_seeded(city) {
+            match self.database.get_latest_weather(city).await {
+                Ok(latest) => self.pressure_trend.seed(city, latest.and_then(|row| row.pressure)),
+                Err(e) => log::warn!("⚠️  Could not load last stored pressure for {}: {}", city, e),
+            }
+        }
+        weather_data.pressure_trend = Some(self.pressure_trend.trend(city, weather_data.pressure).to_string());
+
+        // Readings skipped on purpose still reach the non-storage sinks and
+        // feed the average and the day's range; ones that fail to be stored don't
+        let skipped = if !self.storage_sampler.should_store(city, weather_data.timestamp) {
+            self.metrics.incr("insert.skipped_sampled", &tags);
+            log::debug!("⏭️  Not storing this fetch for {} (STORE_EVERY_N / STORAGE_RESOLUTION)", city);
+            Some(CityStatus::SkippedSampled)
+        } else if config.diff_only_insert && !self.change_detector.has_changed(&weather_data) {
+            self.metrics.incr("insert.skipped_unchanged", &tags);
+            log::info!(
+                "⏭️  Skipping insert for {}: no change beyond tolerances since last stored value",
+                city
+            );
+            Some(CityStatus::SkippedUnchanged)
+        } else {
+            None
+        };
+        if let Some(status) = skipped {
+            let published = self.sinks.iter().filter(|sink| !sink.is_storage());
+            let results = sinks::write_all(published, &weather_data).await;
+            self.record_sink_results(results, failed_sinks);
+            self.fold_reading(&weather_data, false);
+            return status;
+        }
+
+        let results = sinks::write_required_first(&self.sinks, &weather_data).await;
+        if let Some(reason) = self.record_sink_results(results, failed_sinks) {
+            self.metrics.incr("insert.skipped_sink_failure", &tags);
+            log::error!("❌ Not storing {} in the primary database: {}", city, reason);
+            return CityStatus::NotQueued(reason);
+        }
+
+        // Folded in by run_cycle once the cycle is queued
+        if config.cycle_transaction || config.pipeline_cycles {
+            cycle_rows.push(weather_data);
+            return CityStatus::Queued;
+        }
+        match self.insert_writer.enqueue(weather_data.clone()).await {
+            Ok(()) => {
+                self.fold_reading(&weather_data, true);
+                CityStatus::Queued
+            }
+            Err(e) => {
+                self.metrics.incr("insert.failure", &tags);
+                log::error!("❌ Could not queue weather data for insert: {}", e);
+                CityStatus::NotQueued(e.to_string())
+            }
+        }
+    }
+}
@@ -1,10 +1,359 @@
-use crate::models::weather::WeatherData;
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use crate::models::units::Units;
+use crate::models::weather::{ComputedColumns, WeatherData};
+use crate::utils::redact;
+use crate::utils::retry::{retry, RetryPolicy};
+use sqlx::{Connection, PgConnection, PgPool, Postgres, QueryBuilder, migrate::{Migrate, Migrator}, postgres::{PgArguments, PgPoolOptions}, query::QueryAs, types::Json};
+use std::collections::HashSet;
+use thiserror::Error;
 use std::time::Duration;
 use anyhow::{Result, Context};
 
+/// Schema migrations from `rust_etl/migrations`, embedded at build time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Where a migration stands against the database; see
+/// [`DatabaseService::migration_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file has changed since; `migrate` refuses to run
+    /// until it is restored.
+    Modified,
+    /// Recorded in the database but not part of this build, e.g. applied by
+    /// a newer version.
+    Unknown,
+}
+
+impl MigrationState {
+    pub fn name(self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    /// Empty for [`MigrationState::Unknown`].
+    pub description: String,
+    pub state: MigrationState,
+}
+
+/// Columns selected whenever rows are read back into [`WeatherData`].
+const WEATHER_COLUMNS: &str = r#"
+    city,
+    temperature,
+    feels_like,
+    humidity,
+    pressure,
+    sea_level_pressure,
+    ground_level_pressure,
+    wind_speed,
+    wind_direction,
+    weather_main,
+    weather_description,
+    weather_icon,
+    weather_id,
+    timestamp,
+    timezone,
+    timezone_name,
+    uv_index,
+    dew_point,
+    wind_chill,
+    minutes_to_precip,
+    temperature_ema,
+    day_high,
+    day_low,
+    comfort_category,
+    pressure_trend,
+    api_latency_ms,
+    source,
+    station_base,
+    station_id,
+    station_type,
+    units,
+    timestamp_suspect,
+    labels,
+    location_id
+"#;
+
+/// Columns the ETL writes to; checked by `rust_etl doctor`.
+pub const REQUIRED_COLUMNS: &[&str] = &[
+    "city",
+    "temperature",
+    "feels_like",
+    "humidity",
+    "pressure",
+    "sea_level_pressure",
+    "ground_level_pressure",
+    "wind_speed",
+    "wind_direction",
+    "weather_main",
+    "weather_description",
+    "weather_icon",
+    "weather_id",
+    "timestamp",
+    "timezone",
+    "timezone_name",
+    "uv_index",
+    "dew_point",
+    "wind_chill",
+    "minutes_to_precip",
+    "temperature_ema",
+    "day_high",
+    "day_low",
+    "comfort_category",
+    "pressure_trend",
+    "api_latency_ms",
+    "source",
+    "station_base",
+    "station_id",
+    "station_type",
+    "units",
+    "timestamp_suspect",
+    "labels",
+    "location_id",
+];
+
+/// Newest observation first. Providers can repeat `dt` across polls, so ties
+/// go to the most recently ingested row (by `id` within one transaction,
+/// where `created_at` is shared).
+const NEWEST_FIRST: &str = "timestamp DESC, created_at DESC NULLS LAST, id DESC";
+
+/// Upper bound on rows returned by [`DatabaseService::get_recent`].
+pub const MAX_RECENT_LIMIT: i64 = 1000;
+
+/// Result of [`DatabaseService::insert_or_dead_letter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    Inserted(InsertedRow),
+    DeadLettered,
+}
+
+/// Identity the database assigned to a stored row, read back with
+/// `RETURNING` so a successful insert is confirmed rather than assumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::FromRow)]
+pub struct InsertedRow {
+    pub id: i32,
+    pub created_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Whether an insert error was caused by the row itself (SQLSTATE class 22
+/// data exception or 23 integrity violation) rather than by the connection,
+/// so retrying it cannot succeed.
+pub fn is_data_error(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db_err)) => db_err
+            .code()
+            .map(|code| code.starts_with("22") || code.starts_with("23"))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// One weather API fetch attempt for the `fetch_log` table.
+#[derive(Debug, Clone)]
+pub struct FetchAttempt<'a> {
+    pub city: &'a str,
+    pub provider: &'static str,
+    /// `None` when no response was received.
+    pub http_status: Option<u16>,
+    /// OpenWeatherMap's `cod`, when the response carried one.
+    pub api_code: Option<i32>,
+    pub error: Option<String>,
+    pub latency: Duration,
+}
+
+/// Optional filters selecting the rows a maintenance command touches.
+#[derive(Debug, Clone, Default)]
+pub struct RowScope {
+    pub city: Option<String>,
+    /// Inclusive lower bound on the observation `timestamp` (Unix seconds).
+    pub from: Option<i64>,
+    /// Inclusive upper bound on the observation `timestamp` (Unix seconds).
+    pub to: Option<i64>,
+}
+
+/// How many of the expected observation slots in `[from, to)` hold at
+/// least one row, from [`DatabaseService::coverage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub from: i64,
+    pub to: i64,
+    pub interval: Duration,
+    pub expected: i64,
+    pub collected: i64,
+    /// Runs of consecutive empty slots, oldest first.
+    pub gaps: Vec<Gap>,
+}
+
+/// Consecutive empty slots from `start` (inclusive) to `end` (exclusive),
+/// in Unix seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Gap {
+    pub fn seconds(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+impl Coverage {
+    /// Builds the report from the sorted, distinct indexes of the slots that
+    /// hold data; slot `n` starts at `from + n * interval`.
+    pub fn from_slots(from: i64, to: i64, interval: Duration, filled: &[i64]) -> Self {
+        let step = interval.as_secs().max(1) as i64;
+        let expected = ((to - from).max(0) + step - 1) / step;
+        let slot_start = |slot: i64| (from + slot * step).min(to);
+
+        let mut gaps = Vec::new();
+        let mut next = 0;
+        for &slot in filled.iter().chain(std::iter::once(&expected)) {
+            if slot > next {
+                gaps.push(Gap {
+                    start: slot_start(next),
+                    end: slot_start(slot),
+                });
+            }
+            next = slot + 1;
+        }
+
+        Self {
+            from,
+            to,
+            interval,
+            expected,
+            collected: filled.len() as i64,
+            gaps,
+        }
+    }
+
+    /// Fraction of expected slots collected, from 0 to 1.
+    pub fn ratio(&self) -> f64 {
+        if self.expected == 0 {
+            return 1.0;
+        }
+        self.collected as f64 / self.expected as f64
+    }
+
+    pub fn largest_gap(&self) -> Option<Gap> {
+        self.gaps.iter().copied().max_by_key(|gap| (gap.seconds(), -gap.start))
+    }
+}
+
+/// The single-row insert shared by the pool and transaction paths.
+fn insert_query(data: &WeatherData) -> QueryAs<'_, Postgres, InsertedRow, PgArguments> {
+    sqlx::query_as(
+        r#"
+        INSERT INTO weather_data (
+            city, temperature, feels_like, humidity, pressure, sea_level_pressure, ground_level_pressure,
+            wind_speed, wind_direction, weather_main, weather_description,
+            weather_icon, weather_id, timestamp, timezone, timezone_name, uv_index, dew_point,
+            wind_chill, minutes_to_precip, temperature_ema, day_high, day_low, comfort_category, pressure_trend,
+            api_latency_ms, source, station_base, station_id, station_type, units, timestamp_suspect, labels,
+            location_id
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+            $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34
+        )
+        RETURNING id, created_at
+        "#
+    )
+    .bind(&data.city)
+    .bind(data.temperature)
+    .bind(data.feels_like)
+    .bind(data.humidity)
+    .bind(data.pressure)
+    .bind(data.sea_level_pressure)
+    .bind(data.ground_level_pressure)
+    .bind(data.wind_speed)
+    .bind(data.wind_direction)
+    .bind(&data.weather_main)
+    .bind(&data.weather_description)
+    .bind(&data.weather_icon)
+    .bind(data.weather_id)
+    .bind(data.timestamp)
+    .bind(data.timezone)
+    .bind(&data.timezone_name)
+    .bind(data.uv_index)
+    .bind(data.dew_point)
+    .bind(data.wind_chill)
+    .bind(data.minutes_to_precip)
+    .bind(data.temperature_ema)
+    .bind(data.day_high)
+    .bind(data.day_low)
+    .bind(&data.comfort_category)
+    .bind(&data.pressure_trend)
+    .bind(data.api_latency_ms)
+    .bind(&data.source)
+    .bind(&data.station_base)
+    .bind(data.station_id)
+    .bind(data.station_type)
+    .bind(&data.units)
+    .bind(data.timestamp_suspect)
+    .bind(Json(&data.labels))
+    .bind(data.location_id)
+}
+
+#[derive(sqlx::FromRow)]
+struct IdentifiedWeather {
+    id: i32,
+    #[sqlx(flatten)]
+    data: WeatherData,
+}
+
 pub struct DatabaseService {
     pool: PgPool,
+    #[cfg(feature = "fault-injection")]
+    faults: crate::services::db_faults::FaultInjector,
+}
+
+/// A failed connection attempt, with the URL's password masked out of the
+/// driver's message.
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct ConnectError {
+    message: String,
+    transient: bool,
+}
+
+impl ConnectError {
+    fn new(error: &sqlx::Error, database_url: &str) -> Self {
+        // SQLSTATE class 08 is a connection exception; 57P03 is a server
+        // still starting up or shutting down, 53300 one out of connections
+        let transient = match error {
+            sqlx::Error::Io(_) | sqlx::Error::Tls(_) | sqlx::Error::PoolTimedOut => true,
+            sqlx::Error::Database(e) => e
+                .code()
+                .is_some_and(|code| code.starts_with("08") || code == "57P03" || code == "53300"),
+            _ => false,
+        };
+        Self {
+            message: redact::mask_url_in(&error.to_string(), database_url),
+            transient,
+        }
+    }
+
+    /// Whether trying again later could succeed: the server was unreachable
+    /// or not yet accepting connections, rather than refusing these
+    /// credentials or this database.
+    pub fn is_transient(&self) -> bool {
+        self.transient
+    }
+}
+
+/// Whether a connection error is worth retrying. Errors that aren't a
+/// [`ConnectError`] come after a successful connection and are retried.
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<ConnectError>().is_none_or(ConnectError::is_transient)
 }
 
 impl DatabaseService {
@@ -14,86 +363,525 @@ impl DatabaseService {
             .acquire_timeout(Duration::from_secs(30))
             .connect(database_url)
             .await
-            .context("Failed to connect to database")?;
+            .map_err(|e| anyhow::anyhow!(redact::mask_url_in(&e.to_string(), database_url)))
+            .with_context(|| format!("Failed to connect to database at {}", redact::mask_url(database_url)))?;
+
+        Ok(Self {
+            pool,
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
+        })
+    }
+
+    /// [`new`](Self::new), retried with `policy`'s backoff while the database
+    /// is unreachable, e.g. when it starts alongside the service. Each
+    /// attempt is a single connection, which fails as soon as the server
+    /// refuses it rather than after the pool's acquire timeout. Rejected
+    /// credentials, a missing database or an invalid URL fail at once.
+    pub async fn connect_with_retry(database_url: &str, policy: &RetryPolicy) -> Result<Self> {
+        let mut attempt = 0;
+        retry(policy, "Database connection", is_transient, || {
+            attempt += 1;
+            log::info!("🔌 Connecting to database (attempt {}/{})", attempt, policy.max_attempts);
+            async move {
+                let probe = PgConnection::connect(database_url)
+                    .await
+                    .map_err(|e| ConnectError::new(&e, database_url))
+                    .with_context(|| format!("Failed to connect to database at {}", redact::mask_url(database_url)))?;
+                let _ = probe.close().await;
+                Self::new(database_url).await
+            }
+        })
+        .await
+    }
+
+    /// Like [`new`](Self::new), but connects on first use instead of up
+    /// front, and gives up on a connection after `acquire_timeout`. Only an
+    /// invalid URL fails here.
+    pub fn connect_lazy(database_url: &str, acquire_timeout: Duration) -> Result<Self> {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(acquire_timeout)
+            .connect_lazy(database_url)
+            .map_err(|e| anyhow::anyhow!(redact::mask_url_in(&e.to_string(), database_url)))
+            .with_context(|| format!("Invalid database URL {}", redact::mask_url(database_url)))?;
+
+        Ok(Self {
+            pool,
+            #[cfg(feature = "fault-injection")]
+            faults: Default::default(),
+        })
+    }
+
+    /// Closes the pool, waiting for checked-out connections to be returned.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    /// Failures to inject into the next insert statements.
+    #[cfg(feature = "fault-injection")]
+    pub fn faults(&self) -> &crate::services::db_faults::FaultInjector {
+        &self.faults
+    }
+
+    pub async fn insert_weather_data(&self, data: &WeatherData) -> Result<InsertedRow> {
+        #[cfg(feature = "fault-injection")]
+        self.faults.check().context("Failed to insert weather data")?;
+
+        insert_query(data)
+            .fetch_one(&self.pool)
+            .await
+            .context("Failed to insert weather data")
+    }
+
+    /// Inserts all of `rows` in one transaction, one statement per row, and
+    /// rolls everything back if any insert fails.
+    pub async fn insert_cycle(&self, rows: &[WeatherData]) -> Result<Vec<InsertedRow>> {
+        let mut tx = self.pool.begin().await.context("Failed to begin cycle transaction")?;
+
+        let mut inserted = Vec::with_capacity(rows.len());
+        for data in rows {
+            #[cfg(feature = "fault-injection")]
+            self.faults.check().context("Failed to insert weather data in cycle transaction")?;
+
+            let row = insert_query(data)
+                .fetch_one(&mut *tx)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to insert weather data for {} in cycle transaction",
+                        data.city.as_deref().unwrap_or("Unknown")
+                    )
+                })?;
+            inserted.push(row);
+        }
+
+        tx.commit().await.context("Failed to commit cycle transaction")?;
+        Ok(inserted)
+    }
+
+    /// Inserts all of `rows` in a single multi-row statement; either every
+    /// row is stored or none is. Returns the stored rows in input order.
+    pub async fn insert_batch(&self, rows: &[WeatherData]) -> Result<Vec<InsertedRow>> {
+        #[cfg(feature = "fault-injection")]
+        self.faults.check().context("Failed to insert weather data batch")?;
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO weather_data (city, temperature, feels_like, humidity, pressure, \
+             sea_level_pressure, ground_level_pressure, \
+             wind_speed, wind_direction, weather_main, weather_description, \
+             weather_icon, weather_id, timestamp, timezone, timezone_name, uv_index, dew_point, \
+             wind_chill, minutes_to_precip, temperature_ema, day_high, day_low, comfort_category, pressure_trend, api_latency_ms, source, station_base, station_id, station_type, units, timestamp_suspect, labels, location_id) ",
+        );
+        query.push_values(rows, |mut row, data| {
+            row.push_bind(&data.city)
+                .push_bind(data.temperature)
+                .push_bind(data.feels_like)
+                .push_bind(data.humidity)
+                .push_bind(data.pressure)
+                .push_bind(data.sea_level_pressure)
+                .push_bind(data.ground_level_pressure)
+                .push_bind(data.wind_speed)
+                .push_bind(data.wind_direction)
+                .push_bind(&data.weather_main)
+                .push_bind(&data.weather_description)
+                .push_bind(&data.weather_icon)
+                .push_bind(data.weather_id)
+                .push_bind(data.timestamp)
+                .push_bind(data.timezone)
+                .push_bind(&data.timezone_name)
+                .push_bind(data.uv_index)
+                .push_bind(data.dew_point)
+                .push_bind(data.wind_chill)
+                .push_bind(data.minutes_to_precip)
+                .push_bind(data.temperature_ema)
+                .push_bind(data.day_high)
+                .push_bind(data.day_low)
+                .push_bind(&data.comfort_category)
+                .push_bind(&data.pressure_trend)
+                .push_bind(data.api_latency_ms)
+                .push_bind(&data.source)
+                .push_bind(&data.station_base)
+                .push_bind(data.station_id)
+                .push_bind(data.station_type)
+                .push_bind(&data.units)
+                .push_bind(data.timestamp_suspect)
+                .push_bind(Json(&data.labels))
+                .push_bind(data.location_id);
+        });
+
+        query.push(" RETURNING id, created_at");
+
+        let mut inserted: Vec<InsertedRow> = query
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to insert weather data batch")?;
 
-        Ok(Self { pool })
+        if inserted.len() != rows.len() {
+            return Err(anyhow::anyhow!(
+                "batch insert confirmed {} of {} rows",
+                inserted.len(),
+                rows.len()
+            ));
+        }
+        // SERIAL ids follow VALUES order within one statement
+        inserted.sort_by_key(|row| row.id);
+        Ok(inserted)
     }
 
-    pub async fn insert_weather_data(&self, data: &WeatherData) -> Result<()> {
+    /// Inserts `data`, retrying up to `max_attempts` times. A row that keeps
+    /// failing with a data error is moved to `dead_letter` so it cannot block
+    /// the pipeline; connection errors are returned to the caller instead.
+    pub async fn insert_or_dead_letter(&self, data: &WeatherData, max_attempts: u32) -> Result<InsertOutcome> {
+        let mut attempt = 1;
+        loop {
+            let err = match self.insert_weather_data(data).await {
+                Ok(row) => return Ok(InsertOutcome::Inserted(row)),
+                Err(e) => e,
+            };
+
+            if !is_data_error(&err) {
+                return Err(err);
+            }
+
+            if attempt >= max_attempts {
+                let error_text = format!("{:#}", err);
+                self.insert_dead_letter(data, &error_text, attempt).await?;
+                log::error!(
+                    "☠️  Dead-lettered observation for {} (timestamp {}) after {} failed attempts: {}",
+                    data.city.as_deref().unwrap_or("Unknown"),
+                    data.timestamp,
+                    attempt,
+                    error_text
+                );
+                return Ok(InsertOutcome::DeadLettered);
+            }
+
+            log::warn!("⚠️  Insert attempt {}/{} failed: {:#}", attempt, max_attempts, err);
+            attempt += 1;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    pub async fn insert_dead_letter(&self, data: &WeatherData, error: &str, attempts: u32) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO weather_data (
-                city, temperature, feels_like, humidity, pressure,
-                wind_speed, wind_direction, weather_main, weather_description,
-                weather_icon, timestamp, timezone
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            INSERT INTO dead_letter (city, payload, error, attempts)
+            VALUES ($1, $2, $3, $4)
             "#
         )
         .bind(&data.city)
-        .bind(data.temperature)
-        .bind(data.feels_like)
-        .bind(data.humidity)
-        .bind(data.pressure)
-        .bind(data.wind_speed)
-        .bind(data.wind_direction)
-        .bind(&data.weather_main)
-        .bind(&data.weather_description)
-        .bind(&data.weather_icon)
-        .bind(data.timestamp)
-        .bind(data.timezone)
+        .bind(Json(data))
+        .bind(error)
+        .bind(attempts as i32)
+        .execute(&self.pool)
+        .await
+        .context("Failed to insert dead letter")?;
+
+        Ok(())
+    }
+
+    pub async fn log_fetch_attempt(&self, attempt: &FetchAttempt<'_>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO fetch_log (city, provider, http_status, api_code, error, latency_ms)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(attempt.city)
+        .bind(attempt.provider)
+        .bind(attempt.http_status.map(i32::from))
+        .bind(attempt.api_code)
+        .bind(&attempt.error)
+        .bind(i32::try_from(attempt.latency.as_millis()).unwrap_or(i32::MAX))
         .execute(&self.pool)
         .await
-        .context("Failed to insert weather data")?;
+        .context("Failed to log fetch attempt")?;
 
         Ok(())
     }
 
+    /// Id of the `locations` row named `name`, compared case-insensitively.
+    pub async fn find_location_id(&self, name: &str) -> Result<Option<i32>> {
+        sqlx::query_scalar("SELECT id FROM locations WHERE LOWER(name) = LOWER($1)")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up location")
+    }
+
+    /// Id of the `locations` row named `name`, adding one with only the name
+    /// if there is none; the other metadata is left for the user to fill in.
+    pub async fn find_or_create_location(&self, name: &str) -> Result<i32> {
+        let created: Option<i32> = sqlx::query_scalar(
+            r#"
+            INSERT INTO locations (name) VALUES ($1)
+            ON CONFLICT (LOWER(name)) DO NOTHING
+            RETURNING id
+            "#
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to add location")?;
+
+        match created {
+            Some(id) => Ok(id),
+            None => self
+                .find_location_id(name)
+                .await?
+                .context("Location vanished after a conflicting insert"),
+        }
+    }
+
     pub async fn get_latest_weather(&self, city: &str) -> Result<Option<WeatherData>> {
-        // Using query instead of query! to avoid compile-time database verification
-        let record: Option<(Option<String>, f64, Option<f64>, i32, Option<i32>, f64, Option<f64>, Option<String>, Option<String>, Option<String>, i64, Option<i32>)> =
-            sqlx::query_as(
-                r#"
-                SELECT
-                    city,
-                    temperature,
-                    feels_like,
-                    humidity,
-                    pressure,
-                    wind_speed,
-                    wind_direction,
-                    weather_main,
-                    weather_description,
-                    weather_icon,
-                    timestamp,
-                    timezone
-                FROM weather_data
-                WHERE city = $1
-                ORDER BY timestamp DESC
-                LIMIT 1
-                "#
-            )
+        let query = format!(
+            "SELECT {} FROM weather_data WHERE city = $1 ORDER BY {} LIMIT 1",
+            WEATHER_COLUMNS, NEWEST_FIRST
+        );
+
+        sqlx::query_as::<_, WeatherData>(&query)
             .bind(city)
             .fetch_optional(&self.pool)
             .await
-            .context("Failed to fetch latest weather data")?;
-
-        Ok(record.map(|(city, temperature, feels_like, humidity, pressure, wind_speed, wind_direction, weather_main, weather_description, weather_icon, timestamp, timezone)| {
-            WeatherData {
-                city,
-                temperature,
-                feels_like,
-                humidity,
-                pressure,
-                wind_speed,
-                wind_direction,
-                weather_main,
-                weather_description,
-                weather_icon,
-                timestamp,
-                timezone,
-                created_at: None,
+            .context("Failed to fetch latest weather data")
+    }
+
+    /// Returns up to `limit` most recent observations for `city`, newest first.
+    /// `limit` must be positive and is capped at [`MAX_RECENT_LIMIT`].
+    pub async fn get_recent(&self, city: &str, limit: i64) -> Result<Vec<WeatherData>> {
+        if limit <= 0 {
+            return Err(anyhow::anyhow!("limit must be positive, got {}", limit));
+        }
+
+        let query = format!(
+            "SELECT {} FROM weather_data WHERE city = $1 ORDER BY {} LIMIT $2",
+            WEATHER_COLUMNS, NEWEST_FIRST
+        );
+
+        sqlx::query_as::<_, WeatherData>(&query)
+            .bind(city)
+            .bind(limit.min(MAX_RECENT_LIMIT))
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to fetch recent weather data")
+    }
+
+    /// Lowest and highest temperature stored for `city` in `units` at or after
+    /// `since` (Unix seconds); `None` when there are no such rows.
+    pub async fn temperature_range_since(&self, city: &str, since: i64, units: Units) -> Result<Option<(f64, f64)>> {
+        let (low, high): (Option<f64>, Option<f64>) = sqlx::query_as(
+            r#"
+            SELECT MIN(temperature), MAX(temperature)
+            FROM weather_data
+            WHERE city = $1 AND timestamp >= $2 AND COALESCE(units, 'metric') = $3
+            "#
+        )
+        .bind(city)
+        .bind(since)
+        .bind(units.name())
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to read today's temperature range")?;
+        Ok(low.zip(high))
+    }
+
+    /// Units of the most recent row for each of `cities` that has rows;
+    /// `None` for rows written before units were recorded.
+    pub async fn latest_units_by_city(&self, cities: &[String]) -> Result<Vec<(String, Option<String>)>> {
+        sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (city) city, units
+            FROM weather_data
+            WHERE city = ANY($1)
+            ORDER BY city, timestamp DESC, created_at DESC NULLS LAST, id DESC
+            "#
+        )
+        .bind(cities)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read stored units")
+    }
+
+    pub async fn count_rows(&self, scope: &RowScope) -> Result<i64> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM weather_data
+            WHERE ($1::text IS NULL OR city = $1)
+              AND ($2::bigint IS NULL OR timestamp >= $2)
+              AND ($3::bigint IS NULL OR timestamp <= $3)
+            "#
+        )
+        .bind(&scope.city)
+        .bind(scope.from)
+        .bind(scope.to)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to count weather data rows")
+    }
+
+    /// Counts the `interval`-long slots in `[from, to)` that hold at least one
+    /// observation for `city`, and locates the empty ones.
+    pub async fn coverage(&self, city: &str, from: i64, to: i64, interval: Duration) -> Result<Coverage> {
+        let filled: Vec<i64> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT (timestamp - $2) / $4 AS slot FROM weather_data
+            WHERE city = $1 AND timestamp >= $2 AND timestamp < $3
+            ORDER BY slot
+            "#
+        )
+        .bind(city)
+        .bind(from)
+        .bind(to)
+        .bind(interval.as_secs().max(1) as i64)
+        .fetch_all(&self.pool)
+        .await
+        .with_context(|| format!("Failed to read observation times for {}", city))?;
+
+        Ok(Coverage::from_slots(from, to, interval, &filled))
+    }
+
+    /// Reads the next `limit` rows in `scope` with `id > after_id`, in id
+    /// order, so large tables can be walked without OFFSET scans.
+    pub async fn rows_after(&self, after_id: i32, scope: &RowScope, limit: i64) -> Result<Vec<(i32, WeatherData)>> {
+        let query = format!(
+            r#"
+            SELECT id, {} FROM weather_data
+            WHERE id > $1
+              AND ($2::text IS NULL OR city = $2)
+              AND ($3::bigint IS NULL OR timestamp >= $3)
+              AND ($4::bigint IS NULL OR timestamp <= $4)
+            ORDER BY id
+            LIMIT $5
+            "#,
+            WEATHER_COLUMNS
+        );
+
+        let rows = sqlx::query_as::<_, IdentifiedWeather>(&query)
+            .bind(after_id)
+            .bind(&scope.city)
+            .bind(scope.from)
+            .bind(scope.to)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to read weather data batch")?;
+
+        Ok(rows.into_iter().map(|row| (row.id, row.data)).collect())
+    }
+
+    /// Writes recomputed derived columns for a batch of rows in one statement.
+    pub async fn update_computed(&self, rows: &[(i32, ComputedColumns)]) -> Result<u64> {
+        let ids: Vec<i32> = rows.iter().map(|(id, _)| *id).collect();
+        let dew_points: Vec<Option<f64>> = rows.iter().map(|(_, c)| c.dew_point).collect();
+        let wind_chills: Vec<Option<f64>> = rows.iter().map(|(_, c)| c.wind_chill).collect();
+        let comfort_categories: Vec<Option<String>> =
+            rows.iter().map(|(_, c)| c.comfort_category.clone()).collect();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE weather_data AS w
+            SET dew_point = v.dew_point, wind_chill = v.wind_chill, comfort_category = v.comfort_category
+            FROM UNNEST($1::int[], $2::float8[], $3::float8[], $4::text[])
+                AS v(id, dew_point, wind_chill, comfort_category)
+            WHERE w.id = v.id
+            "#
+        )
+        .bind(&ids)
+        .bind(&dew_points)
+        .bind(&wind_chills)
+        .bind(&comfort_categories)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update computed columns")?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Returns the entries of `columns` that `table` lacks. Every column is
+    /// reported missing when the table does not exist.
+    pub async fn missing_columns(&self, table: &str, columns: &[&str]) -> Result<Vec<String>> {
+        let existing: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns WHERE table_name = $1"
+        )
+        .bind(table)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read table columns")?;
+
+        Ok(columns
+            .iter()
+            .filter(|column| !existing.iter().any(|e| e == *column))
+            .map(|column| column.to_string())
+            .collect())
+    }
+
+    /// Applies pending migrations and returns the `(version, description)`
+    /// of each one applied by this call, oldest first.
+    pub async fn run_migrations(&self) -> Result<Vec<(i64, String)>> {
+        let applied_before: HashSet<i64> = {
+            let mut conn = self.pool.acquire().await.context("Failed to acquire connection for migrations")?;
+            conn.ensure_migrations_table().await.context("Failed to create migrations table")?;
+            conn.list_applied_migrations()
+                .await
+                .context("Failed to list applied migrations")?
+                .into_iter()
+                .map(|migration| migration.version)
+                .collect()
+        };
+
+        MIGRATOR.run(&self.pool).await.context("Failed to apply migrations")?;
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| !applied_before.contains(&migration.version))
+            .map(|migration| (migration.version, migration.description.to_string()))
+            .collect())
+    }
+
+    /// Every migration known to this build or recorded in
+    /// `_sqlx_migrations`, by version. Read-only: the migrations table is
+    /// not created when missing.
+    pub async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let mut conn = self.pool.acquire().await.context("Failed to acquire connection for migrations")?;
+        let has_table: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(&mut *conn)
+            .await
+            .context("Failed to look up the migrations table")?;
+        let applied = if has_table {
+            conn.list_applied_migrations().await.context("Failed to list applied migrations")?
+        } else {
+            Vec::new()
+        };
+
+        let mut statuses: Vec<MigrationStatus> = MIGRATOR
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .map(|migration| {
+                let state = match applied.iter().find(|a| a.version == migration.version) {
+                    Some(a) if a.checksum == migration.checksum => MigrationState::Applied,
+                    Some(_) => MigrationState::Modified,
+                    None => MigrationState::Pending,
+                };
+                MigrationStatus {
+                    version: migration.version,
+                    description: migration.description.to_string(),
+                    state,
+                }
+            })
+            .collect();
+        for a in &applied {
+            if !statuses.iter().any(|status| status.version == a.version) {
+                statuses.push(MigrationStatus {
+                    version: a.version,
+                    description: String::new(),
+                    state: MigrationState::Unknown,
+                });
             }
-        }))
+        }
+        statuses.sort_by_key(|status| status.version);
+        Ok(statuses)
     }
 
     pub async fn health_check(&self) -> Result<()> {
@@ -103,5 +891,24 @@ impl DatabaseService {
             .context("Database health check failed")?;
         Ok(())
     }
+
+    /// Like [`health_check`](Self::health_check), but also confirms that
+    /// `weather_data` accepts writes: inserts a placeholder row inside a
+    /// transaction that is always rolled back. Fails on a read-only server
+    /// (e.g. a standby after failover), a full disk or missing privileges,
+    /// all of which `SELECT 1` passes.
+    pub async fn health_check_writable(&self) -> Result<()> {
+        let mut transaction = self.pool.begin().await.context("Database health check failed")?;
+        // An explicit id leaves the sequence untouched, so probes don't
+        // leave gaps in the ids of real rows
+        sqlx::query(
+            "INSERT INTO weather_data (id, temperature, humidity, wind_speed, timestamp) VALUES (-1, 0, 0, 0, 0)",
+        )
+        .execute(&mut *transaction)
+        .await
+        .context("Database is not writable")?;
+        transaction.rollback().await.context("Database health check failed")?;
+        Ok(())
+    }
 }
 
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const DAY_SECONDS: i64 = 86_400;
+
+/// Running high and low temperature of the current local day per city,
+/// starting over at local midnight. Kept in memory, so it is seeded from the
+/// rows already stored today after a restart.
+pub struct DayExtremes {
+    days: HashMap<String, DayRange>,
+    seeded: HashSet<String>,
+}
+
+/// Temperature range of one city's local day.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DayRange {
+    /// Days since the epoch in the city's local time.
+    pub index: i64,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// Local day of `timestamp` at UTC offset `offset` (seconds).
+fn day_index(timestamp: i64, offset: i32) -> i64 {
+    (timestamp + offset as i64).div_euclid(DAY_SECONDS)
+}
+
+/// Unix time of the local midnight that starts the day of `timestamp`, at
+/// UTC offset `offset` (seconds).
+pub fn local_midnight(timestamp: i64, offset: i32) -> i64 {
+    day_index(timestamp, offset) * DAY_SECONDS - offset as i64
+}
+
+impl DayExtremes {
+    pub fn new() -> Self {
+        Self {
+            days: HashMap::new(),
+            seeded: HashSet::new(),
+        }
+    }
+
+    /// Whether `city` has been seeded (or updated) yet.
+    pub fn is_seeded(&self, city: &str) -> bool {
+        self.seeded.contains(city)
+    }
+
+    /// Starts `city` from the `(low, high)` of the rows stored since the local
+    /// midnight before `timestamp`, if any, so the lookup is not repeated for
+    /// cities with nothing stored today.
+    pub fn seed(&mut self, city: &str, timestamp: i64, offset: i32, range: Option<(f64, f64)>) {
+        self.seeded.insert(city.to_string());
+        if let Some((low, high)) = range {
+            let index = day_index(timestamp, offset);
+            self.days.insert(city.to_string(), DayRange { index, low, high });
+        }
+    }
+
+    /// The `(low, high)` of the day's range for `city` once `temperature`
+    /// observed at `timestamp` is folded in, without folding it in. A reading
+    /// from another local day is its own range.
+    pub fn range(&self, city: &str, timestamp: i64, offset: i32, temperature: f64) -> (f64, f64) {
+        let index = day_index(timestamp, offset);
+        match self.days.get(city) {
+            Some(day) if day.index == index => (day.low.min(temperature), day.high.max(temperature)),
+            _ => (temperature, temperature),
+        }
+    }
+
+    /// Folds `temperature` observed at `timestamp` into the day's range for
+    /// `city` and returns `(low, high)`. A reading from a later local day
+    /// starts a new range; one from an earlier day is returned as its own
+    /// range without changing today's.
+    pub fn update(&mut self, city: &str, timestamp: i64, offset: i32, temperature: f64) -> (f64, f64) {
+        self.seeded.insert(city.to_string());
+        let (low, high) = self.range(city, timestamp, offset, temperature);
+        let index = day_index(timestamp, offset);
+        if self.days.get(city).is_none_or(|day| index >= day.index) {
+            self.days.insert(city.to_string(), DayRange { index, low, high });
+        }
+        (low, high)
+    }
+
+    /// The current day's range of each city, for `STATE_FILE`.
+    pub fn snapshot(&self) -> HashMap<String, DayRange> {
+        self.days.clone()
+    }
+
+    /// Continues the ranges of a [`snapshot`](Self::snapshot). A range from
+    /// an earlier local day is replaced by the next reading as usual.
+    pub fn restore(&mut self, days: HashMap<String, DayRange>) {
+        for (city, day) in days {
+            self.seeded.insert(city.clone());
+            self.days.insert(city, day);
+        }
+    }
+}
+
+impl Default for DayExtremes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,132 @@
+//! Deterministic database failures for testing recovery paths. Only built
+//! with the `fault-injection` feature.
+//!
+//! Faults are queued on a [`DatabaseService`](super::database::DatabaseService)
+//! through `faults()` and consumed in order by its insert statements: each
+//! single-row insert, each row of a cycle transaction and each batch
+//! statement takes one. The injected error is the `sqlx::Error` a real
+//! failure of that kind produces, so `is_data_error` and the callers'
+//! handling see no difference.
+
+use sqlx::error::{DatabaseError, ErrorKind};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+
+/// Kind of failure to inject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The connection dropped mid-statement.
+    ConnectionReset,
+    /// The server ran out of disk space (SQLSTATE 53100).
+    DiskFull,
+    /// No pooled connection became available in time.
+    Timeout,
+    /// The row was rejected (SQLSTATE 22003, numeric value out of range), as
+    /// when a value doesn't fit its column.
+    DataError,
+}
+
+impl Fault {
+    fn to_error(self) -> sqlx::Error {
+        match self {
+            Fault::ConnectionReset => sqlx::Error::Io(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "connection reset by peer (injected)",
+            )),
+            Fault::DiskFull => sqlx::Error::Database(Box::new(InjectedDatabaseError {
+                code: "53100",
+                message: "could not extend file: No space left on device (injected)",
+            })),
+            Fault::Timeout => sqlx::Error::PoolTimedOut,
+            Fault::DataError => sqlx::Error::Database(Box::new(InjectedDatabaseError {
+                code: "22003",
+                message: "value out of range (injected)",
+            })),
+        }
+    }
+}
+
+/// Queue of outcomes for the next insert statements: a fault, or `None` to
+/// let the statement through.
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    pending: Mutex<VecDeque<Option<Fault>>>,
+}
+
+impl FaultInjector {
+    /// Makes the next `count` insert statements fail with `fault`, after any
+    /// faults already queued.
+    pub fn fail_next_inserts(&self, count: usize, fault: Fault) {
+        self.lock().extend(std::iter::repeat_n(Some(fault), count));
+    }
+
+    /// Lets the next `count` insert statements through before the faults
+    /// queued after this call, e.g. to fail the second row of a cycle.
+    pub fn allow_next_inserts(&self, count: usize) {
+        self.lock().extend(std::iter::repeat_n(None, count));
+    }
+
+    /// Queued outcomes not yet consumed.
+    pub fn pending(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Takes the next queued fault as an error.
+    pub(crate) fn check(&self) -> Result<(), sqlx::Error> {
+        match self.lock().pop_front().flatten() {
+            Some(fault) => Err(fault.to_error()),
+            None => Ok(()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Option<Fault>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Debug)]
+struct InjectedDatabaseError {
+    code: &'static str,
+    message: &'static str,
+}
+
+impl fmt::Display for InjectedDatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl std::error::Error for InjectedDatabaseError {}
+
+impl DatabaseError for InjectedDatabaseError {
+    fn message(&self) -> &str {
+        self.message
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.code))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// Typed failures from the weather API that callers may want to react to.
+/// They travel inside `anyhow::Error`; use `downcast_ref::<FetchError>()`.
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("failed to parse weather API response: {source} (body starts with: {snippet})")]
+    Parse {
+        #[source]
+        source: serde_json::Error,
+        snippet: String,
+    },
+
+    #[error("response too large: exceeds the {limit}-byte limit")]
+    ResponseTooLarge { limit: usize },
+
+    #[error("observation timestamp {timestamp} is {ahead_by}s in the future")]
+    FutureTimestamp { timestamp: i64, ahead_by: i64 },
+
+    #[error("{provider} rejected the API key (HTTP 401)")]
+    InvalidApiKey { provider: &'static str },
+
+    #[error("your API key isn't subscribed to One Call 3.0 (a separate \"One Call by Call\" subscription is required)")]
+    OneCallNotSubscribed,
+
+    #[error("{provider} refused the request (HTTP 403): {message}")]
+    Forbidden { provider: &'static str, message: String },
+
+    #[error("API quota exceeded")]
+    QuotaExceeded { status: u16, retry_after: Option<Duration> },
+
+    #[error("all API keys are over quota; the first resets in {}s", retry_in.as_secs())]
+    KeysExhausted { retry_in: Duration },
+
+    #[error("OpenWeatherMap API returned error code: {cod}")]
+    ErrorCode { cod: i32 },
+
+    #[error("{provider} returned {status}: {body}")]
+    Status {
+        provider: &'static str,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+}
+
+impl FetchError {
+    /// Whether this is a parse error from a body that ended early, as when a
+    /// proxy cuts the response off, rather than one that doesn't match the
+    /// expected shape. Only the first is worth requesting again.
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, FetchError::Parse { source, .. } if source.is_eof())
+    }
+}
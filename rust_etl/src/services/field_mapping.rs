@@ -0,0 +1,224 @@
+//! Builds [`WeatherData`] from an arbitrary JSON response using a configured
+//! mapping of record fields to paths in the response, so a provider with a
+//! compatible current-conditions endpoint needs no code of its own.
+//!
+//! Paths are dot-separated keys with optional array indices, e.g.
+//! `current.temp_c`, `weather[0].main` or `weather.0.main`; a leading `$.`
+//! is ignored. Numbers may also arrive as numeric strings.
+
+use crate::models::units::Units;
+use crate::models::weather::WeatherData;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Record fields a mapping may target.
+pub const MAPPABLE_FIELDS: &[&str] = &[
+    "city",
+    "temperature",
+    "feels_like",
+    "humidity",
+    "pressure",
+    "sea_level_pressure",
+    "ground_level_pressure",
+    "wind_speed",
+    "wind_direction",
+    "weather_main",
+    "weather_description",
+    "weather_icon",
+    "weather_id",
+    "timestamp",
+    "timezone",
+    "timezone_name",
+    "uv_index",
+];
+
+/// Fields every mapping must provide; the record has no sensible default
+/// for them.
+pub const REQUIRED_FIELDS: &[&str] = &["temperature", "humidity", "wind_speed"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A parsed path into a JSON document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    raw: String,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(raw: &str) -> Result<Self> {
+        let trimmed = raw.trim();
+        let path = trimmed.strip_prefix("$.").unwrap_or(trimmed);
+        let mut segments = Vec::new();
+
+        for part in path.split('.') {
+            let (key, mut indices) = match part.find('[') {
+                Some(start) => part.split_at(start),
+                None => (part, ""),
+            };
+            if key.is_empty() && indices.is_empty() {
+                return Err(anyhow::anyhow!("invalid path '{}': empty segment", raw));
+            }
+            if !key.is_empty() {
+                segments.push(match key.parse() {
+                    Ok(index) => Segment::Index(index),
+                    Err(_) => Segment::Key(key.to_string()),
+                });
+            }
+            while let Some(rest) = indices.strip_prefix('[') {
+                let (index, after) = rest
+                    .split_once(']')
+                    .ok_or_else(|| anyhow::anyhow!("invalid path '{}': unclosed '['", raw))?;
+                let index = index
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid path '{}': '{}' is not an array index", raw, index))?;
+                segments.push(Segment::Index(index));
+                indices = after;
+            }
+            if !indices.is_empty() {
+                return Err(anyhow::anyhow!("invalid path '{}': unexpected '{}'", raw, indices));
+            }
+        }
+
+        Ok(Self {
+            raw: trimmed.to_string(),
+            segments,
+        })
+    }
+
+    /// The value at this path, or `None` when it is missing or `null`.
+    pub fn get<'a>(&self, document: &'a Value) -> Option<&'a Value> {
+        let value = self.segments.iter().try_fold(document, |value, segment| match segment {
+            Segment::Key(key) => value.get(key),
+            Segment::Index(index) => value.get(index),
+        })?;
+        (!value.is_null()).then_some(value)
+    }
+}
+
+/// Record field → response path, validated against [`MAPPABLE_FIELDS`] and
+/// [`REQUIRED_FIELDS`].
+#[derive(Debug, Clone)]
+pub struct FieldMapping {
+    paths: BTreeMap<String, JsonPath>,
+}
+
+impl FieldMapping {
+    pub fn new(fields: &BTreeMap<String, String>) -> Result<Self> {
+        let mut paths = BTreeMap::new();
+        for (field, path) in fields {
+            if !MAPPABLE_FIELDS.contains(&field.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "unknown field '{}'; expected one of {}",
+                    field,
+                    MAPPABLE_FIELDS.join(", ")
+                ));
+            }
+            paths.insert(field.clone(), JsonPath::parse(path).with_context(|| format!("field '{}'", field))?);
+        }
+
+        let missing: Vec<&str> = REQUIRED_FIELDS
+            .iter()
+            .copied()
+            .filter(|field| !paths.contains_key(*field))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!("no path for required field(s) {}", missing.join(", ")));
+        }
+
+        Ok(Self { paths })
+    }
+
+    /// Builds a record from `document`, whose values are in `units`. The
+    /// city defaults to `city` and the timestamp to now when not mapped or
+    /// absent.
+    pub fn extract(&self, document: &Value, city: &str, units: Units) -> Result<WeatherData> {
+        let required = |field: &str| -> Result<f64> {
+            self.number(field, document)?.with_context(|| {
+                format!("response has no value for {} at '{}'", field, self.paths[field].raw)
+            })
+        };
+        let temperature = required("temperature")?;
+        let humidity = required("humidity")?;
+        let wind_speed = required("wind_speed")?;
+
+        Ok(WeatherData {
+            city: Some(self.text("city", document)?.unwrap_or_else(|| city.to_string())),
+            temperature,
+            feels_like: self.number("feels_like", document)?,
+            humidity: humidity.round() as i32,
+            pressure: self.number("pressure", document)?.map(|value| value.round() as i32),
+            sea_level_pressure: self.number("sea_level_pressure", document)?.map(|value| value.round() as i32),
+            ground_level_pressure: self.number("ground_level_pressure", document)?.map(|value| value.round() as i32),
+            wind_speed,
+            wind_direction: self.number("wind_direction", document)?,
+            weather_main: self.text("weather_main", document)?,
+            weather_description: self.text("weather_description", document)?,
+            weather_icon: self.text("weather_icon", document)?,
+            weather_id: self.number("weather_id", document)?.map(|value| value as i32),
+            timestamp: self.timestamp(document)?.unwrap_or_else(|| chrono::Utc::now().timestamp()),
+            timezone: self.number("timezone", document)?.map(|value| value as i32),
+            timezone_name: self.text("timezone_name", document)?,
+            uv_index: self.number("uv_index", document)?,
+            ..WeatherData::builder().units(units).build()
+        })
+    }
+
+    fn value<'a>(&self, field: &str, document: &'a Value) -> Option<(&JsonPath, &'a Value)> {
+        let path = self.paths.get(field)?;
+        Some((path, path.get(document)?))
+    }
+
+    fn number(&self, field: &str, document: &Value) -> Result<Option<f64>> {
+        let Some((path, value)) = self.value(field, document) else {
+            return Ok(None);
+        };
+        let number = match value {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        };
+        number
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("{} at '{}' is not a number: {}", field, path.raw, value))
+    }
+
+    fn text(&self, field: &str, document: &Value) -> Result<Option<String>> {
+        let Some((path, value)) = self.value(field, document) else {
+            return Ok(None);
+        };
+        match value {
+            Value::String(text) => Ok(Some(text.clone())),
+            Value::Number(number) => Ok(Some(number.to_string())),
+            _ => Err(anyhow::anyhow!("{} at '{}' is not text: {}", field, path.raw, value)),
+        }
+    }
+
+    /// Unix seconds, as a number or numeric string, or an RFC 3339 time.
+    fn timestamp(&self, document: &Value) -> Result<Option<i64>> {
+        let Some((path, value)) = self.value("timestamp", document) else {
+            return Ok(None);
+        };
+        let seconds = match value {
+            Value::Number(number) => number.as_f64().map(|seconds| seconds as i64),
+            Value::String(text) => text
+                .trim()
+                .parse()
+                .ok()
+                .or_else(|| chrono::DateTime::parse_from_rfc3339(text.trim()).ok().map(|time| time.timestamp())),
+            _ => None,
+        };
+        seconds.map(Some).ok_or_else(|| {
+            anyhow::anyhow!(
+                "timestamp at '{}' is neither Unix seconds nor an RFC 3339 time: {}",
+                path.raw,
+                value
+            )
+        })
+    }
+}
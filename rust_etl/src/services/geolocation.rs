@@ -0,0 +1,120 @@
+//! `AUTO_LOCATE`: finds the machine's approximate location from its public
+//! IP address, once at startup, so collection works without `CITIES`.
+
+use crate::config::app_config::AppConfig;
+use crate::models::weather::GeocodedCity;
+use crate::services::weather_service::{http_client, read_limited, retry_after, status_error};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Largest geolocation response accepted, in bytes.
+const MAX_RESPONSE_BYTES: usize = 64 * 1024;
+
+/// Location reported by the geolocation service. Accepts the field names of
+/// ip-api.com (`countryCode`, `lat`, `lon`) and ipapi.co (`country_code`,
+/// `latitude`, `longitude`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Location {
+    pub city: String,
+    #[serde(alias = "countryCode")]
+    pub country_code: Option<String>,
+    #[serde(alias = "latitude")]
+    pub lat: f64,
+    #[serde(alias = "longitude")]
+    pub lon: f64,
+}
+
+impl Location {
+    /// The city as a weather API query, with the country code when known,
+    /// e.g. `Montreal,CA`.
+    pub fn query(&self) -> String {
+        match self.country_code.as_deref().filter(|code| !code.is_empty()) {
+            Some(code) => format!("{},{}", self.city, code),
+            None => self.city.clone(),
+        }
+    }
+
+    /// The location as a geocoding result, so its coordinates stand in for
+    /// a geocoding request.
+    pub fn place(&self) -> GeocodedCity {
+        GeocodedCity {
+            name: self.city.clone(),
+            lat: self.lat,
+            lon: self.lon,
+            country: self.country_code.clone(),
+        }
+    }
+}
+
+/// Error fields of ip-api.com (`status`, `message`) and ipapi.co (`error`,
+/// `reason`), which may answer failed lookups with HTTP 200.
+#[derive(Debug, Deserialize)]
+struct LookupStatus {
+    status: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    error: bool,
+    reason: Option<String>,
+}
+
+/// Looks up the location of this machine's public IP at `AUTO_LOCATE_URL`.
+pub async fn locate(config: &AppConfig) -> Result<Location> {
+    let response = http_client(config)
+        .get(&config.auto_locate_url)
+        .send()
+        .await
+        .context("Failed to reach the geolocation service")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let retry_after = retry_after(&response);
+        let body = read_limited(response, MAX_RESPONSE_BYTES).await.unwrap_or_default();
+        return Err(status_error("Geolocation service", status, retry_after, &body));
+    }
+
+    let body = read_limited(response, MAX_RESPONSE_BYTES).await?;
+    if let Ok(lookup) = serde_json::from_str::<LookupStatus>(&body) {
+        if let Some(status) = lookup.status.filter(|status| status != "success") {
+            return Err(anyhow::anyhow!(
+                "geolocation lookup failed: {}",
+                lookup.message.unwrap_or(status)
+            ));
+        }
+        if lookup.error {
+            return Err(anyhow::anyhow!(
+                "geolocation lookup failed: {}",
+                lookup.reason.as_deref().unwrap_or("unknown error")
+            ));
+        }
+    }
+    let location: Location = serde_json::from_str(&body).context("Unexpected geolocation response")?;
+    if location.city.trim().is_empty() {
+        return Err(anyhow::anyhow!("geolocation service did not return a city"));
+    }
+    Ok(location)
+}
+
+/// With `AUTO_LOCATE` and no `CITIES`, collects for the detected location,
+/// keeping its coordinates for geocoding. Does nothing otherwise.
+pub async fn apply(config: &mut AppConfig) -> Result<()> {
+    if !config.auto_locate {
+        return Ok(());
+    }
+    if !config.cities.is_empty() {
+        log::info!("📍 CITIES is set; skipping AUTO_LOCATE");
+        return Ok(());
+    }
+
+    let location = locate(config).await.context("AUTO_LOCATE failed; set CITIES instead")?;
+    log::info!(
+        "📍 Detected location: {} ({:.2}, {:.2}) via {}",
+        location.query(),
+        location.lat,
+        location.lon,
+        config.auto_locate_url
+    );
+    config.city = location.query();
+    config.cities = vec![config.city.clone()];
+    config.located = Some(location);
+    Ok(())
+}
@@ -0,0 +1,486 @@
+use crate::config::app_config::AppConfig;
+use crate::models::compass::{wind_compass, Language};
+use crate::models::weather::WeatherData;
+use crate::services::database::{is_data_error, DatabaseService, InsertOutcome, InsertedRow};
+use crate::services::metrics::Metrics;
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Unit of work on the insert queue.
+enum WriteJob {
+    /// Inserted independently, batched with neighbouring rows.
+    Row(Box<WeatherData>),
+    /// A whole cycle's rows, inserted all-or-nothing in one transaction.
+    Cycle(Vec<WeatherData>),
+    /// A whole cycle's rows, inserted independently in batches.
+    Rows(Vec<WeatherData>),
+    /// Answered once every job queued before it has been written.
+    Flush(oneshot::Sender<()>),
+}
+
+impl WriteJob {
+    /// Observations the job carries.
+    fn rows(&self) -> usize {
+        match self {
+            WriteJob::Row(_) => 1,
+            WriteJob::Cycle(rows) | WriteJob::Rows(rows) => rows.len(),
+            WriteJob::Flush(_) => 0,
+        }
+    }
+}
+
+/// A job and when it was queued, to measure how far the writer lags.
+struct Queued {
+    job: WriteJob,
+    queued_at: Instant,
+}
+
+/// How backed up the insert queue is, shared with `GET /health`. Depth,
+/// capacity and the high-water mark are counted in jobs, the unit of the
+/// bounded channel: one row, or a whole cycle queued at once. The queue
+/// counts as backed up at or above the high-water mark, and as degraded once
+/// it has stayed there for `WRITER_QUEUE_DEGRADED_AFTER_SECONDS`, before a
+/// full queue starts holding up fetching. Rows not yet written, including the
+/// writer's unwritten batch, are counted alongside.
+pub struct QueueHealth {
+    capacity: usize,
+    high_water: usize,
+    degraded_after: Duration,
+    /// Jobs queued that the writer hasn't picked up yet.
+    depth: AtomicUsize,
+    /// Rows in queued jobs or the writer's batch that aren't written yet.
+    rows: AtomicUsize,
+    /// How long the most recently received job waited, in milliseconds.
+    last_wait_ms: AtomicU64,
+    backed_up_since: Mutex<Option<Instant>>,
+}
+
+/// Snapshot of [`QueueHealth`].
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueReport {
+    /// Jobs waiting for the writer.
+    pub depth: usize,
+    /// `INSERT_QUEUE_CAPACITY`, in jobs.
+    pub capacity: usize,
+    /// Depth at or above which the queue counts as backed up.
+    pub high_water: usize,
+    /// Rows waiting to be written, queued or in the writer's batch.
+    pub rows: usize,
+    /// How long the job the writer last picked up had waited.
+    pub last_wait_ms: u64,
+    /// Seconds the queue has been backed up, if it is.
+    pub backed_up_seconds: Option<u64>,
+    pub degraded: bool,
+}
+
+impl QueueHealth {
+    /// `high_water_percent` of `capacity`, at least one job, is the
+    /// high-water mark.
+    pub fn new(capacity: usize, high_water_percent: u8, degraded_after: Duration) -> Self {
+        let high_water = (capacity * usize::from(high_water_percent)).div_ceil(100).max(1);
+        Self {
+            capacity,
+            high_water,
+            degraded_after,
+            depth: AtomicUsize::new(0),
+            rows: AtomicUsize::new(0),
+            last_wait_ms: AtomicU64::new(0),
+            backed_up_since: Mutex::new(None),
+        }
+    }
+
+    fn from_config(config: &AppConfig) -> Self {
+        Self::new(
+            config.insert_queue_capacity,
+            config.writer_queue_high_water_percent,
+            config.writer_queue_degraded_after,
+        )
+    }
+
+    /// Counts a job carrying `rows` handed to the writer.
+    pub fn job_queued(&self, rows: usize) {
+        self.rows.fetch_add(rows, Ordering::Relaxed);
+        let depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        self.record_depth(depth);
+    }
+
+    /// Counts a job the writer has picked up, freeing its slot.
+    pub fn job_received(&self) {
+        let depth = self.depth.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.record_depth(depth);
+    }
+
+    /// Counts `rows` the writer has finished with, stored or not.
+    pub fn rows_written(&self, rows: usize) {
+        self.rows.fetch_sub(rows, Ordering::Relaxed);
+    }
+
+    /// Jobs waiting for the writer.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Rows waiting to be written.
+    pub fn rows(&self) -> usize {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    /// Starts or ends a backed-up period at `depth`.
+    fn record_depth(&self, depth: usize) {
+        let mut since = self.backed_up_since.lock().unwrap_or_else(|e| e.into_inner());
+        if depth < self.high_water {
+            *since = None;
+        } else if since.is_none() {
+            *since = Some(Instant::now());
+        }
+    }
+
+    fn record_wait(&self, wait: Duration) {
+        self.last_wait_ms.store(wait.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn report(&self) -> QueueReport {
+        let backed_up_for = self
+            .backed_up_since
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|since| since.elapsed());
+        QueueReport {
+            depth: self.depth.load(Ordering::Relaxed),
+            capacity: self.capacity,
+            high_water: self.high_water,
+            rows: self.rows.load(Ordering::Relaxed),
+            last_wait_ms: self.last_wait_ms.load(Ordering::Relaxed),
+            backed_up_seconds: backed_up_for.map(|elapsed| elapsed.as_secs()),
+            degraded: backed_up_for.is_some_and(|elapsed| elapsed >= self.degraded_after),
+        }
+    }
+}
+
+/// An observation the writer stored, as published to subscribers: the row
+/// as inserted, with the `id` and `created_at` the database assigned.
+#[derive(Debug, Clone, Serialize)]
+pub struct InsertedObservation {
+    pub id: i32,
+    #[serde(flatten)]
+    pub observation: WeatherData,
+}
+
+/// Producer side of the insert queue. Fetching hands observations to a
+/// dedicated writer task through a bounded channel, so a slow database makes
+/// [`InsertWriter::enqueue`] wait instead of letting the queue grow.
+pub struct InsertWriter {
+    sender: mpsc::Sender<Queued>,
+    metrics: Arc<Metrics>,
+    health: Arc<QueueHealth>,
+    inserted: broadcast::Sender<InsertedObservation>,
+}
+
+impl InsertWriter {
+    /// Starts the writer task. It exits once the `InsertWriter` is dropped
+    /// and the queue has been drained. Every confirmed insert is published on
+    /// `inserted`.
+    pub fn spawn(
+        database: Arc<DatabaseService>,
+        metrics: Arc<Metrics>,
+        inserted: broadcast::Sender<InsertedObservation>,
+        config: &AppConfig,
+    ) -> (Self, JoinHandle<()>) {
+        let (sender, receiver) = mpsc::channel(config.insert_queue_capacity);
+        let health = Arc::new(QueueHealth::from_config(config));
+        let output = Output {
+            metrics: Arc::clone(&metrics),
+            inserted: inserted.clone(),
+            lang: config.lang,
+            health: Arc::clone(&health),
+        };
+        let handle = tokio::spawn(run(
+            receiver,
+            database,
+            output,
+            config.insert_batch_size,
+            config.writer_flush_interval,
+            config.insert_max_attempts,
+        ));
+
+        (
+            Self {
+                sender,
+                metrics,
+                health,
+                inserted,
+            },
+            handle,
+        )
+    }
+
+    /// Receives each observation once its insert is confirmed.
+    pub fn subscribe(&self) -> broadcast::Receiver<InsertedObservation> {
+        self.inserted.subscribe()
+    }
+
+    /// Queue depth and lag, for `GET /health`.
+    pub fn health(&self) -> Arc<QueueHealth> {
+        Arc::clone(&self.health)
+    }
+
+    /// Queues `data` for insertion, waiting while the queue is full.
+    pub async fn enqueue(&self, data: WeatherData) -> Result<()> {
+        self.send(WriteJob::Row(Box::new(data))).await
+    }
+
+    /// Queues one cycle's rows to be stored atomically: either all of them
+    /// land or, if any insert fails, none do.
+    pub async fn enqueue_cycle(&self, rows: Vec<WeatherData>) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.send(WriteJob::Cycle(rows)).await
+    }
+
+    /// Queues one cycle's rows as a single job, so they never wait for
+    /// queue slots one by one. Each is inserted independently.
+    pub async fn enqueue_rows(&self, rows: Vec<WeatherData>) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        self.send(WriteJob::Rows(rows)).await
+    }
+
+    /// Returns a receiver that completes once everything queued so far has
+    /// been written (or dead-lettered, or given up on).
+    pub async fn flush(&self) -> Result<oneshot::Receiver<()>> {
+        let (written, receiver) = oneshot::channel();
+        self.send(WriteJob::Flush(written)).await?;
+        Ok(receiver)
+    }
+
+    async fn send(&self, job: WriteJob) -> Result<()> {
+        let rows = job.rows();
+        let queued = Queued {
+            job,
+            queued_at: Instant::now(),
+        };
+        self.health.job_queued(rows);
+        if self.sender.send(queued).await.is_err() {
+            self.health.job_received();
+            self.health.rows_written(rows);
+            anyhow::bail!("insert writer has stopped");
+        }
+        self.metrics.gauge("insert.queue_depth", self.health.depth() as f64, &[]);
+        Ok(())
+    }
+
+    /// Rows waiting to be written, queued or in the writer's batch.
+    pub fn pending_rows(&self) -> usize {
+        self.health.rows()
+    }
+}
+
+/// Where the writer reports what it stored.
+struct Output {
+    metrics: Arc<Metrics>,
+    inserted: broadcast::Sender<InsertedObservation>,
+    lang: Language,
+    health: Arc<QueueHealth>,
+}
+
+/// Accumulates rows until `batch_size` are pending or `flush_interval` has
+/// passed since the oldest of them arrived, whichever comes first. A cycle
+/// job flushes the pending rows first so arrival order is kept.
+async fn run(
+    mut receiver: mpsc::Receiver<Queued>,
+    database: Arc<DatabaseService>,
+    output: Output,
+    batch_size: usize,
+    flush_interval: Duration,
+    max_attempts: u32,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut flush_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        let job = match flush_at {
+            Some(deadline) => tokio::select! {
+                job = receiver.recv() => job,
+                _ = tokio::time::sleep_until(deadline) => {
+                    write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                    flush_at = None;
+                    continue;
+                }
+            },
+            None => receiver.recv().await,
+        };
+
+        let job = job.map(|queued| {
+            let wait = queued.queued_at.elapsed();
+            output.metrics.timing("insert.queue_wait", wait, &[]);
+            output.health.record_wait(wait);
+            output.health.job_received();
+            queued.job
+        });
+        match job {
+            Some(WriteJob::Row(data)) => {
+                batch.push(*data);
+                if batch.len() >= batch_size || flush_interval.is_zero() {
+                    write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                    flush_at = None;
+                } else if flush_at.is_none() {
+                    flush_at = Some(tokio::time::Instant::now() + flush_interval);
+                }
+            }
+            Some(WriteJob::Cycle(rows)) => {
+                write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                flush_at = None;
+                write_cycle(&database, &output, &rows).await;
+                output.health.rows_written(rows.len());
+            }
+            Some(WriteJob::Rows(rows)) => {
+                batch.extend(rows);
+                write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                flush_at = None;
+            }
+            Some(WriteJob::Flush(written)) => {
+                write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                flush_at = None;
+                let _ = written.send(());
+            }
+            None => {
+                // Sender dropped: write what is left and stop
+                write_pending(&database, &output, &mut batch, batch_size, max_attempts).await;
+                return;
+            }
+        }
+        output.metrics.gauge("insert.queue_depth", output.health.depth() as f64, &[]);
+    }
+}
+
+/// Writes and clears the pending rows, `batch_size` per statement.
+async fn write_pending(
+    database: &DatabaseService,
+    output: &Output,
+    batch: &mut Vec<WeatherData>,
+    batch_size: usize,
+    max_attempts: u32,
+) {
+    for chunk in batch.chunks(batch_size) {
+        write_batch(database, output, chunk, max_attempts).await;
+    }
+    output.health.rows_written(batch.len());
+    batch.clear();
+}
+
+/// Stores a cycle in one transaction. When it rolls back because of a data
+/// error the rows are dead-lettered together so the snapshot stays whole.
+async fn write_cycle(database: &DatabaseService, output: &Output, rows: &[WeatherData]) {
+    let metrics = &output.metrics;
+    let started = Instant::now();
+    let result = database.insert_cycle(rows).await;
+    metrics.timing("insert.duration", started.elapsed(), &[]);
+
+    let err = match result {
+        Ok(inserted) => {
+            for (data, row) in rows.iter().zip(&inserted) {
+                record_inserted(output, data, row);
+            }
+            return;
+        }
+        Err(e) => e,
+    };
+
+    let error_text = format!("{:#}", err);
+    log::error!("❌ Cycle transaction rolled back, {} observation(s) not stored: {}", rows.len(), error_text);
+
+    for data in rows {
+        let tags = [("city", data.city.as_deref().unwrap_or("Unknown"))];
+        if !is_data_error(&err) {
+            metrics.incr("insert.failure", &tags);
+            continue;
+        }
+        match database.insert_dead_letter(data, &error_text, 1).await {
+            Ok(()) => metrics.incr("insert.dead_lettered", &tags),
+            Err(e) => {
+                metrics.incr("insert.failure", &tags);
+                log::error!("❌ Failed to dead-letter rolled-back observation: {:#}", e);
+            }
+        }
+    }
+}
+
+/// Writes `batch` in one statement, falling back to row-by-row inserts (with
+/// retries and dead-lettering) when the batch is rejected, so one bad row
+/// cannot take the rest of the batch down with it.
+async fn write_batch(database: &DatabaseService, output: &Output, batch: &[WeatherData], max_attempts: u32) {
+    let metrics = &output.metrics;
+    if batch.len() > 1 {
+        let started = Instant::now();
+        match database.insert_batch(batch).await {
+            Ok(rows) => {
+                metrics.timing("insert.duration", started.elapsed(), &[]);
+                for (data, row) in batch.iter().zip(&rows) {
+                    record_inserted(output, data, row);
+                }
+                return;
+            }
+            Err(e) => log::warn!("⚠️  Batch insert of {} rows failed, inserting individually: {:#}", batch.len(), e),
+        }
+    }
+
+    for data in batch {
+        let tags = [("city", data.city.as_deref().unwrap_or("Unknown"))];
+        let started = Instant::now();
+        let inserted = database.insert_or_dead_letter(data, max_attempts).await;
+        metrics.timing("insert.duration", started.elapsed(), &tags);
+
+        match inserted {
+            Ok(InsertOutcome::DeadLettered) => metrics.incr("insert.dead_lettered", &tags),
+            Ok(InsertOutcome::Inserted(row)) => record_inserted(output, data, &row),
+            Err(e) => {
+                metrics.incr("insert.failure", &tags);
+                log::error!("❌ Database insert failed: {}", e);
+            }
+        }
+    }
+}
+
+fn record_inserted(output: &Output, data: &WeatherData, row: &InsertedRow) {
+    let metrics = &output.metrics;
+    let city = data.city.as_deref().unwrap_or("Unknown");
+    metrics.incr("insert.success", &[("city", city)]);
+    metrics.gauge("insert.last_id", row.id as f64, &[("city", city)]);
+    // End-to-end staleness: observation time to confirmed insert
+    let age = chrono::Utc::now().timestamp() - data.timestamp;
+    metrics.histogram("insert.observation_age_seconds", age as f64, &[("city", city)]);
+
+    let units = data.units();
+    log::info!(
+        "✅ Weather data inserted (id {}): {} - 🌡️ {:.1}{} (feels {:.1}{}), 💧 {}%, 🌬️ {:.1} {} {}, ☁️ {} ({})",
+        row.id,
+        city,
+        data.temperature,
+        units.temperature_symbol(),
+        data.feels_like.unwrap_or(0.0),
+        units.temperature_symbol(),
+        data.humidity,
+        data.wind_speed,
+        units.wind_speed_symbol(),
+        data.wind_direction.map_or("-", |degrees| wind_compass(degrees, output.lang)),
+        data.weather_main.as_deref().unwrap_or("Unknown"),
+        data.weather_description.as_deref().unwrap_or("Unknown")
+    );
+
+    let inserted = InsertedObservation {
+        id: row.id,
+        observation: WeatherData {
+            created_at: row.created_at.map(|created_at| created_at.and_utc()),
+            ..data.clone()
+        },
+    };
+    // No subscribers is the normal case
+    let _ = output.inserted.send(inserted);
+}
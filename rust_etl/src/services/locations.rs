@@ -0,0 +1,66 @@
+//! `LOCATION_IDS`: resolves each observation's city to a row of the
+//! `locations` table, cached for the life of the process.
+
+use crate::services::database::DatabaseService;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How observations get a `location_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LocationIds {
+    /// Not resolved; `location_id` stays null.
+    #[default]
+    Off,
+    /// Looked up in `locations`; null for cities that have no row.
+    Existing,
+    /// Looked up, adding a row with just the name for cities that have none.
+    Create,
+}
+
+/// Cached city → `location_id` resolution. Misses are cached too, so a
+/// `locations` row added while running applies after a restart.
+pub struct LocationResolver {
+    mode: LocationIds,
+    ids: HashMap<String, Option<i32>>,
+}
+
+impl LocationResolver {
+    pub fn new(mode: LocationIds) -> Self {
+        Self {
+            mode,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// The `location_id` for `city`, or `None` when resolution is off, the
+    /// city is unknown in `existing` mode, or the lookup failed. Failures
+    /// are logged and retried on the next observation.
+    pub async fn resolve(&mut self, database: &DatabaseService, city: &str) -> Option<i32> {
+        if self.mode == LocationIds::Off {
+            return None;
+        }
+        if let Some(id) = self.ids.get(city) {
+            return *id;
+        }
+
+        let resolved = match self.mode {
+            LocationIds::Off => return None,
+            LocationIds::Existing => database.find_location_id(city).await,
+            LocationIds::Create => database.find_or_create_location(city).await.map(Some),
+        };
+        match resolved {
+            Ok(id) => {
+                if id.is_none() {
+                    log::info!("📍 {} has no row in locations; storing it without location_id", city);
+                }
+                self.ids.insert(city.to_string(), id);
+                id
+            }
+            Err(e) => {
+                log::warn!("⚠️  Could not resolve the location of {}: {:#}", city, e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::config::app_config::AppConfig;
+use crate::models::condition::WeatherCondition;
+use crate::models::units::Units;
+use crate::models::weather::WeatherData;
+use crate::services::fetch_error::FetchError;
+use crate::services::field_mapping::FieldMapping;
+use crate::services::weather_service::{http_client, latency_ms, read_limited, retry_after, status_error};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Instant;
+
+/// Client for a provider described entirely by configuration: a URL
+/// template and a [`FieldMapping`] from `MAPPED_PROVIDER_FIELDS`.
+pub struct MappedService {
+    client: Client,
+    url_template: String,
+    api_key: String,
+    mapping: FieldMapping,
+    max_response_bytes: usize,
+    units: Units,
+    resolve_timezone_name: bool,
+    canonical_weather_main: bool,
+    record_api_latency: bool,
+}
+
+impl MappedService {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        let mapping = FieldMapping::new(&config.mapped_provider_fields).context("Invalid MAPPED_PROVIDER_FIELDS")?;
+
+        Ok(Self {
+            client: http_client(config),
+            url_template: config.mapped_provider_url.clone(),
+            api_key: config.mapped_provider_api_key.clone(),
+            mapping,
+            max_response_bytes: config.max_response_bytes,
+            units: config.units,
+            resolve_timezone_name: config.resolve_timezone_name,
+            canonical_weather_main: config.canonical_weather_main,
+            record_api_latency: config.record_api_latency,
+        })
+    }
+
+    /// The request URL: `{city}`, `{api_key}` and `{units}` in the template
+    /// are replaced, URL-encoded.
+    fn url(&self, city: &str) -> String {
+        self.url_template
+            .replace("{city}", &urlencoding::encode(city))
+            .replace("{api_key}", &urlencoding::encode(&self.api_key))
+            .replace("{units}", self.units.name())
+    }
+
+    pub async fn fetch_weather(&self, city: &str) -> Result<WeatherData> {
+        log::info!("🌤️  Fetching weather data for {} from the mapped provider", city);
+
+        let started = Instant::now();
+        let response = self.client
+            .get(self.url(city))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("Failed to send request to the mapped provider")?;
+
+        let status = response.status();
+        let retry_after = retry_after(&response);
+        let body = read_limited(response, self.max_response_bytes).await?;
+        let latency = started.elapsed();
+        let body = if self.api_key.is_empty() { body } else { body.replace(&self.api_key, "****") };
+
+        if !status.is_success() {
+            return Err(status_error("Mapped provider", status, retry_after, &body));
+        }
+
+        let document: Value = serde_json::from_str(&body).map_err(|source| FetchError::Parse {
+            source,
+            snippet: body.chars().take(512).collect(),
+        })?;
+
+        let mut weather_data = self
+            .mapping
+            .extract(&document, city, self.units)
+            .context("Mapped provider response does not match MAPPED_PROVIDER_FIELDS")?;
+        if !self.resolve_timezone_name {
+            weather_data.timezone_name = None;
+        }
+        if self.canonical_weather_main {
+            weather_data.weather_main = weather_data
+                .weather_main
+                .as_deref()
+                .map(|main| WeatherCondition::from(main).to_string());
+        }
+        if self.record_api_latency {
+            weather_data.api_latency_ms = Some(latency_ms(latency));
+        }
+
+        log::info!(
+            "✅ Successfully fetched weather for {} from the mapped provider: {:.1}{}, {}",
+            weather_data.city.as_deref().unwrap_or("Unknown"),
+            weather_data.temperature,
+            self.units.temperature_symbol(),
+            weather_data.weather_main.as_deref().unwrap_or("Unknown")
+        );
+
+        Ok(weather_data)
+    }
+}
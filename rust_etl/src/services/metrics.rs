@@ -0,0 +1,186 @@
+use crate::config::app_config::AppConfig;
+use anyhow::Result;
+#[cfg(feature = "server")]
+use anyhow::Context;
+#[cfg(feature = "server")]
+use std::net::UdpSocket;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Value of the `city` tag for cities past `METRICS_MAX_CITIES`.
+pub const OTHER_CITY: &str = "other";
+
+/// Bounds the distinct values of the `city` tag: dropped altogether with
+/// `METRICS_CITY_LABELS=false`, otherwise the first `limit` cities seen keep
+/// their name and later ones share [`OTHER_CITY`].
+pub struct CityLabels {
+    enabled: bool,
+    limit: usize,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl CityLabels {
+    /// `limit` 0 allows any number of cities.
+    pub fn new(enabled: bool, limit: usize) -> Self {
+        Self {
+            enabled,
+            limit,
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The tag value to send for `city`, or `None` to leave the tag off.
+    pub fn label<'a>(&self, city: &'a str) -> Option<&'a str> {
+        if !self.enabled {
+            return None;
+        }
+        if self.limit == 0 {
+            return Some(city);
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        if seen.contains(city) {
+            return Some(city);
+        }
+        if seen.len() < self.limit {
+            seen.insert(city.to_string());
+            return Some(city);
+        }
+        Some(OTHER_CITY)
+    }
+}
+
+/// Fire-and-forget StatsD client sending one datagram per metric over UDP.
+#[cfg(feature = "server")]
+pub struct StatsdClient {
+    socket: UdpSocket,
+    prefix: String,
+    tags: bool,
+    city_labels: CityLabels,
+}
+
+#[cfg(feature = "server")]
+impl StatsdClient {
+    pub fn new(addr: &str, prefix: &str, tags: bool, city_labels: CityLabels) -> Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .context("Failed to bind StatsD socket")?;
+        socket
+            .connect(addr)
+            .with_context(|| format!("Failed to resolve StatsD address {}", addr))?;
+        socket
+            .set_nonblocking(true)
+            .context("Failed to configure StatsD socket")?;
+
+        Ok(Self {
+            socket,
+            prefix: prefix.trim_end_matches('.').to_string(),
+            tags,
+            city_labels,
+        })
+    }
+
+    fn send(&self, name: &str, value: &str, kind: &str, tags: &[(&str, &str)]) {
+        let mut line = if self.prefix.is_empty() {
+            format!("{}:{}|{}", name, value, kind)
+        } else {
+            format!("{}.{}:{}|{}", self.prefix, name, value, kind)
+        };
+
+        // DogStatsD tag extension; plain StatsD servers should run with tags disabled
+        if self.tags && !tags.is_empty() {
+            let rendered: Vec<String> = tags
+                .iter()
+                .filter_map(|&(k, v)| match k {
+                    "city" => self.city_labels.label(v).map(|city| (k, city)),
+                    _ => Some((k, v)),
+                })
+                .map(|(k, v)| format!("{}:{}", k, v))
+                .collect();
+            if !rendered.is_empty() {
+                line.push_str("|#");
+                line.push_str(&rendered.join(","));
+            }
+        }
+
+        if let Err(e) = self.socket.send(line.as_bytes()) {
+            log::debug!("StatsD send failed for {}: {}", name, e);
+        }
+    }
+}
+
+/// Instrumentation facade used by the ETL loop. Every call is a no-op when no
+/// backend is configured or the crate is built without the `server` feature.
+#[derive(Default)]
+pub struct Metrics {
+    #[cfg(feature = "server")]
+    statsd: Option<StatsdClient>,
+}
+
+#[cfg(feature = "server")]
+impl Metrics {
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let statsd = match &config.statsd_addr {
+            Some(addr) => Some(StatsdClient::new(
+                addr,
+                &config.statsd_prefix,
+                config.statsd_tags,
+                CityLabels::new(config.metrics_city_labels, config.metrics_max_cities),
+            )?),
+            None => None,
+        };
+
+        Ok(Self { statsd })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.statsd.is_some()
+    }
+
+    pub fn incr(&self, name: &str, tags: &[(&str, &str)]) {
+        if let Some(statsd) = &self.statsd {
+            statsd.send(name, "1", "c", tags);
+        }
+    }
+
+    pub fn timing(&self, name: &str, elapsed: Duration, tags: &[(&str, &str)]) {
+        if let Some(statsd) = &self.statsd {
+            statsd.send(name, &elapsed.as_millis().to_string(), "ms", tags);
+        }
+    }
+
+    pub fn gauge(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        if let Some(statsd) = &self.statsd {
+            statsd.send(name, &value.to_string(), "g", tags);
+        }
+    }
+
+    /// Records one sample of a distribution. `statsd_exporter` and the Datadog
+    /// agent aggregate these into histogram buckets/percentiles.
+    pub fn histogram(&self, name: &str, value: f64, tags: &[(&str, &str)]) {
+        if let Some(statsd) = &self.statsd {
+            statsd.send(name, &value.to_string(), "h", tags);
+        }
+    }
+}
+
+#[cfg(not(feature = "server"))]
+impl Metrics {
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        if config.statsd_addr.is_some() {
+            log::warn!("⚠️  STATSD_ADDR is set but this build has no `server` feature; metrics are disabled");
+        }
+        Ok(Self::default())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        false
+    }
+
+    pub fn incr(&self, _name: &str, _tags: &[(&str, &str)]) {}
+
+    pub fn timing(&self, _name: &str, _elapsed: Duration, _tags: &[(&str, &str)]) {}
+
+    pub fn gauge(&self, _name: &str, _value: f64, _tags: &[(&str, &str)]) {}
+
+    pub fn histogram(&self, _name: &str, _value: f64, _tags: &[(&str, &str)]) {}
+}
@@ -1,4 +1,25 @@
+pub mod api_keys;
+pub mod change_detector;
+pub mod collect_trigger;
+pub mod collect_window;
+pub mod collector;
 pub mod database;
+pub mod day_extremes;
+#[cfg(feature = "fault-injection")]
+pub mod db_faults;
+pub mod fetch_error;
+pub mod field_mapping;
+pub mod geolocation;
+pub mod insert_writer;
+pub mod locations;
+pub mod mapped_service;
+pub mod metrics;
+pub mod pressure_trend;
+pub mod provider;
+pub mod runtime_state;
+pub mod schema_drift;
+pub mod smoothing;
+pub mod storage_sampler;
 pub mod weather_service;
-
+pub mod weatherapi_service;
 
@@ -0,0 +1,60 @@
+use crate::models::weather::PressureTrend;
+use std::collections::{HashMap, HashSet};
+
+/// Remembers the pressure of the last stored observation per city, so each
+/// new reading can be classified as rising, steady or falling.
+pub struct PressureTrendTracker {
+    threshold: i32,
+    last: HashMap<String, i32>,
+    seeded: HashSet<String>,
+}
+
+impl PressureTrendTracker {
+    /// `threshold` is the smallest change in hPa that counts as a trend.
+    pub fn new(threshold: i32) -> Self {
+        Self {
+            threshold,
+            last: HashMap::new(),
+            seeded: HashSet::new(),
+        }
+    }
+
+    /// Whether `city` has been seeded from the database (or recorded) yet.
+    pub fn is_seeded(&self, city: &str) -> bool {
+        self.seeded.contains(city)
+    }
+
+    /// Seeds `city` with the pressure of its latest stored row, if any, so the
+    /// lookup is not repeated for cities with no history.
+    pub fn seed(&mut self, city: &str, pressure: Option<i32>) {
+        self.seeded.insert(city.to_string());
+        if let Some(pressure) = pressure {
+            self.last.insert(city.to_string(), pressure);
+        }
+    }
+
+    /// Trend of `pressure` against the last stored reading for `city`.
+    pub fn trend(&self, city: &str, pressure: Option<i32>) -> PressureTrend {
+        PressureTrend::between(self.last.get(city).copied(), pressure, self.threshold)
+    }
+
+    /// Records `pressure` as the last stored reading for `city`.
+    pub fn record(&mut self, city: &str, pressure: Option<i32>) {
+        self.seeded.insert(city.to_string());
+        if let Some(pressure) = pressure {
+            self.last.insert(city.to_string(), pressure);
+        }
+    }
+
+    /// The last stored pressure of each city, for `STATE_FILE`.
+    pub fn snapshot(&self) -> HashMap<String, i32> {
+        self.last.clone()
+    }
+
+    /// Continues from the pressures of a [`snapshot`](Self::snapshot).
+    pub fn restore(&mut self, last: HashMap<String, i32>) {
+        for (city, pressure) in last {
+            self.seed(&city, Some(pressure));
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::config::app_config::AppConfig;
+use crate::models::weather::{ComfortThresholds, WeatherData};
+use crate::services::fetch_error::FetchError;
+use crate::services::mapped_service::MappedService;
+use crate::services::weather_service::WeatherService;
+use crate::services::weatherapi_service::WeatherApiService;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// Weather data providers selectable with `WEATHER_PROVIDER` and
+/// `FALLBACK_PROVIDER`. The lowercase name is also stored in `source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    OpenWeatherMap,
+    WeatherApi,
+    /// Any JSON endpoint, read through `MAPPED_PROVIDER_FIELDS`.
+    Mapped,
+}
+
+impl ProviderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderKind::OpenWeatherMap => "openweathermap",
+            ProviderKind::WeatherApi => "weatherapi",
+            ProviderKind::Mapped => "mapped",
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What to do with an observation whose timestamp is further in the future
+/// than `MAX_FUTURE_SKEW_SECONDS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FutureTimestampAction {
+    /// Reject the fetch so the row is never stored.
+    Drop,
+    /// Store the row with `timestamp_suspect` set.
+    Flag,
+}
+
+/// Plausible sea-level pressure; the recorded extremes are about 870 and
+/// 1084 hPa.
+pub const PRESSURE_RANGE_HPA: RangeInclusive<i32> = 850..=1100;
+
+/// Plausible station pressure; about 300 hPa on the highest summits.
+pub const GROUND_PRESSURE_RANGE_HPA: RangeInclusive<i32> = 250..=1100;
+
+enum ProviderClient {
+    OpenWeatherMap(Box<WeatherService>),
+    WeatherApi(WeatherApiService),
+    Mapped(Box<MappedService>),
+}
+
+/// A configured provider client plus the sanity checks applied to every
+/// observation it returns.
+pub struct WeatherProvider {
+    client: ProviderClient,
+    max_future_skew: Duration,
+    future_action: FutureTimestampAction,
+    comfort: Option<ComfortThresholds>,
+    wind_chill: bool,
+}
+
+impl WeatherProvider {
+    /// Fails when the provider's settings are unusable, such as an invalid
+    /// `MAPPED_PROVIDER_FIELDS`.
+    pub fn new(kind: ProviderKind, config: &AppConfig) -> Result<Self> {
+        let client = match kind {
+            ProviderKind::OpenWeatherMap => ProviderClient::OpenWeatherMap(Box::new(WeatherService::new(config))),
+            ProviderKind::WeatherApi => ProviderClient::WeatherApi(WeatherApiService::new(config)),
+            ProviderKind::Mapped => ProviderClient::Mapped(Box::new(MappedService::new(config)?)),
+        };
+
+        Ok(Self {
+            client,
+            max_future_skew: config.max_future_skew,
+            future_action: config.future_timestamp_action,
+            comfort: config.comfort_thresholds(),
+            wind_chill: config.wind_chill,
+        })
+    }
+
+    pub fn kind(&self) -> ProviderKind {
+        match self.client {
+            ProviderClient::OpenWeatherMap(_) => ProviderKind::OpenWeatherMap,
+            ProviderClient::WeatherApi(_) => ProviderKind::WeatherApi,
+            ProviderClient::Mapped(_) => ProviderKind::Mapped,
+        }
+    }
+
+    /// Fetches current conditions for `city`, tagging the record with the
+    /// provider it came from.
+    pub async fn fetch_weather(&self, city: &str) -> Result<WeatherData> {
+        let mut data = match &self.client {
+            ProviderClient::OpenWeatherMap(service) => service.fetch_weather(city).await?,
+            ProviderClient::WeatherApi(service) => service.fetch_weather(city).await?,
+            ProviderClient::Mapped(service) => service.fetch_weather(city).await?,
+        };
+        data.source = Some(self.kind().name().to_string());
+        self.check_ranges(&mut data);
+        data.apply_computed(self.comfort.as_ref(), self.wind_chill);
+        self.check_timestamp(&mut data)?;
+        Ok(data)
+    }
+
+    /// Clamps humidity to 0-100% and treats a pressure outside
+    /// [`PRESSURE_RANGE_HPA`] (or [`GROUND_PRESSURE_RANGE_HPA`] at ground
+    /// level) as unknown, so one bad field neither fails the insert nor
+    /// skews the derived columns.
+    fn check_ranges(&self, data: &mut WeatherData) {
+        let city = data.city.clone().unwrap_or_else(|| "Unknown".to_string());
+        if !(0..=100).contains(&data.humidity) {
+            log::warn!(
+                "⚠️  {} returned humidity {}% for {}; clamping to 0-100",
+                self.kind(),
+                data.humidity,
+                city
+            );
+            data.humidity = data.humidity.clamp(0, 100);
+        }
+        if let Some(pressure) = data.pressure.filter(|pressure| !PRESSURE_RANGE_HPA.contains(pressure)) {
+            log::warn!(
+                "⚠️  {} returned pressure {} hPa for {}; storing it as unknown",
+                self.kind(),
+                pressure,
+                city
+            );
+            data.pressure = None;
+        }
+        if let Some(pressure) = data.sea_level_pressure.filter(|pressure| !PRESSURE_RANGE_HPA.contains(pressure)) {
+            log::warn!(
+                "⚠️  {} returned sea-level pressure {} hPa for {}; storing it as unknown",
+                self.kind(),
+                pressure,
+                city
+            );
+            data.sea_level_pressure = None;
+        }
+        if let Some(pressure) = data.ground_level_pressure.filter(|pressure| !GROUND_PRESSURE_RANGE_HPA.contains(pressure)) {
+            log::warn!(
+                "⚠️  {} returned ground-level pressure {} hPa for {}; storing it as unknown",
+                self.kind(),
+                pressure,
+                city
+            );
+            data.ground_level_pressure = None;
+        }
+    }
+
+    /// Drops or flags observations dated too far ahead of the local clock,
+    /// which would otherwise sort ahead of every later reading.
+    fn check_timestamp(&self, data: &mut WeatherData) -> Result<()> {
+        let now = chrono::Utc::now().timestamp();
+        let ahead_by = data.timestamp - now;
+        if ahead_by <= self.max_future_skew.as_secs() as i64 {
+            return Ok(());
+        }
+
+        log::warn!(
+            "⚠️  {} returned a timestamp {}s in the future for {} (dt={}, now={})",
+            self.kind(),
+            ahead_by,
+            data.city.as_deref().unwrap_or("Unknown"),
+            data.timestamp,
+            now
+        );
+
+        match self.future_action {
+            FutureTimestampAction::Drop => Err(FetchError::FutureTimestamp {
+                timestamp: data.timestamp,
+                ahead_by,
+            }
+            .into()),
+            FutureTimestampAction::Flag => {
+                data.timestamp_suspect = true;
+                Ok(())
+            }
+        }
+    }
+}
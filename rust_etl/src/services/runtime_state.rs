@@ -0,0 +1,74 @@
+//! `STATE_FILE`: the collector's per-city running state, saved after each
+//! cycle and restored on startup so a restart doesn't reset it.
+
+use crate::models::units::Units;
+use crate::services::day_extremes::DayRange;
+use crate::services::storage_sampler::SamplerState;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Format version written to the file. Files from a newer version are
+/// ignored rather than misread.
+pub const STATE_VERSION: u32 = 1;
+
+/// Snapshot of [`Collector`](crate::services::collector::Collector) state,
+/// keyed by city.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub version: u32,
+    /// Unix seconds when the snapshot was taken.
+    pub saved_at: i64,
+    /// Units of the temperatures below; they are not restored under others.
+    pub units: Units,
+    #[serde(default)]
+    pub temperature_ema: HashMap<String, f64>,
+    #[serde(default)]
+    pub day_extremes: HashMap<String, DayRange>,
+    /// Pressure of the last stored reading, for the pressure trend.
+    #[serde(default)]
+    pub last_pressure: HashMap<String, i32>,
+    #[serde(default)]
+    pub storage_sampler: SamplerState,
+    /// UTC offset of each configured city's latest observation.
+    #[serde(default)]
+    pub utc_offsets: HashMap<String, i32>,
+}
+
+impl RuntimeState {
+    /// Reads the state saved at `path`. Returns `None` when there is no
+    /// file yet.
+    pub async fn load(path: &Path) -> Result<Option<Self>> {
+        let text = match tokio::fs::read_to_string(path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let state: Self = serde_json::from_str(&text).with_context(|| format!("Invalid state file {}", path.display()))?;
+        if state.version > STATE_VERSION {
+            return Err(anyhow::anyhow!(
+                "{} has state version {}; this build reads up to {}",
+                path.display(),
+                state.version,
+                STATE_VERSION
+            ));
+        }
+        Ok(Some(state))
+    }
+
+    /// Writes the state to `path` through a temporary file, so a crash mid-write
+    /// leaves the previous snapshot intact.
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        let mut temporary = PathBuf::from(path).into_os_string();
+        temporary.push(".tmp");
+        let text = serde_json::to_string(self)?;
+        tokio::fs::write(&temporary, text)
+            .await
+            .with_context(|| format!("Failed to write {}", Path::new(&temporary).display()))?;
+        tokio::fs::rename(&temporary, path)
+            .await
+            .with_context(|| format!("Failed to replace {}", path.display()))
+    }
+}
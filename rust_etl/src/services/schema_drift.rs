@@ -0,0 +1,117 @@
+//! Compares raw API responses with the documented response shape, so fields
+//! OpenWeatherMap adds, renames or drops are noticed (`STRICT_PARSING`).
+
+use serde_json::Value;
+
+/// Documented fields of a JSON object.
+pub struct Shape(&'static [Field]);
+
+pub struct Field {
+    name: &'static str,
+    /// Present in every documented response.
+    required: bool,
+    /// Shape of a nested object, or of each element of a nested array.
+    nested: Option<&'static Shape>,
+}
+
+const fn required(name: &'static str) -> Field {
+    Field { name, required: true, nested: None }
+}
+
+const fn optional(name: &'static str) -> Field {
+    Field { name, required: false, nested: None }
+}
+
+const fn nested(name: &'static str, required: bool, shape: &'static Shape) -> Field {
+    Field { name, required, nested: Some(shape) }
+}
+
+static COORD: Shape = Shape(&[required("lon"), required("lat")]);
+
+static CONDITION: Shape = Shape(&[required("id"), required("main"), required("description"), required("icon")]);
+
+static MAIN: Shape = Shape(&[
+    required("temp"),
+    required("feels_like"),
+    required("temp_min"),
+    required("temp_max"),
+    required("pressure"),
+    required("humidity"),
+    optional("sea_level"),
+    optional("grnd_level"),
+]);
+
+static WIND: Shape = Shape(&[required("speed"), optional("deg"), optional("gust")]);
+
+static CLOUDS: Shape = Shape(&[required("all")]);
+
+static PRECIPITATION: Shape = Shape(&[optional("1h"), optional("3h")]);
+
+static SYS: Shape = Shape(&[
+    optional("type"),
+    optional("id"),
+    optional("message"),
+    optional("country"),
+    optional("sunrise"),
+    optional("sunset"),
+]);
+
+/// Current weather (`/data/2.5/weather`).
+pub static CURRENT_WEATHER: Shape = Shape(&[
+    nested("coord", true, &COORD),
+    nested("weather", true, &CONDITION),
+    optional("base"),
+    nested("main", true, &MAIN),
+    optional("visibility"),
+    nested("wind", true, &WIND),
+    nested("clouds", true, &CLOUDS),
+    nested("rain", false, &PRECIPITATION),
+    nested("snow", false, &PRECIPITATION),
+    required("dt"),
+    nested("sys", true, &SYS),
+    optional("timezone"),
+    required("id"),
+    required("name"),
+    optional("cod"),
+]);
+
+/// Lists unknown and missing fields of `value` as dotted paths, e.g.
+/// `unknown field main.temp_feel`. Empty when the shape matches.
+pub fn drift(value: &Value, shape: &Shape) -> Vec<String> {
+    let mut found = Vec::new();
+    check(value, shape, "", &mut found);
+    found
+}
+
+fn check(value: &Value, shape: &Shape, prefix: &str, found: &mut Vec<String>) {
+    let object = match value {
+        Value::Object(object) => object,
+        Value::Array(items) => {
+            for item in items {
+                check(item, shape, prefix, found);
+            }
+            return;
+        }
+        _ => return,
+    };
+
+    for name in object.keys() {
+        if !shape.0.iter().any(|field| field.name == name) {
+            found.push(format!("unknown field {}{}", prefix, name));
+        }
+    }
+    for field in shape.0 {
+        match object.get(field.name) {
+            None | Some(Value::Null) if field.required => {
+                found.push(format!("missing field {}{}", prefix, field.name));
+            }
+            Some(value) => {
+                if let Some(nested) = field.nested {
+                    check(value, nested, &format!("{}{}.", prefix, field.name), found);
+                }
+            }
+            _ => {}
+        }
+    }
+    found.dedup();
+}
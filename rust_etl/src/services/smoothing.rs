@@ -0,0 +1,65 @@
+use std::collections::{HashMap, HashSet};
+
+/// Per-city exponential moving average of temperature. Kept in memory, so it
+/// starts over on restart unless seeded from the last stored row.
+pub struct TemperatureEma {
+    alpha: f64,
+    values: HashMap<String, f64>,
+    seeded: HashSet<String>,
+}
+
+impl TemperatureEma {
+    /// `alpha` is the weight of each new reading, in `(0, 1]`; 1 disables
+    /// smoothing.
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha,
+            values: HashMap::new(),
+            seeded: HashSet::new(),
+        }
+    }
+
+    /// Whether `city` has been seeded (or updated) yet.
+    pub fn is_seeded(&self, city: &str) -> bool {
+        self.seeded.contains(city)
+    }
+
+    /// Starts `city` from a previous average, if any, so the lookup is not
+    /// repeated for cities with no history.
+    pub fn seed(&mut self, city: &str, previous: Option<f64>) {
+        self.seeded.insert(city.to_string());
+        if let Some(previous) = previous {
+            self.values.insert(city.to_string(), previous);
+        }
+    }
+
+    /// The average for `city` once `temperature` is folded in, without
+    /// folding it in. The first reading for a city is taken as is.
+    pub fn next(&self, city: &str, temperature: f64) -> f64 {
+        match self.values.get(city) {
+            Some(previous) => self.alpha * temperature + (1.0 - self.alpha) * previous,
+            None => temperature,
+        }
+    }
+
+    /// Folds `temperature` into the average for `city` and returns the new
+    /// average.
+    pub fn update(&mut self, city: &str, temperature: f64) -> f64 {
+        let ema = self.next(city, temperature);
+        self.seeded.insert(city.to_string());
+        self.values.insert(city.to_string(), ema);
+        ema
+    }
+
+    /// The current average of each city, for `STATE_FILE`.
+    pub fn snapshot(&self) -> HashMap<String, f64> {
+        self.values.clone()
+    }
+
+    /// Continues the averages of a [`snapshot`](Self::snapshot).
+    pub fn restore(&mut self, values: HashMap<String, f64>) {
+        for (city, value) in values {
+            self.seed(&city, Some(value));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Decimates storage independently of the fetch cadence. Per city, only
+/// every `every`th successful fetch is stored, starting with the first one,
+/// and with a non-zero `resolution` at most one observation is stored per
+/// resolution-aligned time bucket (by observation timestamp).
+///
+/// Sampling runs before the diff-only filter. A sampled observation is then
+/// compared against the last *stored* row, so enabling both can only reduce
+/// storage further and the two never fight: with `STORE_EVERY_N=6` and
+/// `DIFF_ONLY_INSERT=true`, at most one row per six fetches is written, and
+/// only if it differs from the previous stored row.
+pub struct StorageSampler {
+    every: u64,
+    resolution: u64,
+    state: SamplerState,
+}
+
+/// Per-city progress of a [`StorageSampler`], for `STATE_FILE`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplerState {
+    /// Successful fetches counted so far.
+    pub counts: HashMap<String, u64>,
+    /// `STORAGE_RESOLUTION` bucket of the last observation stored.
+    pub last_buckets: HashMap<String, i64>,
+}
+
+impl StorageSampler {
+    pub fn new(every: u64, resolution: Duration) -> Self {
+        if !resolution.is_zero() && resolution.as_secs() == 0 {
+            log::warn!(
+                "⚠️  Storage resolution {:?} is under a second; storing every sampled fetch",
+                resolution
+            );
+        }
+        Self {
+            every: every.max(1),
+            resolution: resolution.as_secs(),
+            state: SamplerState::default(),
+        }
+    }
+
+    /// The per-city counts and buckets, for `STATE_FILE`.
+    pub fn snapshot(&self) -> SamplerState {
+        self.state.clone()
+    }
+
+    /// Continues from a [`snapshot`](Self::snapshot), so a restart keeps
+    /// the `STORE_EVERY_N` phase and doesn't store twice in one bucket.
+    pub fn restore(&mut self, state: SamplerState) {
+        self.state = state;
+    }
+
+    /// Whether the next successful fetch for `city`, observed at `timestamp`
+    /// (Unix seconds), should be stored. Nothing is counted until
+    /// [`record`](Self::record).
+    pub fn should_store(&self, city: &str, timestamp: i64) -> bool {
+        let count = self.state.counts.get(city).copied().unwrap_or(0);
+        if !count.is_multiple_of(self.every) {
+            return false;
+        }
+        self.resolution == 0 || self.state.last_buckets.get(city) != Some(&self.bucket(timestamp))
+    }
+
+    /// Counts a fetch for `city` once it has been stored or skipped on
+    /// purpose. A fetch that was due takes its `STORAGE_RESOLUTION` bucket.
+    pub fn record(&mut self, city: &str, timestamp: i64) {
+        let count = self.state.counts.entry(city.to_string()).or_insert(0);
+        let sampled = count.is_multiple_of(self.every);
+        *count += 1;
+        if sampled && self.resolution != 0 {
+            let bucket = self.bucket(timestamp);
+            self.state.last_buckets.insert(city.to_string(), bucket);
+        }
+    }
+
+    fn bucket(&self, timestamp: i64) -> i64 {
+        timestamp.div_euclid(self.resolution as i64)
+    }
+}
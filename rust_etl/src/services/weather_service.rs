@@ -1,71 +1,747 @@
-use crate::models::weather::{ApiResponse, WeatherData};
-use reqwest::Client;
-use std::time::Duration;
+use crate::config::app_config::{AppConfig, DEFAULT_ONECALL_V25_PATH_TEMPLATE};
+use crate::models::units::Units;
+use crate::models::weather::{ApiResponse, GeocodedCity, OneCallResponse, WeatherData};
+use crate::services::api_keys::{ApiKeyRing, NoUsableKey};
+use crate::services::fetch_error::FetchError;
+use crate::services::schema_drift::{self, Shape};
+use crate::utils::correlation;
+use crate::utils::signing::RequestSigner;
+use crate::utils::timezone;
+use reqwest::{redirect, Client};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use anyhow::{Result, Context};
 
 pub struct WeatherService {
     client: Client,
-    api_key: String,
+    signer: Option<RequestSigner>,
+    correlation_id_header: Option<String>,
+    api_keys: ApiKeyRing,
+    quota_reset: Duration,
+    base_url: String,
+    path_template: String,
+    tier: ApiTier,
+    geocoding_path_template: String,
+    /// Geocoding results by configured city name, looked up once per run.
+    geocoded: Mutex<HashMap<String, GeocodedCity>>,
+    onecall_path_template: String,
+    onecall_v25_path_template: String,
+    onecall_fallback_to_v25: bool,
+    /// Set once One Call 3.0 turned out to need a subscription the key lacks.
+    onecall_use_v25: AtomicBool,
+    units: Units,
+    resolve_timezone_name: bool,
+    canonical_weather_main: bool,
+    collect_uv_index: bool,
+    collect_minutely_precip: bool,
+    record_api_latency: bool,
+    retry_on_parse_error: bool,
+    max_response_bytes: usize,
+    strict_parsing: bool,
+    /// Drift already logged, so each difference is reported once per run.
+    reported_drift: Mutex<HashSet<String>>,
 }
 
+/// `template` with `minutely` removed from its `exclude` list when the
+/// nowcast is wanted, and unchanged otherwise.
+fn onecall_template(template: &str, minutely: bool) -> String {
+    let Some(start) = template.find("exclude=").map(|at| at + "exclude=".len()).filter(|_| minutely) else {
+        return template.to_string();
+    };
+    let end = template[start..].find('&').map_or(template.len(), |at| start + at);
+    let parts: Vec<&str> = template[start..end].split(',').filter(|part| *part != "minutely").collect();
+    format!("{}{}{}", &template[..start], parts.join(","), &template[end..])
+}
+
+/// Outcome of a single raw request, used by `rust_etl doctor`.
+pub struct ProbeResult {
+    pub status: reqwest::StatusCode,
+    pub server_date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Whether a failed fetch is worth retrying with backoff. Oversized and
+/// unparseable responses are not: the first would repeat and the second has
+/// its own single retry. Neither are key, permission and quota errors, which
+/// only a config change or the quota window resetting fixes, or a rejected
+/// future timestamp.
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    !matches!(
+        err.downcast_ref::<FetchError>(),
+        Some(FetchError::ResponseTooLarge { .. })
+            | Some(FetchError::Parse { .. })
+            | Some(FetchError::InvalidApiKey { .. })
+            | Some(FetchError::Forbidden { .. })
+            | Some(FetchError::OneCallNotSubscribed)
+            | Some(FetchError::QuotaExceeded { .. })
+            | Some(FetchError::KeysExhausted { .. })
+            | Some(FetchError::FutureTimestamp { .. })
+    )
+}
+
+/// Whether collection should stop: every configured key was rejected, and
+/// a rejected key won't start working on its own.
+pub fn is_fatal(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref::<FetchError>(), Some(FetchError::InvalidApiKey { .. }))
+}
+
+/// How long to wait before the next fetch after a quota or permission error,
+/// overriding the normal interval if longer.
+pub fn cooldown(err: &anyhow::Error, quota_reset: Duration) -> Option<Duration> {
+    match err.downcast_ref::<FetchError>()? {
+        FetchError::KeysExhausted { retry_in } => Some(*retry_in),
+        FetchError::QuotaExceeded { retry_after, .. } => Some(retry_after.unwrap_or(quota_reset)),
+        FetchError::Forbidden { .. } => Some(quota_reset),
+        _ => None,
+    }
+}
+
+/// Provider error codes meaning the key is over its quota: OpenWeatherMap's
+/// `cod` 429 and WeatherAPI.com's 2007.
+const QUOTA_ERROR_CODES: &[i64] = &[429, 2007];
+
+/// Provider error codes meaning the key itself is refused: WeatherAPI.com's
+/// 2008, a disabled key.
+const REJECTED_KEY_ERROR_CODES: &[i64] = &[2008];
+
+/// Maps an unsuccessful response to a typed error where the status has a
+/// specific meaning. A 401 from One Call 3.0 about its separate subscription
+/// is not a bad key. A 403 is classified by the provider's error code in the
+/// body: quota exhaustion, a refused key, or otherwise a plan or permission
+/// restriction.
+pub(crate) fn status_error(
+    provider: &'static str,
+    status: reqwest::StatusCode,
+    retry_after: Option<Duration>,
+    body: &str,
+) -> anyhow::Error {
+    match status.as_u16() {
+        401 if body.contains("One Call 3.0") => FetchError::OneCallNotSubscribed.into(),
+        401 => FetchError::InvalidApiKey { provider }.into(),
+        429 => FetchError::QuotaExceeded { status: 429, retry_after }.into(),
+        403 => match provider_error_code(body) {
+            Some(code) if QUOTA_ERROR_CODES.contains(&code) => FetchError::QuotaExceeded { status: 403, retry_after }.into(),
+            Some(code) if REJECTED_KEY_ERROR_CODES.contains(&code) => FetchError::InvalidApiKey { provider }.into(),
+            _ => FetchError::Forbidden {
+                provider,
+                message: body.to_string(),
+            }
+            .into(),
+        },
+        _ => FetchError::Status {
+            provider,
+            status,
+            body: body.to_string(),
+        }
+        .into(),
+    }
+}
+
+/// HTTP status of the response behind a failed fetch, or `None` when no
+/// response was received (connection errors, every key resting). Parse and
+/// timestamp errors come from a successful response.
+pub fn http_status(err: &anyhow::Error) -> Option<u16> {
+    match err.downcast_ref::<FetchError>()? {
+        FetchError::Status { status, .. } => Some(status.as_u16()),
+        FetchError::QuotaExceeded { status, .. } => Some(*status),
+        FetchError::InvalidApiKey { .. } | FetchError::OneCallNotSubscribed => Some(401),
+        FetchError::Forbidden { .. } => Some(403),
+        FetchError::Parse { .. } | FetchError::FutureTimestamp { .. } | FetchError::ErrorCode { .. } => Some(200),
+        FetchError::ResponseTooLarge { .. } | FetchError::KeysExhausted { .. } => None,
+    }
+}
+
+/// OpenWeatherMap's `cod` for a failed fetch, read from the error body when
+/// the response had one.
+pub fn api_code(err: &anyhow::Error) -> Option<i32> {
+    let body = match err.downcast_ref::<FetchError>()? {
+        FetchError::ErrorCode { cod } => return Some(*cod),
+        FetchError::Status { body, .. } => body,
+        FetchError::Forbidden { message, .. } => message,
+        _ => return None,
+    };
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    json_code(body.get("cod")?).and_then(|cod| i32::try_from(cod).ok())
+}
+
+/// The provider's own error code in an error body: OpenWeatherMap's `cod`
+/// or WeatherAPI.com's `error.code`.
+fn provider_error_code(body: &str) -> Option<i64> {
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    json_code(body.get("cod").or_else(|| body.get("error")?.get("code"))?)
+}
+
+/// An error code given as a number or, as on some OpenWeatherMap
+/// endpoints, a string.
+fn json_code(code: &serde_json::Value) -> Option<i64> {
+    match code {
+        serde_json::Value::Number(code) => code.as_i64(),
+        serde_json::Value::String(code) => code.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Milliseconds for the `api_latency_ms` column, saturating at `i32::MAX`.
+pub(crate) fn latency_ms(elapsed: Duration) -> i32 {
+    i32::try_from(elapsed.as_millis()).unwrap_or(i32::MAX)
+}
+
+/// Parses a `Retry-After` header given in seconds.
+pub(crate) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// Longest slice of a response body kept in logs and parse errors.
+const BODY_SNIPPET_CHARS: usize = 512;
+
 impl WeatherService {
-    pub fn new(api_key: String) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent("WeatherETL/1.0")
-            .build()
-            .expect("Failed to create HTTP client");
+    pub fn new(config: &AppConfig) -> Self {
+        let client = http_client(config);
+
+        let signer = config.request_signing_secret.as_deref().map(|secret| {
+            RequestSigner::new(secret, &config.request_signature_header, &config.request_timestamp_header)
+        });
+
+        Self {
+            client,
+            signer,
+            correlation_id_header: Some(config.correlation_id_header.trim().to_string()).filter(|header| !header.is_empty()),
+            api_keys: ApiKeyRing::new(config.api_keys.clone()),
+            quota_reset: config.api_quota_reset,
+            base_url: config.api_base_url.trim_end_matches('/').to_string(),
+            path_template: config.path_template.clone(),
+            tier: config.openweather_tier,
+            geocoding_path_template: config.geocoding_path_template.clone(),
+            geocoded: Mutex::new(
+                config
+                    .located
+                    .iter()
+                    .map(|location| (location.query(), location.place()))
+                    .collect(),
+            ),
+            onecall_path_template: onecall_template(&config.onecall_path_template, config.collect_minutely_precip),
+            onecall_v25_path_template: onecall_template(DEFAULT_ONECALL_V25_PATH_TEMPLATE, config.collect_minutely_precip),
+            onecall_fallback_to_v25: config.onecall_fallback_to_v25,
+            onecall_use_v25: AtomicBool::new(false),
+            units: config.units,
+            resolve_timezone_name: config.resolve_timezone_name,
+            canonical_weather_main: config.canonical_weather_main,
+            collect_uv_index: config.collect_uv_index,
+            collect_minutely_precip: config.collect_minutely_precip,
+            record_api_latency: config.record_api_latency,
+            retry_on_parse_error: config.retry_on_parse_error,
+            max_response_bytes: config.max_response_bytes,
+            strict_parsing: config.strict_parsing,
+            reported_drift: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Substitutes `{name}` placeholders (URL-encoded), `{api_key}` and
+    /// `{units}` into a path template and prefixes the base URL.
+    fn render_url(&self, template: &str, api_key: &str, params: &[(&str, &str)]) -> String {
+        let mut path = template
+            .replace("{api_key}", &urlencoding::encode(api_key))
+            .replace("{units}", self.units.name());
+        for (name, value) in params {
+            path = path.replace(&format!("{{{}}}", name), &urlencoding::encode(value));
+        }
+
+        if path.starts_with('/') {
+            format!("{}{}", self.base_url, path)
+        } else {
+            format!("{}/{}", self.base_url, path)
+        }
+    }
+
+    /// Truncates `body` for logging and strips the API keys from it.
+    fn body_snippet(&self, body: &str) -> String {
+        let mut snippet: String = body.chars().take(BODY_SNIPPET_CHARS).collect();
+        for key in self.api_keys.keys() {
+            snippet = snippet.replace(key.as_str(), "****");
+        }
+        if body.chars().count() > BODY_SNIPPET_CHARS {
+            format!("{}…", snippet)
+        } else {
+            snippet
+        }
+    }
+
+    /// Sends one request for `city` and reports the status and server clock
+    /// without interpreting the body.
+    pub async fn probe(&self, city: &str) -> Result<ProbeResult> {
+        let api_key = match self.api_keys.current() {
+            Ok((_, key)) => key,
+            Err(_) => self.api_keys.keys().first().map(String::as_str).unwrap_or_default(),
+        };
+        let response = self
+            .request(&self.render_url(&self.path_template, api_key, &[("city", city)]))?
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("Failed to send request to OpenWeatherMap API")?;
 
-        Self { client, api_key }
+        let server_date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| chrono::DateTime::parse_from_rfc2822(value).ok())
+            .map(|date| date.with_timezone(&chrono::Utc));
+
+        Ok(ProbeResult {
+            status: response.status(),
+            server_date,
+        })
     }
 
     pub async fn fetch_weather(&self, city: &str) -> Result<WeatherData> {
-        let url = format!(
-            "https://api.openweathermap.org/data/2.5/weather?q={}&appid={}&units=metric",
-            city, self.api_key
-        );
+        correlation::scope(async {
+            match self.fetch_weather_once(city).await {
+                Err(e) if self.retry_on_parse_error && e.downcast_ref::<FetchError>().is_some_and(FetchError::is_truncated) => {
+                    log::warn!("⚠️  {}; response looks truncated, retrying once", e);
+                    self.fetch_weather_once(city).await
+                }
+                result => result,
+            }
+        })
+        .await
+    }
 
+    async fn fetch_weather_once(&self, city: &str) -> Result<WeatherData> {
         log::info!("🌤️  Fetching weather data for {} from OpenWeatherMap", city);
 
-        let response = self.client
-            .get(&url)
+        let weather_data = match self.tier {
+            ApiTier::Free => self.fetch_current_weather(city).await?,
+            ApiTier::OneCall => self.fetch_onecall_weather(city).await?,
+        };
+
+        log::info!(
+            "✅ Successfully fetched weather for {}: {:.1}{}, {} ({})",
+            weather_data.city.as_deref().unwrap_or("Unknown"),
+            weather_data.temperature,
+            self.units.temperature_symbol(),
+            weather_data.weather_main.as_deref().unwrap_or("Unknown"),
+            weather_data.weather_description.as_deref().unwrap_or("Unknown")
+        );
+
+        Ok(weather_data)
+    }
+
+    /// Current weather 2.5, enriched from One Call when enabled.
+    async fn fetch_current_weather(&self, city: &str) -> Result<WeatherData> {
+        let started = Instant::now();
+        let api_response: ApiResponse = self
+            .get_with_key(&self.path_template, &[("city", city)], Some(&schema_drift::CURRENT_WEATHER))
+            .await?;
+        let latency = started.elapsed();
+
+        if api_response.cod != 200 {
+            return Err(FetchError::ErrorCode { cod: api_response.cod }.into());
+        }
+
+        let mut weather_data = self.to_record(&api_response);
+        if self.record_api_latency {
+            weather_data.api_latency_ms = Some(latency_ms(latency));
+        }
+
+        if self.collect_uv_index || self.collect_minutely_precip {
+            match self.fetch_onecall(api_response.coord.lat, api_response.coord.lon).await {
+                Ok(onecall) => {
+                    if self.collect_uv_index {
+                        weather_data.uv_index = onecall.current.uvi;
+                    }
+                    if self.collect_minutely_precip {
+                        weather_data.minutes_to_precip = onecall.minutes_to_precip();
+                    }
+                }
+                Err(e) => log::warn!("⚠️  Failed to fetch One Call data for {}: {:#}", city, e),
+            }
+        }
+
+        Ok(weather_data)
+    }
+
+    /// One Call's `current` block at the city's geocoded coordinates, which
+    /// brings the UV index and precipitation nowcast with it.
+    async fn fetch_onecall_weather(&self, city: &str) -> Result<WeatherData> {
+        let place = self.geocode(city).await?;
+
+        let started = Instant::now();
+        let response = self.fetch_onecall(place.lat, place.lon).await?;
+        let latency = started.elapsed();
+
+        let mut data = WeatherData::from_onecall_response(&response, &place, self.units)
+            .with_context(|| format!("One Call response for {} has no current conditions", city))?;
+        if self.resolve_timezone_name {
+            data.timezone_name = place
+                .country
+                .as_deref()
+                .zip(response.timezone_offset)
+                .and_then(|(country, offset)| timezone::resolve(country, place.lon, offset))
+                .map(str::to_string);
+        }
+        if self.canonical_weather_main {
+            data.weather_main = data.condition().map(|condition| condition.to_string());
+        }
+        if self.record_api_latency {
+            data.api_latency_ms = Some(latency_ms(latency));
+        }
+        Ok(data)
+    }
+
+    /// Coordinates of `city` from the geocoding API, cached for the run. The
+    /// location `AUTO_LOCATE` detected starts out cached.
+    async fn geocode(&self, city: &str) -> Result<GeocodedCity> {
+        if let Some(place) = self.geocoded.lock().unwrap_or_else(|e| e.into_inner()).get(city) {
+            return Ok(place.clone());
+        }
+
+        let matches: Vec<GeocodedCity> = self
+            .get_with_key(&self.geocoding_path_template, &[("city", city)], None)
+            .await
+            .with_context(|| format!("Failed to geocode {}", city))?;
+        let place = matches
+            .into_iter()
+            .next()
+            .with_context(|| format!("OpenWeatherMap geocoding found no place named '{}'", city))?;
+        log::info!("📍 Geocoded {} to {} ({:.4}, {:.4})", city, place.name, place.lat, place.lon);
+
+        self.geocoded.lock().unwrap_or_else(|e| e.into_inner()).insert(city.to_string(), place.clone());
+        Ok(place)
+    }
+
+    /// Builds the stored record for a response, resolving the timezone name
+    /// from the country, longitude and offset when enabled.
+    fn to_record(&self, response: &ApiResponse) -> WeatherData {
+        let mut data = WeatherData::from_api_response(response, self.units);
+        if self.resolve_timezone_name {
+            data.timezone_name = response
+                .sys
+                .country
+                .as_deref()
+                .and_then(|country| timezone::resolve(country, response.coord.lon, response.timezone))
+                .map(str::to_string);
+        }
+        if self.canonical_weather_main {
+            data.weather_main = data.condition().map(|condition| condition.to_string());
+        }
+        data
+    }
+
+    /// Requests the One Call API for the UV index and precipitation nowcast,
+    /// switching to One Call 2.5 for the rest of the run when enabled and the
+    /// key has no 3.0 subscription.
+    pub async fn fetch_onecall(&self, lat: f64, lon: f64) -> Result<OneCallResponse> {
+        correlation::scope(self.fetch_onecall_once(lat, lon)).await
+    }
+
+    async fn fetch_onecall_once(&self, lat: f64, lon: f64) -> Result<OneCallResponse> {
+        let params = [("lat", lat.to_string()), ("lon", lon.to_string())];
+        let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+        if !self.onecall_use_v25.load(Ordering::Relaxed) {
+            let result = self.get_with_key::<OneCallResponse>(&self.onecall_path_template, &params, None).await;
+            match result {
+                Err(e) if self.onecall_fallback_to_v25
+                    && matches!(e.downcast_ref::<FetchError>(), Some(FetchError::OneCallNotSubscribed)) =>
+                {
+                    log::warn!("⚠️  {}; falling back to One Call 2.5 (ONECALL_FALLBACK_TO_V25)", e);
+                    self.onecall_use_v25.store(true, Ordering::Relaxed);
+                }
+                result => return result,
+            }
+        }
+
+        self.get_with_key(&self.onecall_v25_path_template, &params, None)
+            .await
+            .context("One Call 2.5 fallback failed")
+    }
+
+    /// Requests `template` with the active API key, rotating to the next key
+    /// whenever one reports its quota as exceeded or is rejected. Only when
+    /// every key has been rejected is the result an `InvalidApiKey` error.
+    /// `shape` is the documented response shape checked under
+    /// `STRICT_PARSING`.
+    async fn get_with_key<T: DeserializeOwned>(
+        &self,
+        template: &str,
+        params: &[(&str, &str)],
+        shape: Option<&Shape>,
+    ) -> Result<T> {
+        loop {
+            let (index, api_key) = match self.api_keys.current() {
+                Ok(key) => key,
+                Err(NoUsableKey::Exhausted(retry_in)) => return Err(FetchError::KeysExhausted { retry_in }.into()),
+                Err(NoUsableKey::AllRejected) => {
+                    return Err(FetchError::InvalidApiKey {
+                        provider: "OpenWeatherMap API",
+                    }
+                    .into())
+                }
+            };
+
+            let result = self.get_json(&self.render_url(template, api_key, params), shape).await;
+            match result.as_ref().err().and_then(|e| e.downcast_ref::<FetchError>()) {
+                Some(FetchError::QuotaExceeded { retry_after, .. }) => {
+                    let cooldown = retry_after.unwrap_or(self.quota_reset);
+                    log::warn!(
+                        "⚠️  API key #{} is over quota; resting it for {}s",
+                        index + 1,
+                        cooldown.as_secs()
+                    );
+                    self.api_keys.mark_exhausted(index, cooldown);
+                }
+                Some(FetchError::InvalidApiKey { .. }) => {
+                    log::error!(
+                        "❌ API key #{} of {} was rejected; not using it again until restart",
+                        index + 1,
+                        self.api_keys.keys().len()
+                    );
+                    self.api_keys.mark_rejected(index);
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Builds a GET for `url`, signed when a signing secret is configured
+    /// and carrying the current call's correlation id.
+    fn request(&self, url: &str) -> Result<reqwest::RequestBuilder> {
+        let parsed = reqwest::Url::parse(url).context("Invalid weather API URL")?;
+        let mut request = self.client.get(parsed.clone());
+        if let (Some(header), Some(id)) = (&self.correlation_id_header, correlation::current()) {
+            request = request.header(header.as_str(), id);
+        }
+        Ok(match &self.signer {
+            Some(signer) => signer.sign(request, &parsed),
+            None => request,
+        })
+    }
+
+    /// GETs `url` and deserializes a successful JSON body, keeping a redacted
+    /// snippet of the body when it doesn't match `T`.
+    async fn get_json<T: DeserializeOwned>(&self, url: &str, shape: Option<&Shape>) -> Result<T> {
+        let path = reqwest::Url::parse(url).map(|url| url.path().to_string()).unwrap_or_default();
+        log::debug!("➡️  GET {}", path);
+        let started = Instant::now();
+        let response = self
+            .request(url)?
             .send()
             .await
+            .map_err(reqwest::Error::without_url)
             .context("Failed to send request to OpenWeatherMap API")?;
+        log::debug!("⬅️  {} from {} in {}ms", response.status(), path, latency_ms(started.elapsed()));
 
         if !response.status().is_success() {
             let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "OpenWeatherMap API returned {}: {}",
+            let retry_after = retry_after(&response);
+            let error_text = read_limited(response, self.max_response_bytes).await.unwrap_or_default();
+            return Err(status_error(
+                "OpenWeatherMap API",
                 status,
-                error_text
+                retry_after,
+                &self.body_snippet(&error_text),
             ));
         }
 
-        let api_response: ApiResponse = response
-            .json()
-            .await
-            .context("Failed to parse OpenWeatherMap API response")?;
+        let body = read_limited(response, self.max_response_bytes).await?;
+        if let Some(shape) = shape.filter(|_| self.strict_parsing) {
+            self.report_drift(&body, shape);
+        }
 
-        if api_response.cod != 200 {
-            return Err(anyhow::anyhow!(
-                "OpenWeatherMap API returned error code: {}",
-                api_response.cod
-            ));
+        serde_json::from_str(&body).map_err(|source| {
+            let snippet = self.body_snippet(&body);
+            log::debug!("Unparseable OpenWeatherMap response body: {}", snippet);
+            FetchError::Parse { source, snippet }.into()
+        })
+    }
+
+    /// Logs each difference between `body` and `shape` the first time it is
+    /// seen. Checked before parsing, so a dropped field is named even when
+    /// it makes the parse fail.
+    fn report_drift(&self, body: &str, shape: &Shape) {
+        let Ok(value) = serde_json::from_str(body) else {
+            return;
+        };
+        let mut reported = self.reported_drift.lock().unwrap_or_else(|e| e.into_inner());
+        for difference in schema_drift::drift(&value, shape) {
+            if reported.insert(difference.clone()) {
+                log::warn!("⚠️  OpenWeatherMap response shape changed: {}", difference);
+            }
         }
+    }
+}
 
-        let weather_data = WeatherData::from_api_response(&api_response);
+/// Reads the body as text, refusing to buffer more than `limit` bytes
+/// whether or not `Content-Length` is declared.
+pub(crate) async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<String> {
+    if response.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(FetchError::ResponseTooLarge { limit }.into());
+    }
 
-        log::info!(
-            "✅ Successfully fetched weather for {}: {:.1}°C, {} ({})",
-            weather_data.city.as_deref().unwrap_or("Unknown"),
-            weather_data.temperature,
-            weather_data.weather_main.as_deref().unwrap_or("Unknown"),
-            weather_data.weather_description.as_deref().unwrap_or("Unknown")
-        );
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(reqwest::Error::without_url)
+        .context("Failed to read weather API response")?
+    {
+        if body.len() + chunk.len() > limit {
+            return Err(FetchError::ResponseTooLarge { limit }.into());
+        }
+        body.extend_from_slice(&chunk);
+    }
 
-        Ok(weather_data)
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Which OpenWeatherMap endpoint observations come from (`OPENWEATHER_TIER`).
+/// The tiers return different fields, listed by [`ApiTier::unavailable_fields`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiTier {
+    /// Current weather 2.5 (`/data/2.5/weather`), available to every key.
+    /// One Call is only requested for `COLLECT_UV_INDEX` and
+    /// `COLLECT_MINUTELY_PRECIP`.
+    #[default]
+    Free,
+    /// One Call 3.0 (`/data/3.0/onecall`), which needs a "One Call by Call"
+    /// subscription. Cities are geocoded once, then each fetch is a single
+    /// One Call request that includes the UV index and nowcast.
+    OneCall,
+}
+
+impl ApiTier {
+    pub fn name(self) -> &'static str {
+        match self {
+            ApiTier::Free => "free",
+            ApiTier::OneCall => "onecall",
+        }
+    }
+
+    /// Endpoint observations are read from.
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            ApiTier::Free => "current weather 2.5",
+            ApiTier::OneCall => "One Call 3.0",
+        }
+    }
+
+    /// Columns this tier leaves empty under `config`.
+    pub fn unavailable_fields(self, config: &AppConfig) -> Vec<&'static str> {
+        match self {
+            ApiTier::Free => {
+                let mut fields = Vec::new();
+                if !config.collect_uv_index {
+                    fields.push("uv_index");
+                }
+                if !config.collect_minutely_precip {
+                    fields.push("minutes_to_precip");
+                }
+                fields
+            }
+            ApiTier::OneCall => {
+                let mut fields = vec![
+                    "sea_level_pressure",
+                    "ground_level_pressure",
+                    "station_base",
+                    "station_id",
+                    "station_type",
+                ];
+                if !config.collect_minutely_precip {
+                    fields.push("minutes_to_precip");
+                }
+                fields
+            }
+        }
+    }
+}
+
+impl fmt::Display for ApiTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// IP version used to reach the weather APIs (`HTTP_IP_VERSION`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    /// Dual-stack: whatever the resolver returns, with happy eyeballs.
+    #[default]
+    Any,
+    /// IPv4 only, for hosts whose AAAA records resolve but IPv6 egress is broken.
+    Ipv4,
+    /// IPv6 only.
+    Ipv6,
+}
+
+impl IpVersion {
+    /// Unspecified local address of this family; binding to it makes the
+    /// connector skip resolved addresses of the other family.
+    fn local_address(self) -> Option<IpAddr> {
+        match self {
+            IpVersion::Any => None,
+            IpVersion::Ipv4 => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            IpVersion::Ipv6 => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        }
     }
 }
 
+/// HTTP client shared by the provider services, configured from the
+/// `HTTP_*` settings.
+pub(crate) fn http_client(config: &AppConfig) -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("WeatherETL/1.0")
+        .redirect(redirect_policy(config.max_redirects, config.allow_cross_host_redirects))
+        .danger_accept_invalid_certs(config.insecure_skip_tls_verify)
+        .local_address(config.ip_version.local_address())
+        .gzip(config.http_compression)
+        .brotli(config.http_compression)
+        .deflate(config.http_compression)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// Follows at most `max_redirects` hops, refusing to leave the original host
+/// unless explicitly allowed, and logs every redirect that is followed.
+pub(crate) fn redirect_policy(max_redirects: usize, allow_cross_host: bool) -> redirect::Policy {
+    redirect::Policy::custom(move |attempt| {
+        let origin_host = attempt
+            .previous()
+            .first()
+            .and_then(|url| url.host_str())
+            .map(str::to_owned);
+        let target_host = attempt.url().host_str().map(str::to_owned);
+
+        if attempt.previous().len() > max_redirects {
+            return attempt.error(format!("too many redirects (limit {})", max_redirects));
+        }
+
+        if !allow_cross_host && origin_host != target_host {
+            return attempt.error(format!(
+                "refusing cross-host redirect from {} to {}",
+                origin_host.as_deref().unwrap_or("unknown"),
+                target_host.as_deref().unwrap_or("unknown")
+            ));
+        }
+
+        log::info!(
+            "↪️  Following {} redirect to {}://{}{}",
+            attempt.status(),
+            attempt.url().scheme(),
+            target_host.as_deref().unwrap_or("unknown"),
+            attempt.url().path()
+        );
+        attempt.follow()
+    })
+}
@@ -0,0 +1,95 @@
+use crate::config::app_config::AppConfig;
+use crate::models::condition::WeatherCondition;
+use crate::models::units::Units;
+use crate::models::weather::{WeatherApiResponse, WeatherData};
+use crate::services::fetch_error::FetchError;
+use crate::services::weather_service::{http_client, latency_ms, read_limited, retry_after, status_error};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::time::Instant;
+
+/// Client for the WeatherAPI.com current conditions endpoint, used as an
+/// alternative to OpenWeatherMap.
+pub struct WeatherApiService {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    max_response_bytes: usize,
+    units: Units,
+    resolve_timezone_name: bool,
+    canonical_weather_main: bool,
+    record_api_latency: bool,
+}
+
+impl WeatherApiService {
+    pub fn new(config: &AppConfig) -> Self {
+        let client = http_client(config);
+
+        Self {
+            client,
+            api_key: config.weatherapi_key.clone(),
+            base_url: config.weatherapi_base_url.trim_end_matches('/').to_string(),
+            max_response_bytes: config.max_response_bytes,
+            units: config.units,
+            resolve_timezone_name: config.resolve_timezone_name,
+            canonical_weather_main: config.canonical_weather_main,
+            record_api_latency: config.record_api_latency,
+        }
+    }
+
+    pub async fn fetch_weather(&self, city: &str) -> Result<WeatherData> {
+        log::info!("🌤️  Fetching weather data for {} from WeatherAPI.com", city);
+
+        let url = format!(
+            "{}/v1/current.json?key={}&q={}&aqi=no",
+            self.base_url,
+            urlencoding::encode(&self.api_key),
+            urlencoding::encode(city)
+        );
+
+        let started = Instant::now();
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)
+            .context("Failed to send request to WeatherAPI.com")?;
+
+        let status = response.status();
+        let retry_after = retry_after(&response);
+        let body = read_limited(response, self.max_response_bytes).await?;
+        let latency = started.elapsed();
+        let body = body.replace(&self.api_key, "****");
+
+        if !status.is_success() {
+            return Err(status_error("WeatherAPI.com", status, retry_after, &body));
+        }
+
+        let api_response: WeatherApiResponse = serde_json::from_str(&body).map_err(|source| FetchError::Parse {
+            source,
+            snippet: body.chars().take(512).collect(),
+        })?;
+
+        let mut weather_data = WeatherData::from_weatherapi_response(&api_response, self.units);
+        if !self.resolve_timezone_name {
+            weather_data.timezone_name = None;
+        }
+        if self.canonical_weather_main {
+            let condition = &api_response.current.condition;
+            weather_data.weather_main = Some(WeatherCondition::from_weatherapi(condition.code, &condition.text).to_string());
+        }
+        if self.record_api_latency {
+            weather_data.api_latency_ms = Some(latency_ms(latency));
+        }
+
+        log::info!(
+            "✅ Successfully fetched weather for {} from WeatherAPI.com: {:.1}{}, {}",
+            weather_data.city.as_deref().unwrap_or("Unknown"),
+            weather_data.temperature,
+            self.units.temperature_symbol(),
+            weather_data.weather_main.as_deref().unwrap_or("Unknown")
+        );
+
+        Ok(weather_data)
+    }
+}
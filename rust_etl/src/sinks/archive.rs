@@ -0,0 +1,224 @@
+use crate::config::app_config::AppConfig;
+use crate::models::weather::WeatherData;
+use crate::sinks::{SinkFuture, WeatherSink};
+use anyhow::{Context, Result};
+use arrow_array::{ArrayRef, Float64Array, Int32Array, Int64Array, RecordBatch, StringArray};
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path;
+use object_store::{ObjectStore, PutPayload};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Archives stored observations as Parquet files in S3-compatible object
+/// storage. Rows are buffered and uploaded once `flush_rows` have
+/// accumulated or the oldest has waited `flush_interval` (checked as
+/// observations arrive), and on shutdown. Each upload writes one file per UTC
+/// date and city, under `<prefix>/date=YYYY-MM-DD/city=<city>/`; rows whose
+/// upload failed stay buffered for the next one.
+pub struct ArchiveSink {
+    store: Arc<dyn ObjectStore>,
+    prefix: Vec<String>,
+    flush_rows: usize,
+    flush_interval: Duration,
+    buffer: Mutex<Buffer>,
+}
+
+/// Rows waiting for upload, and when the oldest of them arrived.
+#[derive(Default)]
+struct Buffer {
+    rows: Vec<WeatherData>,
+    since: Option<Instant>,
+}
+
+impl Buffer {
+    fn push(&mut self, row: WeatherData) {
+        self.since.get_or_insert_with(Instant::now);
+        self.rows.push(row);
+    }
+
+    fn take(&mut self) -> Vec<WeatherData> {
+        self.since = None;
+        std::mem::take(&mut self.rows)
+    }
+}
+
+impl ArchiveSink {
+    /// Archives to `bucket` with the `ARCHIVE_*` settings. Nothing is sent
+    /// until the first upload, so an unreachable store doesn't stop the
+    /// service from starting.
+    pub fn connect(bucket: &str, config: &AppConfig) -> Result<Self> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .with_region(&config.archive_region);
+        if let Some(endpoint) = &config.archive_endpoint {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        if let (Some(id), Some(secret)) = (&config.archive_access_key_id, &config.archive_secret_access_key) {
+            builder = builder.with_access_key_id(id).with_secret_access_key(secret);
+        }
+        let store = builder
+            .build()
+            .with_context(|| format!("Failed to configure archive bucket {}", bucket))?;
+
+        Ok(Self::new(
+            Arc::new(store),
+            &config.archive_prefix,
+            config.archive_flush_rows,
+            config.archive_flush_interval,
+        ))
+    }
+
+    /// Archives to `store`, e.g. an in-memory one in tests. A zero
+    /// `flush_interval` uploads by row count only.
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: &str, flush_rows: usize, flush_interval: Duration) -> Self {
+        Self {
+            store,
+            prefix: prefix.split('/').filter(|part| !part.is_empty()).map(str::to_string).collect(),
+            flush_rows: flush_rows.max(1),
+            flush_interval,
+            buffer: Mutex::new(Buffer::default()),
+        }
+    }
+
+    /// Uploads `rows`, one file per partition. Rows of a partition whose
+    /// upload failed go back into the buffer.
+    async fn upload(&self, rows: Vec<WeatherData>) -> Result<()> {
+        let mut partitions: BTreeMap<(String, String), Vec<WeatherData>> = BTreeMap::new();
+        for row in rows {
+            partitions.entry(partition(&row)).or_default().push(row);
+        }
+
+        let file = format!("{}-{:08x}.parquet", chrono::Utc::now().timestamp_millis(), rand::random::<u32>());
+        let mut failure = None;
+        for ((date, city), rows) in partitions {
+            let parts = self.prefix.iter().cloned().chain([format!("date={}", date), format!("city={}", city), file.clone()]);
+            let path = Path::from_iter(parts);
+            match self.put(&path, &rows).await {
+                Ok(()) => log::debug!("Archived {} row(s) to {}", rows.len(), path),
+                Err(e) => {
+                    let mut buffer = self.buffer.lock().await;
+                    for row in rows {
+                        buffer.push(row);
+                    }
+                    failure.get_or_insert(e);
+                }
+            }
+        }
+
+        match failure {
+            Some(e) => Err(e.context("Failed to upload to the archive; the rows are kept for the next upload")),
+            None => Ok(()),
+        }
+    }
+
+    async fn put(&self, path: &Path, rows: &[WeatherData]) -> Result<()> {
+        let batch = record_batch(rows)?;
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), Some(properties))?;
+        writer.write(&batch)?;
+        let bytes = writer.into_inner().context("Failed to encode Parquet file")?;
+
+        self.store
+            .put(path, PutPayload::from(bytes))
+            .await
+            .with_context(|| format!("Failed to upload {}", path))?;
+        Ok(())
+    }
+}
+
+/// UTC date of the observation and its city.
+fn partition(row: &WeatherData) -> (String, String) {
+    let date = chrono::DateTime::from_timestamp(row.timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    (date, row.city.clone().unwrap_or_else(|| "Unknown".to_string()))
+}
+
+/// The CSV sink's columns, plus `units`.
+fn record_batch(rows: &[WeatherData]) -> Result<RecordBatch> {
+    let strings = |value: fn(&WeatherData) -> Option<&str>| -> ArrayRef {
+        Arc::new(rows.iter().map(value).collect::<StringArray>())
+    };
+    let floats = |value: fn(&WeatherData) -> Option<f64>| -> ArrayRef {
+        Arc::new(rows.iter().map(value).collect::<Float64Array>())
+    };
+    let ints = |value: fn(&WeatherData) -> Option<i32>| -> ArrayRef {
+        Arc::new(rows.iter().map(value).collect::<Int32Array>())
+    };
+
+    let columns = [
+        ("city", strings(|row| row.city.as_deref())),
+        ("timestamp", Arc::new(rows.iter().map(|row| Some(row.timestamp)).collect::<Int64Array>()) as ArrayRef),
+        ("temperature", floats(|row| Some(row.temperature))),
+        ("feels_like", floats(|row| row.feels_like)),
+        ("humidity", ints(|row| Some(row.humidity))),
+        ("pressure", ints(|row| row.pressure)),
+        ("sea_level_pressure", ints(|row| row.sea_level_pressure)),
+        ("ground_level_pressure", ints(|row| row.ground_level_pressure)),
+        ("wind_speed", floats(|row| Some(row.wind_speed))),
+        ("wind_direction", floats(|row| row.wind_direction)),
+        ("weather_main", strings(|row| row.weather_main.as_deref())),
+        ("weather_description", strings(|row| row.weather_description.as_deref())),
+        ("weather_id", ints(|row| row.weather_id)),
+        ("uv_index", floats(|row| row.uv_index)),
+        ("dew_point", floats(|row| row.dew_point)),
+        ("wind_chill", floats(|row| row.wind_chill)),
+        ("minutes_to_precip", ints(|row| row.minutes_to_precip)),
+        ("temperature_ema", floats(|row| row.temperature_ema)),
+        ("day_high", floats(|row| row.day_high)),
+        ("day_low", floats(|row| row.day_low)),
+        ("comfort_category", strings(|row| row.comfort_category.as_deref())),
+        ("pressure_trend", strings(|row| row.pressure_trend.as_deref())),
+        ("api_latency_ms", ints(|row| row.api_latency_ms)),
+        ("source", strings(|row| row.source.as_deref())),
+        ("location_id", ints(|row| row.location_id)),
+        ("units", strings(|row| row.units.as_deref())),
+    ];
+    // Every column nullable, so files of one archive share a schema
+    RecordBatch::try_from_iter_with_nullable(columns.into_iter().map(|(name, column)| (name, column, true)))
+        .context("Failed to build archive batch")
+}
+
+impl WeatherSink for ArchiveSink {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            // Uploaded outside the lock, so writes keep buffering meanwhile
+            let due = {
+                let mut buffer = self.buffer.lock().await;
+                buffer.push(data.clone());
+                let waited = !self.flush_interval.is_zero()
+                    && buffer.since.is_some_and(|since| since.elapsed() >= self.flush_interval);
+                (buffer.rows.len() >= self.flush_rows || waited).then(|| buffer.take())
+            };
+            match due {
+                Some(rows) => self.upload(rows).await,
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn is_storage(&self) -> bool {
+        true
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            let rows = self.buffer.lock().await.take();
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.upload(rows).await
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use crate::models::weather::WeatherData;
+use crate::services::change_detector::{ChangeDetector, ChangeTolerances};
+use crate::sinks::{SinkFuture, WeatherSink};
+use std::sync::Mutex;
+
+/// Wraps a sink so an observation is only written when it differs from the
+/// last one the sink accepted for the same city by at least the tolerances.
+/// The last values are held in memory, so the first write after a restart
+/// always goes through.
+pub struct ChangeGatedSink<S> {
+    inner: S,
+    published: Mutex<ChangeDetector>,
+}
+
+impl<S: WeatherSink> ChangeGatedSink<S> {
+    pub fn new(inner: S, tolerances: ChangeTolerances) -> Self {
+        Self {
+            inner,
+            published: Mutex::new(ChangeDetector::new(tolerances)),
+        }
+    }
+
+    fn published(&self) -> std::sync::MutexGuard<'_, ChangeDetector> {
+        self.published.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<S: WeatherSink> WeatherSink for ChangeGatedSink<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            if !self.published().has_changed(data) {
+                log::debug!(
+                    "Not publishing unchanged {} to {} sink",
+                    data.city.as_deref().unwrap_or_default(),
+                    self.inner.name()
+                );
+                return Ok(());
+            }
+            self.inner.write(data).await?;
+            self.published().record(data);
+            Ok(())
+        })
+    }
+
+    fn is_required(&self) -> bool {
+        self.inner.is_required()
+    }
+
+    fn is_storage(&self) -> bool {
+        self.inner.is_storage()
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        self.inner.close()
+    }
+}
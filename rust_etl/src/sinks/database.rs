@@ -0,0 +1,78 @@
+use crate::models::weather::WeatherData;
+use crate::services::database::DatabaseService;
+use crate::services::locations::{LocationIds, LocationResolver};
+use crate::sinks::{SinkFuture, WeatherSink};
+use anyhow::Result;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Inserts every observation into an additional PostgreSQL database, e.g. a
+/// central analytics copy or the new server during a migration. The target
+/// needs the same schema as the primary database; rows that fail are not
+/// dead-lettered there. `location_id` is resolved against the target's own
+/// `locations` table, since its ids need not match the primary's.
+pub struct DatabaseSink {
+    name: String,
+    database: DatabaseService,
+    required: bool,
+    locations: Mutex<LocationResolver>,
+}
+
+impl DatabaseSink {
+    /// Connects on first write, so an unreachable target doesn't stop the
+    /// service from starting. Each write waits at most `timeout` for a
+    /// connection, which bounds how long an outage delays the cycle. When
+    /// `required`, a failed write keeps the row out of the primary database.
+    /// `locations` is the `LOCATION_IDS` mode.
+    pub fn connect_lazy(database_url: &str, timeout: Duration, required: bool, locations: LocationIds) -> Result<Self> {
+        Ok(Self {
+            name: target_name(database_url),
+            database: DatabaseService::connect_lazy(database_url, timeout)?,
+            required,
+            locations: Mutex::new(LocationResolver::new(locations)),
+        })
+    }
+}
+
+/// `database:<host>/<db>`, so each target is told apart in logs and metric
+/// tags without showing credentials.
+fn target_name(database_url: &str) -> String {
+    match reqwest::Url::parse(database_url) {
+        Ok(url) => format!("database:{}{}", url.host_str().unwrap_or_default(), url.path()),
+        Err(_) => "database".to_string(),
+    }
+}
+
+impl WeatherSink for DatabaseSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let city = data.city.as_deref().unwrap_or("Unknown");
+            let data = WeatherData {
+                location_id: self.locations.lock().await.resolve(&self.database, city).await,
+                ..data.clone()
+            };
+            let row = self.database.insert_weather_data(&data).await?;
+            log::debug!("Stored {} in {} (id {})", city, self.name, row.id);
+            Ok(())
+        })
+    }
+
+    fn is_required(&self) -> bool {
+        self.required
+    }
+
+    fn is_storage(&self) -> bool {
+        true
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            self.database.close().await;
+            Ok(())
+        })
+    }
+}
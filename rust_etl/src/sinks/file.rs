@@ -0,0 +1,87 @@
+use crate::models::weather::WeatherData;
+use crate::sinks::format::Serializer;
+use crate::sinks::{SinkFuture, WeatherSink};
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Appends one line per observation to a file. A header (for formats that
+/// have one) is written only when the file starts out empty.
+pub struct FileSink {
+    path: String,
+    serializer: Box<dyn Serializer>,
+    file: Mutex<File>,
+    needs_header: AtomicBool,
+}
+
+impl FileSink {
+    pub fn open(path: &str, serializer: Box<dyn Serializer>) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open sink file {}", path))?;
+        let is_empty = file
+            .metadata()
+            .with_context(|| format!("Failed to read metadata of sink file {}", path))?
+            .len()
+            == 0;
+
+        Ok(Self {
+            path: path.to_string(),
+            serializer,
+            file: Mutex::new(File::from_std(file)),
+            needs_header: AtomicBool::new(is_empty),
+        })
+    }
+}
+
+impl WeatherSink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let line = self.serializer.serialize(data)?;
+
+            // Checked under the lock and cleared only once written, so a
+            // failed write is retried with its header
+            let mut file = self.file.lock().await;
+            let mut output = String::new();
+            if self.needs_header.load(Ordering::Relaxed) {
+                if let Some(header) = self.serializer.header() {
+                    output.push_str(&header);
+                    output.push('\n');
+                }
+            }
+            output.push_str(&line);
+            output.push('\n');
+
+            file.write_all(output.as_bytes())
+                .await
+                .with_context(|| format!("Failed to write to sink file {}", self.path))?;
+            file.flush()
+                .await
+                .with_context(|| format!("Failed to flush sink file {}", self.path))?;
+            self.needs_header.store(false, Ordering::Relaxed);
+            Ok(())
+        })
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            let mut file = self.file.lock().await;
+            file.flush()
+                .await
+                .with_context(|| format!("Failed to flush sink file {}", self.path))?;
+            file.sync_all()
+                .await
+                .with_context(|| format!("Failed to sync sink file {}", self.path))?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,200 @@
+use crate::models::weather::WeatherData;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Turns one observation into one line of output (without the newline).
+pub trait Serializer: Send + Sync {
+    fn serialize(&self, data: &WeatherData) -> Result<String>;
+
+    /// Line written once before the first record, if the format has one.
+    fn header(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Output formats selectable with `SINK_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    Influx,
+}
+
+impl OutputFormat {
+    pub fn serializer(self) -> Box<dyn Serializer> {
+        match self {
+            OutputFormat::Json => Box::new(JsonSerializer),
+            OutputFormat::Csv => Box::new(CsvSerializer),
+            OutputFormat::Influx => Box::new(InfluxSerializer),
+        }
+    }
+}
+
+/// One JSON object per line.
+pub struct JsonSerializer;
+
+impl Serializer for JsonSerializer {
+    fn serialize(&self, data: &WeatherData) -> Result<String> {
+        serde_json::to_string(data).context("Failed to serialize weather data as JSON")
+    }
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "city",
+    "timestamp",
+    "temperature",
+    "feels_like",
+    "humidity",
+    "pressure",
+    "sea_level_pressure",
+    "ground_level_pressure",
+    "wind_speed",
+    "wind_direction",
+    "weather_main",
+    "weather_description",
+    "weather_id",
+    "uv_index",
+    "dew_point",
+    "wind_chill",
+    "minutes_to_precip",
+    "temperature_ema",
+    "day_high",
+    "day_low",
+    "comfort_category",
+    "pressure_trend",
+    "api_latency_ms",
+    "source",
+    "location_id",
+];
+
+/// RFC 4180 CSV with a header row.
+pub struct CsvSerializer;
+
+impl Serializer for CsvSerializer {
+    fn serialize(&self, data: &WeatherData) -> Result<String> {
+        let fields = [
+            csv_field(data.city.as_deref().unwrap_or_default()),
+            data.timestamp.to_string(),
+            data.temperature.to_string(),
+            optional(data.feels_like),
+            data.humidity.to_string(),
+            optional(data.pressure),
+            optional(data.sea_level_pressure),
+            optional(data.ground_level_pressure),
+            data.wind_speed.to_string(),
+            optional(data.wind_direction),
+            csv_field(data.weather_main.as_deref().unwrap_or_default()),
+            csv_field(data.weather_description.as_deref().unwrap_or_default()),
+            optional(data.weather_id),
+            optional(data.uv_index),
+            optional(data.dew_point),
+            optional(data.wind_chill),
+            optional(data.minutes_to_precip),
+            optional(data.temperature_ema),
+            optional(data.day_high),
+            optional(data.day_low),
+            csv_field(data.comfort_category.as_deref().unwrap_or_default()),
+            csv_field(data.pressure_trend.as_deref().unwrap_or_default()),
+            optional(data.api_latency_ms),
+            csv_field(data.source.as_deref().unwrap_or_default()),
+            optional(data.location_id),
+        ];
+        Ok(fields.join(","))
+    }
+
+    fn header(&self) -> Option<String> {
+        Some(CSV_COLUMNS.join(","))
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// InfluxDB line protocol (`weather,city=...,source=... field=... <ns>`), as
+/// accepted by Telegraf's `influx` data format.
+pub struct InfluxSerializer;
+
+impl Serializer for InfluxSerializer {
+    fn serialize(&self, data: &WeatherData) -> Result<String> {
+        let mut line = String::from("weather");
+        for (key, value) in [("city", &data.city), ("source", &data.source)] {
+            if let Some(value) = value.as_deref().filter(|v| !v.is_empty()) {
+                line.push_str(&format!(",{}={}", key, escape_tag(value)));
+            }
+        }
+
+        let mut fields = vec![
+            format!("temperature={}", data.temperature),
+            format!("humidity={}i", data.humidity),
+            format!("wind_speed={}", data.wind_speed),
+        ];
+        let floats = [
+            ("feels_like", data.feels_like),
+            ("wind_direction", data.wind_direction),
+            ("uv_index", data.uv_index),
+            ("dew_point", data.dew_point),
+            ("wind_chill", data.wind_chill),
+            ("temperature_ema", data.temperature_ema),
+            ("day_high", data.day_high),
+            ("day_low", data.day_low),
+        ];
+        fields.extend(floats.iter().filter_map(|(key, value)| value.map(|v| format!("{}={}", key, v))));
+        if let Some(pressure) = data.pressure {
+            fields.push(format!("pressure={}i", pressure));
+        }
+        if let Some(pressure) = data.sea_level_pressure {
+            fields.push(format!("sea_level_pressure={}i", pressure));
+        }
+        if let Some(pressure) = data.ground_level_pressure {
+            fields.push(format!("ground_level_pressure={}i", pressure));
+        }
+        if let Some(weather_id) = data.weather_id {
+            fields.push(format!("weather_id={}i", weather_id));
+        }
+        if let Some(latency) = data.api_latency_ms {
+            fields.push(format!("api_latency_ms={}i", latency));
+        }
+        if let Some(minutes) = data.minutes_to_precip {
+            fields.push(format!("minutes_to_precip={}i", minutes));
+        }
+        if let Some(location_id) = data.location_id {
+            fields.push(format!("location_id={}i", location_id));
+        }
+        if let Some(main) = &data.weather_main {
+            fields.push(format!("weather_main=\"{}\"", escape_string(main)));
+        }
+        if let Some(description) = &data.weather_description {
+            fields.push(format!("weather_description=\"{}\"", escape_string(description)));
+        }
+        if let Some(comfort) = &data.comfort_category {
+            fields.push(format!("comfort_category=\"{}\"", escape_string(comfort)));
+        }
+        if let Some(trend) = &data.pressure_trend {
+            fields.push(format!("pressure_trend=\"{}\"", escape_string(trend)));
+        }
+
+        line.push(' ');
+        line.push_str(&fields.join(","));
+        line.push_str(&format!(" {}", data.timestamp.saturating_mul(1_000_000_000)));
+        Ok(line)
+    }
+}
+
+fn escape_tag(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+fn escape_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,168 @@
+//! Secondary outputs that receive observations alongside the database, e.g.
+//! for piping into other tools.
+
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod change_gated;
+pub mod database;
+pub mod file;
+pub mod format;
+pub mod retrying;
+pub mod stdout;
+
+use crate::config::app_config::AppConfig;
+use crate::models::weather::WeatherData;
+use anyhow::Result;
+use change_gated::ChangeGatedSink;
+use retrying::RetryingSink;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Kinds of sink `from_config` can build, as named in `PUBLISH_ON_CHANGE`.
+pub const SINK_KINDS: &[&str] = &["stdout", "file", "database"];
+
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// A destination for observations. Methods return boxed futures so sinks can
+/// be stored as `Box<dyn WeatherSink>`.
+pub trait WeatherSink: Send + Sync {
+    /// Short name used in logs and metric tags.
+    fn name(&self) -> &str;
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a>;
+
+    /// Whether a failed write should also keep the observation out of the
+    /// primary database, so the two stay in step.
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    /// Whether the sink stores observations like the primary database, so
+    /// `STORE_EVERY_N`, `STORAGE_RESOLUTION` and `DIFF_ONLY_INSERT` skip it
+    /// too. Other sinks receive every fetched observation.
+    fn is_storage(&self) -> bool {
+        false
+    }
+
+    /// Flushes buffered output and releases connections on shutdown. Writes
+    /// after `close` are not expected.
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl WeatherSink for Box<dyn WeatherSink> {
+    fn name(&self) -> &str {
+        self.as_ref().name()
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        self.as_ref().write(data)
+    }
+
+    fn is_required(&self) -> bool {
+        self.as_ref().is_required()
+    }
+
+    fn is_storage(&self) -> bool {
+        self.as_ref().is_storage()
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        self.as_ref().close()
+    }
+}
+
+/// Writes `data` to every sink concurrently, so a sink that is slow or
+/// retrying doesn't hold up the others. Returns each sink's result.
+pub async fn write_all<'a>(
+    sinks: impl IntoIterator<Item = &'a Box<dyn WeatherSink>>,
+    data: &'a WeatherData,
+) -> Vec<(&'a dyn WeatherSink, Result<()>)> {
+    let writes = sinks.into_iter().map(|sink| async move { (sink.as_ref(), sink.write(data).await) });
+    futures_util::future::join_all(writes).await
+}
+
+/// Writes `data` to the required sinks, then to the others only if every
+/// required write succeeded, so an observation kept out of the primary
+/// database doesn't reach the optional outputs either. Returns the result
+/// of each sink written to.
+pub async fn write_required_first<'a>(
+    sinks: &'a [Box<dyn WeatherSink>],
+    data: &'a WeatherData,
+) -> Vec<(&'a dyn WeatherSink, Result<()>)> {
+    let (required, optional): (Vec<_>, Vec<_>) = sinks.iter().partition(|sink| sink.is_required());
+    let mut results = write_all(required, data).await;
+    if results.iter().all(|(_, result)| result.is_ok()) {
+        results.extend(write_all(optional, data).await);
+    }
+    results
+}
+
+/// Closes every sink in turn, giving each at most `timeout`. Failures are
+/// logged rather than returned so one stuck sink can't block the rest.
+pub async fn close_all(sinks: &[Box<dyn WeatherSink>], timeout: Duration) {
+    for sink in sinks {
+        match tokio::time::timeout(timeout, sink.close()).await {
+            Ok(Ok(())) => log::debug!("Closed {} sink", sink.name()),
+            Ok(Err(e)) => log::warn!("⚠️  Failed to close {} sink: {:#}", sink.name(), e),
+            Err(_) => log::warn!("⚠️  Timed out closing {} sink after {}s", sink.name(), timeout.as_secs()),
+        }
+    }
+}
+
+/// Builds the sinks enabled in `config`.
+pub fn from_config(config: &AppConfig) -> Result<Vec<Box<dyn WeatherSink>>> {
+    let mut sinks: Vec<Box<dyn WeatherSink>> = Vec::new();
+
+    if config.stdout_sink {
+        let sink = stdout::StdoutSink::new(config.sink_format.serializer());
+        sinks.push(gate("stdout", with_retry(sink, config, config.stdout_sink_max_attempts), config));
+    }
+    if let Some(path) = &config.file_sink_path {
+        let sink = file::FileSink::open(path, config.sink_format.serializer())?;
+        sinks.push(gate("file", with_retry(sink, config, config.file_sink_max_attempts), config));
+    }
+    for url in &config.database_sink_urls {
+        let sink = database::DatabaseSink::connect_lazy(
+            url,
+            config.database_sink_timeout,
+            config.database_sink_required,
+            config.location_ids,
+        )?;
+        sinks.push(gate("database", with_retry(sink, config, config.database_sink_max_attempts), config));
+    }
+    // Not retried per write: rows whose upload fails stay buffered instead
+    #[cfg(feature = "archive")]
+    if let Some(bucket) = &config.archive_bucket {
+        sinks.push(Box::new(archive::ArchiveSink::connect(bucket, config)?));
+    }
+    #[cfg(not(feature = "archive"))]
+    if config.archive_bucket.is_some() {
+        log::warn!("⚠️  ARCHIVE_BUCKET is set but this build has no `archive` feature; observations are not archived");
+    }
+
+    Ok(sinks)
+}
+
+/// Boxes `sink`, wrapped in a [`RetryingSink`] when it may make more than one
+/// attempt per write.
+fn with_retry<S: WeatherSink + 'static>(sink: S, config: &AppConfig, max_attempts: u32) -> Box<dyn WeatherSink> {
+    if max_attempts > 1 {
+        Box::new(RetryingSink::new(sink, config.sink_retry_policy(max_attempts)))
+    } else {
+        Box::new(sink)
+    }
+}
+
+/// Wraps `sink` in a [`ChangeGatedSink`] when its kind is listed in
+/// `PUBLISH_ON_CHANGE`. The gate sits outside the retries, so an observation
+/// only counts as published once a write succeeded.
+fn gate(kind: &str, sink: Box<dyn WeatherSink>, config: &AppConfig) -> Box<dyn WeatherSink> {
+    if config.publish_on_change.iter().any(|listed| listed == kind) {
+        Box::new(ChangeGatedSink::new(sink, config.publish_tolerances()))
+    } else {
+        sink
+    }
+}
@@ -0,0 +1,42 @@
+use crate::models::weather::WeatherData;
+use crate::sinks::{SinkFuture, WeatherSink};
+use crate::utils::retry::{retry, RetryPolicy};
+
+/// Wraps any sink so each `write` is retried with backoff under the given
+/// policy. The attempt count is configured per kind of sink (stdout, file,
+/// database), so every database sink shares `DATABASE_SINK_MAX_ATTEMPTS`.
+/// `close` is passed through once.
+pub struct RetryingSink<S> {
+    inner: S,
+    policy: RetryPolicy,
+    label: String,
+}
+
+impl<S: WeatherSink> RetryingSink<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        let label = format!("{} sink write", inner.name());
+        Self { inner, policy, label }
+    }
+}
+
+impl<S: WeatherSink> WeatherSink for RetryingSink<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(retry(&self.policy, &self.label, |_| true, move || self.inner.write(data)))
+    }
+
+    fn is_required(&self) -> bool {
+        self.inner.is_required()
+    }
+
+    fn is_storage(&self) -> bool {
+        self.inner.is_storage()
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        self.inner.close()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::models::weather::WeatherData;
+use crate::sinks::format::Serializer;
+use crate::sinks::{SinkFuture, WeatherSink};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Prints one line per observation to stdout; logs go to stderr, so the
+/// output can be piped straight into another tool.
+pub struct StdoutSink {
+    serializer: Box<dyn Serializer>,
+    header_written: AtomicBool,
+}
+
+impl StdoutSink {
+    pub fn new(serializer: Box<dyn Serializer>) -> Self {
+        Self {
+            serializer,
+            header_written: AtomicBool::new(false),
+        }
+    }
+}
+
+impl WeatherSink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let line = self.serializer.serialize(data)?;
+            let mut stdout = std::io::stdout().lock();
+            if !self.header_written.swap(true, Ordering::Relaxed) {
+                if let Some(header) = self.serializer.header() {
+                    writeln!(stdout, "{}", header)?;
+                }
+            }
+            writeln!(stdout, "{}", line)?;
+            stdout.flush()?;
+            Ok(())
+        })
+    }
+
+    fn close(&self) -> SinkFuture<'_> {
+        Box::pin(async {
+            std::io::stdout().lock().flush()?;
+            Ok(())
+        })
+    }
+}
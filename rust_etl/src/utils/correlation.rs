@@ -0,0 +1,30 @@
+//! Correlation ids: one random id per weather API call, shown in every log
+//! line written while the call runs and sent to the API in
+//! `CORRELATION_ID_HEADER`, so one grep finds a fetch's whole lifecycle.
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CORRELATION_ID: String;
+}
+
+/// A random (version 4) UUID.
+pub fn new_id() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Id of the call the current task is running, if any.
+pub fn current() -> Option<String> {
+    CORRELATION_ID.try_with(String::clone).ok()
+}
+
+/// Runs `call` under a new id, or under the current one when already inside
+/// a call, so nested requests (such as One Call during a fetch) share it.
+pub async fn scope<F: Future>(call: F) -> F::Output {
+    let id = current().unwrap_or_else(new_id);
+    CORRELATION_ID.scope(id, call).await
+}
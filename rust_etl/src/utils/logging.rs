@@ -1,3 +1,4 @@
+use crate::utils::correlation;
 use env_logger::Env;
 use std::io::Write;
 
@@ -13,14 +14,17 @@ pub fn init_logger() {
             let target = record.target();
             let args = record.args();
 
-            writeln!(
-                buf,
-                "[{}] {} {}: {}",
-                timestamp,
-                level,
-                target,
-                args
-            )
+            match correlation::current() {
+                Some(id) => writeln!(buf, "[{}] {} {} [{}]: {}", timestamp, level, target, id, args),
+                None => writeln!(
+                    buf,
+                    "[{}] {} {}: {}",
+                    timestamp,
+                    level,
+                    target,
+                    args
+                ),
+            }
         })
         .init();
 
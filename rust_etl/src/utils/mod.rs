@@ -1,8 +1,64 @@
+pub mod correlation;
 pub mod logging;
+pub mod redact;
+pub mod retry;
+pub mod signing;
+pub mod timezone;
 
-pub fn setup_panic_hook() {
-    std::panic::set_hook(Box::new(|panic_info| {
-        eprintln!("Panic occurred: {:?}", panic_info);
-        std::process::exit(1);
+use serde::{Deserialize, Serialize};
+use std::backtrace::{Backtrace, BacktraceStatus};
+
+/// What the panic hook does after reporting a panic (`PANIC_BEHAVIOR`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanicBehavior {
+    /// Exit the process with status 1.
+    #[default]
+    Exit,
+    /// Only log. A panicking spawned task ends while the rest of the service
+    /// keeps running; a panic on the main task still stops the process.
+    Log,
+}
+
+/// Installs a panic hook that reports the panic through the `log` facade at
+/// error level (falling back to stderr before the logger is set up), then
+/// acts on `behavior`. The backtrace honors `RUST_BACKTRACE`. Calling it
+/// again replaces the previous hook.
+pub fn setup_panic_hook(behavior: PanicBehavior) {
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let payload = panic_info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("<non-string panic payload>");
+        let location = panic_info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+
+        let backtrace = Backtrace::capture();
+        let backtrace = match backtrace.status() {
+            BacktraceStatus::Captured => format!("\n{}", backtrace),
+            _ => " (set RUST_BACKTRACE=1 for a backtrace)".to_string(),
+        };
+
+        if log::log_enabled!(log::Level::Error) {
+            log::error!(
+                "💥 Panic in thread '{}' at {}: {}{}",
+                thread_name,
+                location,
+                message,
+                backtrace
+            );
+        } else {
+            eprintln!("Panic in thread '{}' at {}: {}{}", thread_name, location, message, backtrace);
+        }
+
+        if behavior == PanicBehavior::Exit {
+            std::process::exit(1);
+        }
     }));
-}
\ No newline at end of file
+}
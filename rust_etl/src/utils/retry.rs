@@ -0,0 +1,168 @@
+use anyhow::Result;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+/// How randomness is applied to the exponential backoff delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Jitter {
+    /// Plain exponential backoff.
+    None,
+    /// Uniform in `[0, backoff]`.
+    #[default]
+    Full,
+    /// Half the backoff plus uniform in `[0, backoff / 2]`.
+    Equal,
+    /// Uniform in `[base, previous * 3]`, capped at the maximum delay.
+    Decorrelated,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Jitter,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: Jitter::Full,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Un-jittered delay before retry number `retry` (1-based).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// Delay before retry number `retry`; `previous` is the delay used before
+    /// the last retry (or the base delay) and only matters for
+    /// [`Jitter::Decorrelated`].
+    pub fn next_delay<R: Rng + ?Sized>(&self, retry: u32, previous: Duration, rng: &mut R) -> Duration {
+        let backoff = self.backoff(retry);
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => uniform(rng, Duration::ZERO, backoff),
+            Jitter::Equal => backoff / 2 + uniform(rng, Duration::ZERO, backoff / 2),
+            Jitter::Decorrelated => {
+                let upper = previous.saturating_mul(3).max(self.base_delay);
+                uniform(rng, self.base_delay, upper).min(self.max_delay)
+            }
+        }
+    }
+}
+
+fn uniform<R: Rng + ?Sized>(rng: &mut R, low: Duration, high: Duration) -> Duration {
+    if high <= low {
+        return low;
+    }
+    Duration::from_millis(rng.gen_range(low.as_millis() as u64..=high.as_millis() as u64))
+}
+
+/// Retries shared by several [`retry_within`] calls, such as every fetch in
+/// one collection cycle, so an outage can't multiply into a retry storm.
+#[derive(Debug)]
+pub struct RetryBudget {
+    /// `None` for no limit.
+    remaining: Option<AtomicU32>,
+    exhausted: AtomicBool,
+}
+
+impl RetryBudget {
+    /// A budget of `limit` retries; 0 means unlimited.
+    pub fn new(limit: u32) -> Self {
+        Self {
+            remaining: (limit > 0).then(|| AtomicU32::new(limit)),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::new(0)
+    }
+
+    /// Takes one retry from the budget, or returns `false` once it is spent.
+    pub fn try_take(&self) -> bool {
+        let Some(remaining) = &self.remaining else { return true };
+        let taken = remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| left.checked_sub(1))
+            .is_ok();
+        if !taken {
+            self.exhausted.store(true, Ordering::Relaxed);
+        }
+        taken
+    }
+
+    /// Whether a retry has been refused because the budget was spent.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+}
+
+/// Runs `op` until it succeeds, `should_retry` rejects the error, or the
+/// policy's attempts are used up, sleeping with backoff between attempts.
+pub async fn retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    label: &str,
+    should_retry: impl Fn(&anyhow::Error) -> bool,
+    op: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    retry_within(policy, &RetryBudget::unlimited(), label, should_retry, op).await
+}
+
+/// Like [`retry`], but each retry also takes one from `budget`; once it is
+/// spent the last error is returned without further attempts.
+pub async fn retry_within<T, F, Fut>(
+    policy: &RetryPolicy,
+    budget: &RetryBudget,
+    label: &str,
+    should_retry: impl Fn(&anyhow::Error) -> bool,
+    mut op: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    let mut delay = policy.base_delay;
+
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && should_retry(&e) => {
+                if !budget.try_take() {
+                    log::warn!("⚠️  {} failed: {:#}; retry budget spent, not retrying", label, e);
+                    return Err(e);
+                }
+                delay = policy.next_delay(attempt, delay, &mut rand::thread_rng());
+                log::warn!(
+                    "⚠️  {} failed (attempt {}/{}): {:#}; retrying in {}ms",
+                    label,
+                    attempt,
+                    policy.max_attempts,
+                    e,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Offline resolution of IANA timezone names from what the current weather
+//! response provides: country code, longitude and UTC offset.
+
+/// `(country, zone, longitude, standard offset, daylight offset)`, offsets in
+/// seconds. Countries with one entry are matched on offset alone; for the
+/// others the zone nearest in longitude among those matching the offset wins.
+const ZONES: &[(&str, &str, f64, i32, i32)] = &[
+    ("AE", "Asia/Dubai", 55.3, 14400, 14400),
+    ("AR", "America/Argentina/Buenos_Aires", -58.4, -10800, -10800),
+    ("AT", "Europe/Vienna", 16.4, 3600, 7200),
+    ("AU", "Australia/Perth", 115.9, 28800, 28800),
+    ("AU", "Australia/Darwin", 130.8, 34200, 34200),
+    ("AU", "Australia/Adelaide", 138.6, 34200, 37800),
+    ("AU", "Australia/Sydney", 151.2, 36000, 39600),
+    ("AU", "Australia/Brisbane", 153.0, 36000, 36000),
+    ("BE", "Europe/Brussels", 4.4, 3600, 7200),
+    ("BR", "America/Rio_Branco", -67.8, -18000, -18000),
+    ("BR", "America/Manaus", -60.0, -14400, -14400),
+    ("BR", "America/Sao_Paulo", -46.6, -10800, -10800),
+    ("BR", "America/Noronha", -32.4, -7200, -7200),
+    ("CA", "America/Vancouver", -123.1, -28800, -25200),
+    ("CA", "America/Edmonton", -113.5, -25200, -21600),
+    ("CA", "America/Regina", -104.6, -21600, -21600),
+    ("CA", "America/Winnipeg", -97.1, -21600, -18000),
+    ("CA", "America/Toronto", -79.4, -18000, -14400),
+    ("CA", "America/Halifax", -63.6, -14400, -10800),
+    ("CA", "America/St_Johns", -52.7, -12600, -9000),
+    ("CH", "Europe/Zurich", 8.5, 3600, 7200),
+    ("CL", "America/Santiago", -70.7, -14400, -10800),
+    ("CN", "Asia/Shanghai", 121.5, 28800, 28800),
+    ("CO", "America/Bogota", -74.1, -18000, -18000),
+    ("CZ", "Europe/Prague", 14.4, 3600, 7200),
+    ("DE", "Europe/Berlin", 13.4, 3600, 7200),
+    ("DK", "Europe/Copenhagen", 12.6, 3600, 7200),
+    ("EG", "Africa/Cairo", 31.2, 7200, 10800),
+    ("ES", "Atlantic/Canary", -15.4, 0, 3600),
+    ("ES", "Europe/Madrid", -3.7, 3600, 7200),
+    ("FI", "Europe/Helsinki", 24.9, 7200, 10800),
+    ("FR", "Europe/Paris", 2.4, 3600, 7200),
+    ("GB", "Europe/London", -0.1, 0, 3600),
+    ("GR", "Europe/Athens", 23.7, 7200, 10800),
+    ("HK", "Asia/Hong_Kong", 114.2, 28800, 28800),
+    ("IE", "Europe/Dublin", -6.3, 0, 3600),
+    ("IL", "Asia/Jerusalem", 35.2, 7200, 10800),
+    ("IN", "Asia/Kolkata", 88.4, 19800, 19800),
+    ("IT", "Europe/Rome", 12.5, 3600, 7200),
+    ("JP", "Asia/Tokyo", 139.7, 32400, 32400),
+    ("KE", "Africa/Nairobi", 36.8, 10800, 10800),
+    ("KR", "Asia/Seoul", 127.0, 32400, 32400),
+    ("MX", "America/Tijuana", -117.0, -28800, -25200),
+    ("MX", "America/Hermosillo", -110.9, -25200, -25200),
+    ("MX", "America/Mexico_City", -99.1, -21600, -21600),
+    ("MX", "America/Cancun", -86.8, -18000, -18000),
+    ("NG", "Africa/Lagos", 3.4, 3600, 3600),
+    ("NL", "Europe/Amsterdam", 4.9, 3600, 7200),
+    ("NO", "Europe/Oslo", 10.8, 3600, 7200),
+    ("NZ", "Pacific/Auckland", 174.8, 43200, 46800),
+    ("PE", "America/Lima", -77.0, -18000, -18000),
+    ("PH", "Asia/Manila", 121.0, 28800, 28800),
+    ("PL", "Europe/Warsaw", 21.0, 3600, 7200),
+    ("PT", "Europe/Lisbon", -9.1, 0, 3600),
+    ("RU", "Europe/Kaliningrad", 20.5, 7200, 7200),
+    ("RU", "Europe/Moscow", 37.6, 10800, 10800),
+    ("RU", "Europe/Samara", 50.1, 14400, 14400),
+    ("RU", "Asia/Yekaterinburg", 60.6, 18000, 18000),
+    ("RU", "Asia/Omsk", 73.4, 21600, 21600),
+    ("RU", "Asia/Novosibirsk", 82.9, 25200, 25200),
+    ("RU", "Asia/Irkutsk", 104.3, 28800, 28800),
+    ("RU", "Asia/Yakutsk", 129.7, 32400, 32400),
+    ("RU", "Asia/Vladivostok", 131.9, 36000, 36000),
+    ("RU", "Asia/Magadan", 150.8, 39600, 39600),
+    ("RU", "Asia/Kamchatka", 158.6, 43200, 43200),
+    ("SE", "Europe/Stockholm", 18.1, 3600, 7200),
+    ("SG", "Asia/Singapore", 103.8, 28800, 28800),
+    ("TH", "Asia/Bangkok", 100.5, 25200, 25200),
+    ("TR", "Europe/Istanbul", 29.0, 10800, 10800),
+    ("TW", "Asia/Taipei", 121.6, 28800, 28800),
+    ("UA", "Europe/Kyiv", 30.5, 7200, 10800),
+    ("US", "Pacific/Honolulu", -157.9, -36000, -36000),
+    ("US", "America/Anchorage", -149.9, -32400, -28800),
+    ("US", "America/Los_Angeles", -118.2, -28800, -25200),
+    ("US", "America/Phoenix", -112.1, -25200, -25200),
+    ("US", "America/Denver", -105.0, -25200, -21600),
+    ("US", "America/Chicago", -87.6, -21600, -18000),
+    ("US", "America/New_York", -74.0, -18000, -14400),
+    ("VN", "Asia/Ho_Chi_Minh", 106.7, 25200, 25200),
+    ("ZA", "Africa/Johannesburg", 28.0, 7200, 7200),
+];
+
+/// Resolves the IANA zone for a location in `country` (ISO 3166 alpha-2)
+/// observing `offset` seconds from UTC. Returns `None` for countries not in
+/// the table or offsets none of its zones use.
+pub fn resolve(country: &str, lon: f64, offset: i32) -> Option<&'static str> {
+    ZONES
+        .iter()
+        .filter(|(code, _, _, standard, daylight)| {
+            code.eq_ignore_ascii_case(country) && (offset == *standard || offset == *daylight)
+        })
+        .min_by(|a, b| (a.2 - lon).abs().total_cmp(&(b.2 - lon).abs()))
+        .map(|(_, zone, _, _, _)| *zone)
+}
@@ -0,0 +1,79 @@
+//! Key rotation and 403 classification against a stand-in API that answers
+//! by the `appid` a request carries.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::fetch_error::FetchError;
+use rust_etl::services::provider::{ProviderKind, WeatherProvider};
+use rust_etl::services::weather_service::{self, WeatherService};
+
+mod common;
+use common::{current_weather, MockApi};
+
+const REJECTED: &str = r#"{"cod":401,"message":"Invalid API key. Please see https://openweathermap.org/faq#error401 for more info."}"#;
+
+/// Rejects every key except `good-key`.
+async fn mock_api() -> MockApi {
+    MockApi::start(|request, _| match request.query("appid").as_deref() {
+        Some("good-key") => (200, current_weather("Montreal", 21.5)),
+        _ => (401, REJECTED.to_string()),
+    })
+    .await
+}
+
+fn service(base_url: String, keys: &[&str]) -> WeatherService {
+    WeatherService::new(&AppConfig {
+        api_base_url: base_url,
+        api_keys: keys.iter().map(|key| key.to_string()).collect(),
+        ..AppConfig::default()
+    })
+}
+
+fn keys_used(api: &MockApi) -> Vec<String> {
+    api.received().iter().filter_map(|request| request.query("appid")).collect()
+}
+
+#[tokio::test]
+async fn a_rejected_key_is_rotated_past_and_not_used_again() {
+    let api = mock_api().await;
+    let service = service(api.url.clone(), &["bad-key", "good-key"]);
+
+    assert_eq!(service.fetch_weather("Montreal").await.unwrap().temperature, 21.5);
+    assert_eq!(service.fetch_weather("Montreal").await.unwrap().temperature, 21.5);
+
+    assert_eq!(keys_used(&api), ["bad-key", "good-key", "good-key"]);
+}
+
+#[tokio::test]
+async fn rejection_is_fatal_once_every_key_is_rejected() {
+    let api = mock_api().await;
+    let service = service(api.url.clone(), &["bad-key", "other-bad-key"]);
+
+    let error = service.fetch_weather("Montreal").await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<FetchError>(), Some(FetchError::InvalidApiKey { .. })));
+    assert!(weather_service::is_fatal(&error));
+
+    // Neither key is tried again
+    assert!(service.fetch_weather("Montreal").await.is_err());
+    assert_eq!(keys_used(&api), ["bad-key", "other-bad-key"]);
+}
+
+#[tokio::test]
+async fn a_403_is_quota_only_by_error_code() {
+    let api = MockApi::fixed(403, r#"{"cod":403,"message":"Requested data is outside your subscription limits"}"#).await;
+    let error = service(api.url.clone(), &["test-key"]).fetch_weather("Montreal").await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<FetchError>(), Some(FetchError::Forbidden { .. })), "{:#}", error);
+
+    let quota = r#"{"error":{"code":2007,"message":"API key has exceeded calls per month quota."}}"#;
+    let api = MockApi::fixed(403, quota).await;
+    let provider = WeatherProvider::new(
+        ProviderKind::WeatherApi,
+        &AppConfig {
+            weatherapi_base_url: api.url.clone(),
+            weatherapi_key: "test-key".to_string(),
+            ..AppConfig::default()
+        },
+    )
+    .unwrap();
+    let error = provider.fetch_weather("Montreal").await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<FetchError>(), Some(FetchError::QuotaExceeded { status: 403, .. })), "{:#}", error);
+}
@@ -0,0 +1,146 @@
+//! `OPENWEATHER_TIER=onecall`: observations read from One Call at geocoded
+//! coordinates, checked against a stand-in API that records request paths.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::weather_service::{ApiTier, WeatherService};
+
+mod common;
+use common::MockApi;
+
+fn geocoding() -> serde_json::Value {
+    serde_json::json!([{ "name": "Montreal", "lat": 45.5088, "lon": -73.5878, "country": "CA", "state": "Quebec" }])
+}
+
+fn onecall() -> serde_json::Value {
+    serde_json::json!({
+        "lat": 45.5088,
+        "lon": -73.5878,
+        "timezone": "America/Toronto",
+        "timezone_offset": -14400,
+        "current": {
+            "dt": chrono::Utc::now().timestamp() - 60,
+            "temp": 21.5,
+            "feels_like": 21.0,
+            "pressure": 1015,
+            "humidity": 60,
+            "uvi": 4.2,
+            "wind_speed": 3.6,
+            "wind_deg": 250,
+            "weather": [{ "id": 803, "main": "Clouds", "description": "broken clouds", "icon": "04d" }]
+        },
+        "minutely": [
+            { "dt": 1_792_152_000, "precipitation": 0.0 },
+            { "dt": 1_792_152_600, "precipitation": 0.4 }
+        ]
+    })
+}
+
+/// Answers `/geo/` requests with `places` and everything else with One Call.
+async fn mock_api(places: serde_json::Value) -> MockApi {
+    with_onecall(places, onecall()).await
+}
+
+async fn with_onecall(places: serde_json::Value, onecall: serde_json::Value) -> MockApi {
+    MockApi::start(move |request, _| {
+        let body = if request.path().starts_with("/geo/") { places.to_string() } else { onecall.to_string() };
+        (200, body)
+    })
+    .await
+}
+
+fn paths(api: &MockApi) -> Vec<String> {
+    api.received().into_iter().map(|request| request.target).collect()
+}
+
+fn onecall_tier(base_url: String) -> AppConfig {
+    AppConfig {
+        api_base_url: base_url,
+        api_keys: vec!["test-key".to_string()],
+        openweather_tier: ApiTier::OneCall,
+        ..AppConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn observations_come_from_one_call_at_the_geocoded_coordinates() {
+    let api = mock_api(geocoding()).await;
+    let service = WeatherService::new(&onecall_tier(api.url.clone()));
+
+    let data = service.fetch_weather("Montreal,CA").await.unwrap();
+
+    assert_eq!(data.city.as_deref(), Some("Montreal"));
+    assert_eq!((data.temperature, data.humidity, data.pressure), (21.5, 60, Some(1015)));
+    assert_eq!((data.wind_speed, data.wind_direction), (3.6, Some(250.0)));
+    assert_eq!(data.weather_main.as_deref(), Some("Clouds"));
+    assert_eq!(data.timezone, Some(-14400));
+    // Part of the same response, without COLLECT_UV_INDEX; the stand-in
+    // sends the nowcast even though the request excludes it
+    assert_eq!((data.uv_index, data.minutes_to_precip), (Some(4.2), Some(10)));
+    assert_eq!((data.sea_level_pressure, data.station_id), (None, None));
+
+    let paths = paths(&api);
+    assert!(paths[0].starts_with("/geo/1.0/direct?q=Montreal%2CCA&limit=1"), "{:?}", paths);
+    assert!(paths[1].starts_with("/data/3.0/onecall?lat=45.5088&lon=-73.5878"), "{:?}", paths);
+    assert!(paths[1].contains("exclude=minutely,hourly,daily,alerts"), "{:?}", paths);
+}
+
+#[tokio::test]
+async fn the_nowcast_is_requested_only_when_collected() {
+    let api = mock_api(geocoding()).await;
+    let config = AppConfig {
+        collect_minutely_precip: true,
+        ..onecall_tier(api.url.clone())
+    };
+
+    WeatherService::new(&config).fetch_weather("Montreal,CA").await.unwrap();
+
+    let paths = paths(&api);
+    assert!(paths[1].contains("exclude=hourly,daily,alerts&"), "{:?}", paths);
+}
+
+#[tokio::test]
+async fn cities_are_geocoded_once() {
+    let api = mock_api(geocoding()).await;
+    let service = WeatherService::new(&onecall_tier(api.url.clone()));
+
+    service.fetch_weather("Montreal,CA").await.unwrap();
+    service.fetch_weather("Montreal,CA").await.unwrap();
+
+    let paths = paths(&api);
+    assert_eq!(paths.iter().filter(|path| path.starts_with("/geo/")).count(), 1, "{:?}", paths);
+    assert_eq!(paths.len(), 3);
+}
+
+#[tokio::test]
+async fn unknown_cities_are_errors() {
+    let api = mock_api(serde_json::json!([])).await;
+    let service = WeatherService::new(&onecall_tier(api.url.clone()));
+
+    let error = service.fetch_weather("Atlantis").await.unwrap_err();
+
+    assert!(error.to_string().contains("no place named 'Atlantis'"), "{}", error);
+}
+
+#[tokio::test]
+async fn readings_without_a_wind_speed_are_errors() {
+    let mut response = onecall();
+    response["current"].as_object_mut().unwrap().remove("wind_speed");
+    let api = with_onecall(geocoding(), response).await;
+    let service = WeatherService::new(&onecall_tier(api.url.clone()));
+
+    let error = service.fetch_weather("Montreal,CA").await.unwrap_err();
+
+    assert!(error.to_string().contains("no current conditions"), "{}", error);
+}
+
+#[test]
+fn each_tier_lists_the_columns_it_cannot_fill() {
+    let config = AppConfig {
+        collect_uv_index: true,
+        ..AppConfig::default()
+    };
+
+    assert_eq!(ApiTier::Free.unavailable_fields(&config), vec!["minutes_to_precip"]);
+    assert!(ApiTier::OneCall.unavailable_fields(&config).contains(&"ground_level_pressure"));
+    assert!(ApiTier::OneCall.unavailable_fields(&config).contains(&"minutes_to_precip"));
+}
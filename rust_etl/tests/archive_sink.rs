@@ -0,0 +1,84 @@
+//! Archives observations to an in-memory object store. Built only with
+//! `--features archive`.
+#![cfg(feature = "archive")]
+
+use futures_util::TryStreamExt;
+use object_store::memory::InMemory;
+use object_store::ObjectStore;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rust_etl::models::weather::WeatherData;
+use rust_etl::sinks::archive::ArchiveSink;
+use rust_etl::sinks::WeatherSink;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod common;
+use common::observation;
+
+/// Stored file paths, sorted, with the rows in each.
+async fn archived(store: &InMemory) -> Vec<(String, usize)> {
+    let mut files = Vec::new();
+    let listed: Vec<_> = store.list(None).try_collect().await.unwrap();
+    for meta in listed {
+        let bytes = store.get(&meta.location).await.unwrap().bytes().await.unwrap();
+        let rows = ParquetRecordBatchReaderBuilder::try_new(bytes)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        files.push((meta.location.to_string(), rows));
+    }
+    files.sort();
+    files
+}
+
+fn at(city: &str, timestamp: i64) -> WeatherData {
+    WeatherData {
+        timestamp,
+        ..observation(city)
+    }
+}
+
+#[tokio::test]
+async fn rows_are_uploaded_per_date_and_city_once_enough_accumulate() {
+    let store = Arc::new(InMemory::new());
+    let sink = ArchiveSink::new(store.clone(), "archive/weather", 3, Duration::ZERO);
+    // 2026-10-16 and 2026-10-17, UTC
+    let (day, next_day) = (1_792_108_800, 1_792_195_200);
+
+    sink.write(&at("Montreal", day)).await.unwrap();
+    sink.write(&at("Montreal", next_day)).await.unwrap();
+    assert!(archived(&store).await.is_empty());
+    sink.write(&at("Quebec City", day + 60)).await.unwrap();
+
+    let files = archived(&store).await;
+    let partitions: Vec<(&str, usize)> = files
+        .iter()
+        .map(|(path, rows)| (path.rsplit_once('/').unwrap().0, *rows))
+        .collect();
+    assert_eq!(
+        partitions,
+        [
+            ("archive/weather/date=2026-10-16/city=Montreal", 1),
+            ("archive/weather/date=2026-10-16/city=Quebec City", 1),
+            ("archive/weather/date=2026-10-17/city=Montreal", 1),
+        ]
+    );
+    assert!(files.iter().all(|(path, _)| path.ends_with(".parquet")));
+}
+
+#[tokio::test]
+async fn close_uploads_the_remaining_rows() {
+    let store = Arc::new(InMemory::new());
+    let sink = ArchiveSink::new(store.clone(), "weather", 100, Duration::from_secs(3600));
+
+    sink.write(&at("Montreal", 1_792_108_800)).await.unwrap();
+    sink.write(&at("Montreal", 1_792_108_860)).await.unwrap();
+    sink.close().await.unwrap();
+
+    let files = archived(&store).await;
+    assert_eq!(files.len(), 1);
+    assert!(files[0].0.starts_with("weather/date=2026-10-16/city=Montreal/"), "{}", files[0].0);
+    assert_eq!(files[0].1, 2);
+}
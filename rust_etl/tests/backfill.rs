@@ -0,0 +1,107 @@
+//! Needs a PostgreSQL database with `postgres/init.sql` applied; set
+//! `TEST_DATABASE_URL` to run these, otherwise they are skipped.
+
+use rust_etl::cli::backfill::{self, BackfillArgs};
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::models::weather::WeatherData;
+use rust_etl::services::database::DatabaseService;
+
+mod common;
+use common::observation;
+
+/// Stores a cold, windy reading for `city`, with or without its wind chill.
+async fn store_cold_reading(database: &DatabaseService, city: &str, wind_chill: bool) -> WeatherData {
+    let mut data = WeatherData {
+        temperature: -10.0,
+        wind_speed: 5.0,
+        ..observation(city)
+    };
+    data.apply_computed(None, wind_chill);
+    database.insert_weather_data(&data).await.unwrap();
+    data
+}
+
+/// Backfills `city` with `WIND_CHILL` set to `wind_chill` and returns the
+/// stored row.
+async fn backfill(url: &str, database: &DatabaseService, city: &str, wind_chill: bool) -> WeatherData {
+    let config = AppConfig {
+        database_url: url.to_string(),
+        wind_chill,
+        ..AppConfig::default()
+    };
+    let args = BackfillArgs {
+        city: Some(city.to_string()),
+        ..BackfillArgs::default()
+    };
+    backfill::run(&config, &args).await.unwrap();
+    database.get_latest_weather(city).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn wind_chill_is_kept_while_disabled() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+    let city = common::unique_city("Backfill Test");
+    let stored = store_cold_reading(&database, &city, true).await;
+
+    let backfilled = backfill(&url, &database, &city, false).await.wind_chill;
+
+    assert!(stored.wind_chill.is_some());
+    assert_eq!(backfilled, stored.wind_chill);
+}
+
+#[tokio::test]
+async fn wind_chill_is_filled_once_enabled() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+    let city = common::unique_city("Backfill Test");
+    let stored = store_cold_reading(&database, &city, false).await;
+
+    let backfilled = backfill(&url, &database, &city, true).await.wind_chill;
+
+    assert_eq!(stored.wind_chill, None);
+    assert!(backfilled.is_some());
+    assert_eq!(backfilled, stored.wind_chill());
+}
+
+#[tokio::test]
+async fn dew_point_is_filled_for_rows_stored_without_it() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+    let city = common::unique_city("Backfill Test");
+    let data = WeatherData {
+        dew_point: None,
+        ..observation(&city)
+    };
+    database.insert_weather_data(&data).await.unwrap();
+
+    let backfilled = backfill(&url, &database, &city, false).await;
+
+    assert!(backfilled.dew_point.is_some());
+    assert_eq!(backfilled.dew_point, data.dew_point());
+}
+
+#[tokio::test]
+async fn comfort_category_is_kept_while_disabled() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+    let city = common::unique_city("Backfill Test");
+    let mut data = observation(&city);
+    data.apply_computed(AppConfig::default().comfort_thresholds().as_ref(), false);
+    database.insert_weather_data(&data).await.unwrap();
+
+    let config = AppConfig {
+        database_url: url.clone(),
+        comfort_category: false,
+        ..AppConfig::default()
+    };
+    let args = BackfillArgs {
+        city: Some(city.clone()),
+        ..BackfillArgs::default()
+    };
+    backfill::run(&config, &args).await.unwrap();
+    let backfilled = database.get_latest_weather(&city).await.unwrap().unwrap();
+
+    assert!(data.comfort_category.is_some());
+    assert_eq!(backfilled.comfort_category, data.comfort_category);
+}
@@ -0,0 +1,95 @@
+//! `PUBLISH_ON_CHANGE`: gated sinks skip observations that haven't moved
+//! beyond the tolerances since the last one they accepted.
+
+use anyhow::anyhow;
+use rust_etl::models::weather::WeatherData;
+use rust_etl::services::change_detector::ChangeTolerances;
+use rust_etl::sinks::change_gated::ChangeGatedSink;
+use rust_etl::sinks::{SinkFuture, WeatherSink};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+/// Records the temperature of every write; fails them while `failing` is set.
+#[derive(Clone, Default)]
+struct Recording {
+    written: Arc<Mutex<Vec<f64>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl Recording {
+    fn written(&self) -> Vec<f64> {
+        self.written.lock().unwrap().clone()
+    }
+}
+
+impl WeatherSink for Recording {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    fn write<'a>(&'a self, data: &'a WeatherData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(anyhow!("unavailable"));
+            }
+            self.written.lock().unwrap().push(data.temperature);
+            Ok(())
+        })
+    }
+}
+
+fn observation(city: &str, temperature: f64) -> WeatherData {
+    WeatherData {
+        city: Some(city.to_string()),
+        temperature,
+        ..common::fixture()
+    }
+}
+
+fn gated(recording: &Recording) -> ChangeGatedSink<Recording> {
+    let tolerances = ChangeTolerances {
+        temperature: 0.5,
+        ..ChangeTolerances::default()
+    };
+    ChangeGatedSink::new(recording.clone(), tolerances)
+}
+
+#[tokio::test]
+async fn unchanged_observations_are_not_published() {
+    let recording = Recording::default();
+    let sink = gated(&recording);
+
+    for temperature in [20.0, 20.2, 20.4, 20.6, 20.7, 21.1] {
+        sink.write(&observation("Oslo", temperature)).await.unwrap();
+    }
+
+    // Compared with the last published value, not the last observation
+    assert_eq!(recording.written(), vec![20.0, 20.6, 21.1]);
+}
+
+#[tokio::test]
+async fn cities_are_compared_separately() {
+    let recording = Recording::default();
+    let sink = gated(&recording);
+
+    sink.write(&observation("Oslo", 20.0)).await.unwrap();
+    sink.write(&observation("Bergen", 20.0)).await.unwrap();
+    sink.write(&observation("Oslo", 20.0)).await.unwrap();
+
+    assert_eq!(recording.written(), vec![20.0, 20.0]);
+}
+
+#[tokio::test]
+async fn failed_writes_are_not_counted_as_published() {
+    let recording = Recording::default();
+    let sink = gated(&recording);
+
+    recording.failing.store(true, Ordering::SeqCst);
+    assert!(sink.write(&observation("Oslo", 20.0)).await.is_err());
+    recording.failing.store(false, Ordering::SeqCst);
+    sink.write(&observation("Oslo", 20.0)).await.unwrap();
+
+    assert_eq!(recording.written(), vec![20.0]);
+}
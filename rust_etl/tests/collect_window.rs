@@ -0,0 +1,48 @@
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::collect_window::{CollectHours, UtcOffset};
+use serde_json::json;
+
+/// 2026-10-16 00:00 UTC.
+const MIDNIGHT_UTC: i64 = 1_792_108_800;
+const EDT: i32 = -4 * 3600;
+
+fn hours(text: &str) -> CollectHours {
+    text.parse().unwrap()
+}
+
+#[test]
+fn window_is_checked_in_local_time() {
+    let window = hours("6-22");
+    // 09:00 UTC is 05:00 in Montreal
+    assert!(!window.contains(MIDNIGHT_UTC + 9 * 3600, EDT));
+    assert!(window.contains(MIDNIGHT_UTC + 10 * 3600, EDT));
+    assert!(window.contains(MIDNIGHT_UTC + 9 * 3600, 0));
+    // 22:00 local is past the end
+    assert!(!window.contains(MIDNIGHT_UTC + 22 * 3600, 0));
+}
+
+#[test]
+fn window_can_wrap_past_midnight() {
+    let window = hours("22-6");
+    assert!(window.contains(MIDNIGHT_UTC + 23 * 3600, 0));
+    assert!(window.contains(MIDNIGHT_UTC + 5 * 3600, 0));
+    assert!(!window.contains(MIDNIGHT_UTC + 12 * 3600, 0));
+    assert!((0..24).all(|hour| hours("0-24").contains(MIDNIGHT_UTC + hour * 3600, 0)));
+}
+
+#[test]
+fn settings_are_validated_at_load() {
+    let mut config = serde_json::to_value(AppConfig::default()).unwrap();
+    config["COLLECT_HOURS"] = json!("7-19");
+    config["COLLECT_HOURS_UTC_OFFSET"] = json!("+05:30");
+    let parsed: AppConfig = serde_json::from_value(config.clone()).unwrap();
+    assert_eq!(parsed.collect_hours, Some(hours("7-19")));
+    assert_eq!(parsed.collect_hours_utc_offset.unwrap().seconds(), 5 * 3600 + 1800);
+
+    for invalid in ["6", "6-6", "25-3", "six-ten"] {
+        config["COLLECT_HOURS"] = json!(invalid);
+        let error = serde_json::from_value::<AppConfig>(config.clone()).unwrap_err();
+        assert!(error.to_string().contains("invalid collection hours"), "{}", error);
+    }
+    assert!("05:00".parse::<UtcOffset>().is_err());
+}
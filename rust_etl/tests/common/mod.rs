@@ -0,0 +1,232 @@
+//! Helpers shared by the integration tests: the `TEST_DATABASE_URL`
+//! database and a stand-in HTTP API. Every test crate compiles its own copy
+//! and uses only part of it.
+#![allow(dead_code)]
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::Rng;
+use rust_etl::models::weather::WeatherData;
+use rust_etl::services::database::{DatabaseService, RowScope};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// `TEST_DATABASE_URL`, or `None` (after saying the test is skipped) when
+/// it isn't set.
+pub fn database_url() -> Option<String> {
+    let url = std::env::var("TEST_DATABASE_URL").ok();
+    if url.is_none() {
+        eprintln!("TEST_DATABASE_URL not set; skipping");
+    }
+    url
+}
+
+/// The test database, or `None` when `TEST_DATABASE_URL` isn't set.
+pub async fn database() -> Option<Arc<DatabaseService>> {
+    let url = database_url()?;
+    Some(Arc::new(DatabaseService::new(&url).await.expect("connect to TEST_DATABASE_URL")))
+}
+
+/// A plain pool on the test database, for queries `DatabaseService` has no
+/// method for.
+pub async fn pool() -> sqlx::PgPool {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL");
+    sqlx::PgPool::connect(&url).await.expect("connect to TEST_DATABASE_URL")
+}
+
+/// A city name no other test run uses, so rows can be counted per test.
+pub fn unique_city(prefix: &str) -> String {
+    format!("{} {:08x}", prefix, rand::thread_rng().gen::<u32>())
+}
+
+/// A plausible observation: overcast Montreal at 12.5 °C, dated now, from
+/// OpenWeatherMap. Override fields with struct update syntax or
+/// `WeatherDataBuilder::from`.
+pub fn fixture() -> WeatherData {
+    WeatherData::builder()
+        .city("Montreal")
+        .temperature(12.5)
+        .feels_like(11.0)
+        .humidity(70)
+        .pressure(1013)
+        .wind(3.2, Some(180.0))
+        .condition(803, "Clouds", "overcast clouds", "04d")
+        .timezone(0)
+        .source("openweathermap")
+        .build()
+}
+
+/// The [`fixture`] observation for `city`.
+pub fn observation(city: &str) -> WeatherData {
+    WeatherData {
+        city: Some(city.to_string()),
+        ..fixture()
+    }
+}
+
+/// Rows stored for `city`.
+pub async fn stored(database: &DatabaseService, city: &str) -> i64 {
+    let scope = RowScope {
+        city: Some(city.to_string()),
+        ..RowScope::default()
+    };
+    database.count_rows(&scope).await.unwrap()
+}
+
+/// An OpenWeatherMap current-weather body for `city`, observed a minute ago.
+pub fn current_weather(city: &str, temperature: f64) -> String {
+    serde_json::json!({
+        "coord": { "lon": -73.59, "lat": 45.51 },
+        "weather": [{ "id": 803, "main": "Clouds", "description": "broken clouds", "icon": "04d" }],
+        "base": "stations",
+        "main": { "temp": temperature, "feels_like": temperature - 0.5, "pressure": 1015, "humidity": 60 },
+        "wind": { "speed": 3.6, "deg": 250 },
+        "clouds": { "all": 75 },
+        "dt": chrono::Utc::now().timestamp() - 60,
+        "sys": { "country": "CA" },
+        "timezone": -14400,
+        "id": 6077243,
+        "name": city,
+        "cod": 200
+    })
+    .to_string()
+}
+
+/// One request received by a [`MockApi`].
+#[derive(Debug, Clone)]
+pub struct Received {
+    /// Path and query, as sent.
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Received {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    /// A decoded query parameter.
+    pub fn query(&self, name: &str) -> Option<String> {
+        let (_, query) = self.target.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .and_then(|value| urlencoding::decode(value).ok())
+            .map(|value| value.into_owned())
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A stand-in HTTP API on a random local port. Each request is recorded and
+/// answered with the `(status, body)` the responder picks for it, given
+/// how many requests came before.
+pub struct MockApi {
+    /// Base URL, e.g. `http://127.0.0.1:41234`.
+    pub url: String,
+    received: Arc<Mutex<Vec<Received>>>,
+}
+
+impl MockApi {
+    pub async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&Received, usize) -> (u16, String) + Send + Sync + 'static,
+    {
+        Self::serve(respond, false).await
+    }
+
+    /// Like [`start`](Self::start), but gzips the body of every response to
+    /// a request that accepts gzip.
+    pub async fn gzipped<F>(respond: F) -> Self
+    where
+        F: Fn(&Received, usize) -> (u16, String) + Send + Sync + 'static,
+    {
+        Self::serve(respond, true).await
+    }
+
+    async fn serve<F>(respond: F, gzip: bool) -> Self
+    where
+        F: Fn(&Received, usize) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&received);
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else { return };
+                let mut reader = BufReader::new(&mut stream);
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line).await;
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).await.unwrap_or(0) == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.push((name.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+
+                let request = Received {
+                    target: request_line.split_whitespace().nth(1).unwrap_or_default().to_string(),
+                    headers,
+                };
+                let index = {
+                    let mut recorded = recorded.lock().unwrap();
+                    recorded.push(request.clone());
+                    recorded.len() - 1
+                };
+                let (status, body) = respond(&request, index);
+                let reason = reqwest::StatusCode::from_u16(status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or("Unknown");
+                let accepts_gzip = request.header("Accept-Encoding").is_some_and(|accepted| accepted.contains("gzip"));
+                let (body, encoding) = if gzip && accepts_gzip {
+                    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                    encoder.write_all(body.as_bytes()).unwrap();
+                    (encoder.finish().unwrap(), "Content-Encoding: gzip\r\n")
+                } else {
+                    (body.into_bytes(), "")
+                };
+                let head = format!(
+                    "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    reason,
+                    encoding,
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            }
+        });
+
+        Self { url, received }
+    }
+
+    /// Answers every request with `status` and `body`.
+    pub async fn fixed(status: u16, body: impl Into<String>) -> Self {
+        let body = body.into();
+        Self::start(move |_, _| (status, body.clone())).await
+    }
+
+    /// Serves `bodies` in order with status 200, repeating the last one.
+    pub async fn sequence(bodies: Vec<String>) -> Self {
+        Self::start(move |_, index| (200, bodies[index.min(bodies.len() - 1)].clone())).await
+    }
+
+    /// Requests received so far, oldest first.
+    pub fn received(&self) -> Vec<Received> {
+        self.received.lock().unwrap().clone()
+    }
+}
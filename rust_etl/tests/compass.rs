@@ -0,0 +1,38 @@
+use rust_etl::models::compass::{wind_compass, Language};
+
+#[test]
+fn reads_the_language_from_a_locale() {
+    assert_eq!(Language::from_locale("fr"), Language::Fr);
+    assert_eq!(Language::from_locale("fr_CA.UTF-8"), Language::Fr);
+    assert_eq!(Language::from_locale(" FR-be@euro "), Language::Fr);
+    assert_eq!(Language::from_locale("en_US.UTF-8"), Language::En);
+    assert_eq!(Language::from_locale("C"), Language::En);
+    assert_eq!(Language::from_locale("de_DE"), Language::En);
+    assert_eq!(Language::from_locale(""), Language::En);
+}
+
+#[test]
+fn serializes_as_its_code_and_accepts_any_locale() {
+    assert_eq!(serde_json::to_value(Language::Fr).unwrap(), "fr");
+    assert_eq!(serde_json::from_value::<Language>("fr_FR.UTF-8".into()).unwrap(), Language::Fr);
+    assert_eq!(serde_json::from_value::<Language>("POSIX".into()).unwrap(), Language::En);
+}
+
+#[test]
+fn maps_degrees_to_sixteen_points() {
+    assert_eq!(wind_compass(0.0, Language::En), "N");
+    assert_eq!(wind_compass(11.0, Language::En), "N");
+    assert_eq!(wind_compass(12.0, Language::En), "NNE");
+    assert_eq!(wind_compass(225.0, Language::En), "SW");
+    assert_eq!(wind_compass(350.0, Language::En), "N");
+    assert_eq!(wind_compass(-90.0, Language::En), "W");
+    assert_eq!(wind_compass(720.0 + 90.0, Language::En), "E");
+}
+
+#[test]
+fn french_uses_o_for_west() {
+    assert_eq!(wind_compass(225.0, Language::Fr), "SO");
+    assert_eq!(wind_compass(270.0, Language::Fr), "O");
+    assert_eq!(wind_compass(292.5, Language::Fr), "ONO");
+    assert_eq!(wind_compass(90.0, Language::Fr), "E");
+}
@@ -0,0 +1,76 @@
+//! Correlation ids sent with weather API calls, checked against a stand-in
+//! API that records each request's headers.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::weather_service::WeatherService;
+use rust_etl::utils::correlation;
+
+mod common;
+use common::{current_weather, MockApi};
+
+/// The `x-correlation-id` header of each request, `None` when absent.
+fn correlation_ids(api: &MockApi) -> Vec<Option<String>> {
+    api.received()
+        .iter()
+        .map(|request| request.header("x-correlation-id").map(str::to_string))
+        .collect()
+}
+
+fn service(base_url: String, header: &str) -> WeatherService {
+    WeatherService::new(&AppConfig {
+        api_base_url: base_url,
+        api_keys: vec!["test-key".to_string()],
+        correlation_id_header: header.to_string(),
+        ..AppConfig::default()
+    })
+}
+
+#[test]
+fn ids_are_version_4_uuids() {
+    let id = correlation::new_id();
+    let groups: Vec<&str> = id.split('-').collect();
+    assert_eq!(groups.iter().map(|group| group.len()).collect::<Vec<_>>(), [8, 4, 4, 4, 12]);
+    assert!(groups[2].starts_with('4'), "{}", id);
+    assert!(matches!(groups[3].chars().next(), Some('8' | '9' | 'a' | 'b')), "{}", id);
+    assert_ne!(id, correlation::new_id());
+}
+
+#[tokio::test]
+async fn nested_calls_share_the_outer_id() {
+    assert_eq!(correlation::current(), None);
+    let (outer, inner) = correlation::scope(async {
+        let outer = correlation::current();
+        let inner = correlation::scope(async { correlation::current() }).await;
+        (outer, inner)
+    })
+    .await;
+    assert!(outer.is_some());
+    assert_eq!(outer, inner);
+}
+
+#[tokio::test]
+async fn each_fetch_sends_one_id_across_its_retry() {
+    let full = current_weather("Montreal", 21.5);
+    let truncated = full[..full.len() / 2].to_string();
+    let api = MockApi::sequence(vec![truncated, full]).await;
+    let service = service(api.url.clone(), "X-Correlation-ID");
+
+    service.fetch_weather("Montreal").await.unwrap();
+    service.fetch_weather("Montreal").await.unwrap();
+
+    let seen = correlation_ids(&api);
+    assert_eq!(seen.len(), 3);
+    assert!(seen.iter().all(Option::is_some), "{:?}", seen);
+    // The truncated response and its retry are one call
+    assert_eq!(seen[0], seen[1]);
+    assert_ne!(seen[1], seen[2]);
+}
+
+#[tokio::test]
+async fn empty_header_name_sends_no_id() {
+    let api = MockApi::sequence(vec![current_weather("Montreal", 21.5)]).await;
+
+    service(api.url.clone(), "").fetch_weather("Montreal").await.unwrap();
+
+    assert_eq!(correlation_ids(&api), [None]);
+}
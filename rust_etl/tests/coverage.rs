@@ -0,0 +1,53 @@
+use rust_etl::services::database::{Coverage, Gap};
+use std::time::Duration;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+#[test]
+fn empty_slots_become_gaps() {
+    // Six hourly slots from 1000, with data in slots 0, 1 and 4
+    let coverage = Coverage::from_slots(1000, 1000 + 6 * 3600, HOUR, &[0, 1, 4]);
+
+    assert_eq!((coverage.expected, coverage.collected), (6, 3));
+    assert_eq!(
+        coverage.gaps,
+        vec![
+            Gap { start: 1000 + 2 * 3600, end: 1000 + 4 * 3600 },
+            Gap { start: 1000 + 5 * 3600, end: 1000 + 6 * 3600 },
+        ]
+    );
+    assert_eq!(coverage.ratio(), 0.5);
+    assert_eq!(coverage.largest_gap(), Some(coverage.gaps[0]));
+}
+
+#[test]
+fn a_partial_last_slot_is_expected_and_its_gap_ends_at_to() {
+    let coverage = Coverage::from_slots(0, 5400, HOUR, &[0]);
+
+    assert_eq!(coverage.expected, 2);
+    assert_eq!(coverage.gaps, vec![Gap { start: 3600, end: 5400 }]);
+}
+
+#[test]
+fn full_and_empty_ranges() {
+    let full = Coverage::from_slots(0, 3 * 3600, HOUR, &[0, 1, 2]);
+    assert!(full.gaps.is_empty());
+    assert_eq!(full.ratio(), 1.0);
+    assert_eq!(full.largest_gap(), None);
+
+    let none = Coverage::from_slots(0, 3 * 3600, HOUR, &[]);
+    assert_eq!(none.gaps, vec![Gap { start: 0, end: 3 * 3600 }]);
+    assert_eq!(none.ratio(), 0.0);
+
+    let zero_length = Coverage::from_slots(3600, 3600, HOUR, &[]);
+    assert_eq!((zero_length.expected, zero_length.gaps.len()), (0, 0));
+    assert_eq!(zero_length.ratio(), 1.0);
+}
+
+#[test]
+fn the_earliest_of_equal_gaps_is_the_largest() {
+    let coverage = Coverage::from_slots(0, 5 * 3600, HOUR, &[1, 3]);
+
+    assert_eq!(coverage.gaps.len(), 3);
+    assert_eq!(coverage.largest_gap(), Some(Gap { start: 0, end: 3600 }));
+}
@@ -0,0 +1,61 @@
+//! Needs a PostgreSQL database with `postgres/init.sql` applied; set
+//! `TEST_DATABASE_URL` to run these, otherwise they are skipped.
+
+use rust_etl::models::weather::WeatherData;
+use rust_etl::services::database::InsertOutcome;
+
+mod common;
+use common::{database, observation, stored};
+
+fn unique_city() -> String {
+    common::unique_city("Cycle Test")
+}
+
+
+/// Rejected by the database: `weather_icon` is VARCHAR(10).
+fn invalid_observation(city: &str) -> WeatherData {
+    WeatherData {
+        weather_icon: Some("x".repeat(20)),
+        ..observation(city)
+    }
+}
+
+#[tokio::test]
+async fn cycle_commits_every_row() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+
+    let inserted = database
+        .insert_cycle(&[observation(&city), observation(&city)])
+        .await
+        .unwrap();
+
+    assert_eq!(inserted.len(), 2);
+    assert_eq!(stored(&database, &city).await, 2);
+}
+
+#[tokio::test]
+async fn cycle_rolls_back_every_row_when_one_fails() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+
+    let result = database
+        .insert_cycle(&[observation(&city), invalid_observation(&city), observation(&city)])
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(stored(&database, &city).await, 0);
+}
+
+#[tokio::test]
+async fn independent_inserts_keep_the_good_rows() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+
+    let good = database.insert_or_dead_letter(&observation(&city), 1).await.unwrap();
+    let bad = database.insert_or_dead_letter(&invalid_observation(&city), 1).await.unwrap();
+
+    assert!(matches!(good, InsertOutcome::Inserted(_)));
+    assert!(matches!(bad, InsertOutcome::DeadLettered));
+    assert_eq!(stored(&database, &city).await, 1);
+}
@@ -0,0 +1,45 @@
+use rust_etl::services::day_extremes::{local_midnight, DayExtremes};
+
+/// 2026-10-16 00:00 UTC.
+const MIDNIGHT_UTC: i64 = 1_792_108_800;
+/// Montreal in October, UTC-4.
+const EDT: i32 = -4 * 3600;
+
+#[test]
+fn local_midnight_follows_the_offset() {
+    assert_eq!(local_midnight(MIDNIGHT_UTC + 3600, 0), MIDNIGHT_UTC);
+    // 01:00 UTC is still 21:00 the previous day in Montreal
+    assert_eq!(local_midnight(MIDNIGHT_UTC + 3600, EDT), MIDNIGHT_UTC - 86_400 + 4 * 3600);
+    assert_eq!(local_midnight(MIDNIGHT_UTC + 5 * 3600, EDT), MIDNIGHT_UTC + 4 * 3600);
+}
+
+#[test]
+fn range_grows_through_the_day_and_resets_at_local_midnight() {
+    let mut extremes = DayExtremes::new();
+    let morning = MIDNIGHT_UTC + 10 * 3600;
+
+    assert_eq!(extremes.update("Montreal", morning, EDT, 8.0), (8.0, 8.0));
+    assert_eq!(extremes.update("Montreal", morning + 3600, EDT, 12.5), (8.0, 12.5));
+    assert_eq!(extremes.update("Montreal", morning + 7200, EDT, 10.0), (8.0, 12.5));
+    assert_eq!(extremes.update("Quebec", morning, EDT, 3.0), (3.0, 3.0));
+
+    // 23:30 local is the same day; 00:30 local the next one
+    let end_of_day = MIDNIGHT_UTC + 86_400 + 4 * 3600 - 1800;
+    assert_eq!(extremes.update("Montreal", end_of_day, EDT, 6.0), (6.0, 12.5));
+    assert_eq!(extremes.update("Montreal", end_of_day + 3600, EDT, 7.0), (7.0, 7.0));
+}
+
+#[test]
+fn seeded_range_only_applies_to_its_day() {
+    let mut extremes = DayExtremes::new();
+    let noon = MIDNIGHT_UTC + 16 * 3600;
+
+    extremes.seed("Montreal", noon, EDT, Some((4.0, 15.0)));
+    assert!(extremes.is_seeded("Montreal"));
+    assert_eq!(extremes.update("Montreal", noon + 600, EDT, 11.0), (4.0, 15.0));
+    assert_eq!(extremes.update("Montreal", noon + 86_400, EDT, 11.0), (11.0, 11.0));
+
+    extremes.seed("Quebec", noon, EDT, None);
+    assert!(extremes.is_seeded("Quebec"));
+    assert_eq!(extremes.update("Quebec", noon, EDT, 2.0), (2.0, 2.0));
+}
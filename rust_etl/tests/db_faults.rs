@@ -0,0 +1,181 @@
+//! Recovery from injected database failures. Built only with
+//! `--features fault-injection`, and like the other database tests needs
+//! `TEST_DATABASE_URL` pointing at a database with `postgres/init.sql`
+//! applied; otherwise the tests are skipped.
+#![cfg(feature = "fault-injection")]
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::collector::{CityStatus, Collector};
+use rust_etl::models::weather::WeatherData;
+use rust_etl::services::database::{DatabaseService, InsertOutcome};
+use rust_etl::services::db_faults::Fault;
+use rust_etl::services::insert_writer::InsertWriter;
+use rust_etl::services::metrics::Metrics;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+mod common;
+use common::{database, observation, stored};
+
+fn unique_city() -> String {
+    common::unique_city("Fault Test")
+}
+
+async fn dead_lettered(city: &str) -> i64 {
+    let pool = common::pool().await;
+    sqlx::query_scalar("SELECT COUNT(*) FROM dead_letter WHERE city = $1")
+        .bind(city)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+}
+
+/// Runs `rows` through an insert writer configured by `config`, one
+/// `enqueue` per row (or one `enqueue_cycle` with `CYCLE_TRANSACTION`), and
+/// waits for it to drain.
+async fn write(database: &Arc<DatabaseService>, config: &AppConfig, rows: Vec<WeatherData>) {
+    let metrics = Arc::new(Metrics::from_config(config).unwrap());
+    let (inserted, _) = broadcast::channel(16);
+    let (writer, task) = InsertWriter::spawn(Arc::clone(database), metrics, inserted, config);
+    if config.cycle_transaction {
+        writer.enqueue_cycle(rows).await.unwrap();
+    } else {
+        for row in rows {
+            writer.enqueue(row).await.unwrap();
+        }
+    }
+    drop(writer);
+    task.await.unwrap();
+}
+
+fn unbatched() -> AppConfig {
+    AppConfig {
+        writer_flush_interval: Duration::ZERO,
+        ..AppConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn faults_are_consumed_in_order() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    database.faults().fail_next_inserts(1, Fault::Timeout);
+    database.faults().fail_next_inserts(1, Fault::ConnectionReset);
+
+    let first = database.insert_weather_data(&observation(&city)).await.unwrap_err();
+    let second = database.insert_weather_data(&observation(&city)).await.unwrap_err();
+    database.insert_weather_data(&observation(&city)).await.unwrap();
+
+    assert!(matches!(first.downcast_ref::<sqlx::Error>(), Some(sqlx::Error::PoolTimedOut)));
+    assert!(matches!(second.downcast_ref::<sqlx::Error>(), Some(sqlx::Error::Io(_))));
+    assert_eq!(database.faults().pending(), 0);
+    assert_eq!(stored(&database, &city).await, 1);
+}
+
+#[tokio::test]
+async fn writer_keeps_going_after_a_connection_error() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    database.faults().fail_next_inserts(1, Fault::ConnectionReset);
+
+    write(&database, &unbatched(), vec![observation(&city), observation(&city), observation(&city)]).await;
+
+    // Connection errors aren't retried by the writer: that row is lost
+    assert_eq!(stored(&database, &city).await, 2);
+    assert_eq!(dead_lettered(&city).await, 0);
+}
+
+#[tokio::test]
+async fn rejected_batch_falls_back_to_single_inserts() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    database.faults().fail_next_inserts(1, Fault::DiskFull);
+    let config = AppConfig {
+        insert_batch_size: 3,
+        ..AppConfig::default()
+    };
+
+    write(&database, &config, vec![observation(&city), observation(&city), observation(&city)]).await;
+
+    assert_eq!(stored(&database, &city).await, 3);
+}
+
+#[tokio::test]
+async fn transient_data_error_is_retried() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    database.faults().fail_next_inserts(1, Fault::DataError);
+
+    let outcome = database.insert_or_dead_letter(&observation(&city), 2).await.unwrap();
+
+    assert!(matches!(outcome, InsertOutcome::Inserted(_)));
+    assert_eq!(dead_lettered(&city).await, 0);
+}
+
+#[tokio::test]
+async fn persistent_data_error_is_dead_lettered() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    database.faults().fail_next_inserts(2, Fault::DataError);
+    let config = AppConfig {
+        insert_max_attempts: 2,
+        ..unbatched()
+    };
+
+    write(&database, &config, vec![observation(&city), observation(&city)]).await;
+
+    assert_eq!(stored(&database, &city).await, 1);
+    assert_eq!(dead_lettered(&city).await, 1);
+}
+
+#[tokio::test]
+async fn cycle_rolls_back_on_a_mid_cycle_failure() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    // The second row fails after the first was inserted in the transaction
+    database.faults().allow_next_inserts(1);
+    database.faults().fail_next_inserts(1, Fault::Timeout);
+    let config = AppConfig {
+        cycle_transaction: true,
+        ..AppConfig::default()
+    };
+
+    write(&database, &config, vec![observation(&city), observation(&city)]).await;
+
+    assert_eq!(stored(&database, &city).await, 0);
+    assert_eq!(dead_lettered(&city).await, 0);
+}
+
+#[tokio::test]
+async fn diff_only_mode_ignores_rows_that_failed_to_insert() {
+    let Some(database) = database().await else { return };
+    let city = unique_city();
+    let api = common::MockApi::fixed(200, common::current_weather(&city, 5.0)).await;
+    let config = AppConfig {
+        api_base_url: api.url.clone(),
+        api_keys: vec!["test-key".to_string()],
+        cities: vec![city.clone()],
+        fetch_max_attempts: 1,
+        diff_only_insert: true,
+        insert_max_attempts: 1,
+        ..unbatched()
+    };
+    let metrics = Arc::new(Metrics::from_config(&config).unwrap());
+    let (inserted, _) = broadcast::channel(16);
+    let (writer, task) = InsertWriter::spawn(Arc::clone(&database), Arc::clone(&metrics), inserted, &config);
+    let mut collector = Collector::new(&config, Arc::clone(&database), metrics, writer, Vec::new()).unwrap();
+    database.faults().fail_next_inserts(1, Fault::DiskFull);
+
+    let first = collector.run_cycle().await;
+    while database.faults().pending() > 0 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let second = collector.run_cycle().await;
+
+    assert_eq!(first.cities[0].status, CityStatus::Queued);
+    assert_eq!(second.cities[0].status, CityStatus::Queued);
+    drop(collector.into_outputs());
+    task.await.unwrap();
+    assert_eq!(stored(&database, &city).await, 1);
+}
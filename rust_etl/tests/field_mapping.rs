@@ -0,0 +1,78 @@
+use rust_etl::models::units::Units;
+use rust_etl::services::field_mapping::{FieldMapping, JsonPath};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn mapping(fields: &[(&str, &str)]) -> anyhow::Result<FieldMapping> {
+    let fields: BTreeMap<String, String> = fields
+        .iter()
+        .map(|(field, path)| (field.to_string(), path.to_string()))
+        .collect();
+    FieldMapping::new(&fields)
+}
+
+#[test]
+fn paths_accept_keys_and_indices() {
+    let document = json!({ "data": [{ "obs": { "temp": 21.5 } }, { "obs": { "temp": 3 } }] });
+
+    assert_eq!(JsonPath::parse("data[0].obs.temp").unwrap().get(&document), Some(&json!(21.5)));
+    assert_eq!(JsonPath::parse("$.data.1.obs.temp").unwrap().get(&document), Some(&json!(3)));
+    assert_eq!(JsonPath::parse("data[2].obs.temp").unwrap().get(&document), None);
+
+    for invalid in ["data..obs", "data[0", "data[x]", "data[0]x"] {
+        assert!(JsonPath::parse(invalid).is_err(), "{} should not parse", invalid);
+    }
+}
+
+#[test]
+fn extracts_a_record_from_a_nested_response() {
+    let mapping = mapping(&[
+        ("city", "location.name"),
+        ("temperature", "current.temp"),
+        ("humidity", "current.rh"),
+        ("wind_speed", "current.wind.speed"),
+        ("pressure", "current.pressure"),
+        ("weather_main", "current.conditions[0].label"),
+        ("timestamp", "current.observed_at"),
+        ("uv_index", "current.uv"),
+    ])
+    .unwrap();
+    let document = json!({
+        "location": { "name": "Lisbon" },
+        "current": {
+            "temp": "18.4",
+            "rh": 71.6,
+            "wind": { "speed": 4.2 },
+            "pressure": 1016.4,
+            "conditions": [{ "label": "Clouds" }],
+            "observed_at": "2026-10-16T12:00:00Z",
+            "uv": null
+        }
+    });
+
+    let data = mapping.extract(&document, "lisbon", Units::Metric).unwrap();
+
+    assert_eq!(data.city.as_deref(), Some("Lisbon"));
+    assert_eq!(data.temperature, 18.4);
+    assert_eq!(data.humidity, 72);
+    assert_eq!(data.wind_speed, 4.2);
+    assert_eq!(data.pressure, Some(1016));
+    assert_eq!(data.weather_main.as_deref(), Some("Clouds"));
+    assert_eq!(data.timestamp, 1_792_152_000);
+    assert_eq!(data.uv_index, None);
+    assert_eq!(data.units.as_deref(), Some("metric"));
+}
+
+#[test]
+fn invalid_mappings_and_responses_are_rejected() {
+    let error = mapping(&[("temperature", "t"), ("humidity", "h")]).unwrap_err();
+    assert!(error.to_string().contains("wind_speed"), "{}", error);
+    assert!(mapping(&[("temperature", "t"), ("humidity", "h"), ("wind_speed", "w"), ("gusts", "g")]).is_err());
+
+    let mapping = mapping(&[("temperature", "t"), ("humidity", "h"), ("wind_speed", "w")]).unwrap();
+    let data = mapping.extract(&json!({ "t": 1, "h": 50, "w": 2 }), "Oslo", Units::Metric).unwrap();
+    assert_eq!(data.city.as_deref(), Some("Oslo"));
+
+    assert!(mapping.extract(&json!({ "t": 1, "h": 50 }), "Oslo", Units::Metric).is_err());
+    assert!(mapping.extract(&json!({ "t": "warm", "h": 50, "w": 2 }), "Oslo", Units::Metric).is_err());
+}
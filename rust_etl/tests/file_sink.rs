@@ -0,0 +1,42 @@
+use rust_etl::models::weather::WeatherData;
+use rust_etl::sinks::file::FileSink;
+use rust_etl::sinks::format::Serializer;
+use rust_etl::sinks::WeatherSink;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+mod common;
+
+/// Writes the city name under a `city` header, failing its first record.
+#[derive(Default)]
+struct FlakySerializer {
+    failed: AtomicBool,
+}
+
+impl Serializer for FlakySerializer {
+    fn serialize(&self, data: &WeatherData) -> anyhow::Result<String> {
+        if !self.failed.swap(true, Ordering::SeqCst) {
+            anyhow::bail!("not serializable");
+        }
+        Ok(data.city.clone().unwrap_or_default())
+    }
+
+    fn header(&self) -> Option<String> {
+        Some("city".to_string())
+    }
+}
+
+#[tokio::test]
+async fn a_failed_write_keeps_the_header_for_the_next_one() {
+    let path = std::env::temp_dir().join(format!("rust_etl_file_sink_{}.csv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let sink = FileSink::open(path.to_str().unwrap(), Box::new(FlakySerializer::default())).unwrap();
+    let data = common::observation("Quebec");
+
+    assert!(sink.write(&data).await.is_err());
+    sink.write(&data).await.unwrap();
+    sink.write(&data).await.unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(written, "city\nQuebec\nQuebec\n");
+}
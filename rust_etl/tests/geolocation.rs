@@ -0,0 +1,130 @@
+//! Resolves `AUTO_LOCATE` against a stand-in geolocation service.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::geolocation;
+use rust_etl::services::weather_service::{ApiTier, WeatherService};
+
+mod common;
+use common::MockApi;
+
+/// A lookup service answering every request with `status` and `body`.
+async fn mock_service(status: u16, body: serde_json::Value) -> String {
+    format!("{}/json", MockApi::fixed(status, body.to_string()).await.url)
+}
+
+/// `AUTO_LOCATE` without `CITIES`, as loaded from the environment.
+fn auto_locate(url: String) -> AppConfig {
+    AppConfig {
+        auto_locate: true,
+        auto_locate_url: url,
+        cities: Vec::new(),
+        ..AppConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn collects_for_the_detected_location() {
+    let url = mock_service(
+        200,
+        serde_json::json!({ "status": "success", "city": "Montreal", "countryCode": "CA", "lat": 45.5, "lon": -73.6 }),
+    )
+    .await;
+    let mut config = auto_locate(url);
+
+    geolocation::apply(&mut config).await.unwrap();
+
+    assert_eq!(config.city, "Montreal,CA");
+    assert_eq!(config.cities, vec!["Montreal,CA".to_string()]);
+    let located = config.located.unwrap();
+    assert_eq!((located.lat, located.lon), (45.5, -73.6));
+}
+
+#[tokio::test]
+async fn configured_cities_are_kept() {
+    let mut config = auto_locate("http://127.0.0.1:1/json".to_string());
+    config.cities = vec!["London".to_string(), "Paris".to_string()];
+
+    geolocation::apply(&mut config).await.unwrap();
+
+    assert_eq!(config.cities, vec!["London".to_string(), "Paris".to_string()]);
+    assert!(config.located.is_none());
+}
+
+#[tokio::test]
+async fn one_call_uses_the_detected_coordinates() {
+    let url = mock_service(200, serde_json::json!({ "city": "Lyon", "country_code": "FR", "latitude": 45.75, "longitude": 4.85 })).await;
+    let mut config = auto_locate(url);
+    geolocation::apply(&mut config).await.unwrap();
+
+    let onecall = serde_json::json!({
+        "lat": 45.75,
+        "lon": 4.85,
+        "timezone_offset": 7200,
+        "current": {
+            "dt": chrono::Utc::now().timestamp() - 60,
+            "temp": 18.0,
+            "pressure": 1012,
+            "humidity": 55,
+            "wind_speed": 2.0,
+            "weather": [{ "id": 800, "main": "Clear", "description": "clear sky", "icon": "01d" }]
+        }
+    });
+    let api = MockApi::fixed(200, onecall.to_string()).await;
+    config.api_base_url = api.url.clone();
+    config.api_keys = vec!["test-key".to_string()];
+    config.openweather_tier = ApiTier::OneCall;
+
+    let data = WeatherService::new(&config).fetch_weather(&config.city).await.unwrap();
+
+    assert_eq!(data.temperature, 18.0);
+    let requests = api.received();
+    assert_eq!(requests.len(), 1);
+    assert!(!requests[0].path().starts_with("/geo/"), "{}", requests[0].target);
+    assert_eq!(requests[0].query("lat").as_deref(), Some("45.75"));
+    assert_eq!(requests[0].query("lon").as_deref(), Some("4.85"));
+}
+
+#[tokio::test]
+async fn ipapi_co_errors_are_failed_lookups() {
+    let url = mock_service(200, serde_json::json!({ "error": true, "reason": "RateLimited" })).await;
+    let error = geolocation::locate(&auto_locate(url)).await.unwrap_err();
+    assert!(error.to_string().contains("RateLimited"), "{}", error);
+}
+
+#[tokio::test]
+async fn understands_ipapi_co_responses() {
+    let url = mock_service(
+        200,
+        serde_json::json!({ "ip": "203.0.113.7", "city": "Lyon", "country_code": "FR", "latitude": 45.75, "longitude": 4.85 }),
+    )
+    .await;
+
+    let location = geolocation::locate(&auto_locate(url)).await.unwrap();
+
+    assert_eq!(location.query(), "Lyon,FR");
+    assert_eq!((location.lat, location.lon), (45.75, 4.85));
+}
+
+#[tokio::test]
+async fn failed_lookups_are_errors() {
+    let url = mock_service(200, serde_json::json!({ "status": "fail", "message": "private range" })).await;
+    let error = geolocation::apply(&mut auto_locate(url)).await.unwrap_err();
+    assert!(format!("{:#}", error).contains("private range"), "{:#}", error);
+
+    let url = mock_service(503, serde_json::json!({})).await;
+    let error = geolocation::locate(&auto_locate(url)).await.unwrap_err();
+    assert!(error.to_string().contains("503"), "{}", error);
+}
+
+#[tokio::test]
+async fn does_nothing_unless_enabled() {
+    let mut config = AppConfig {
+        auto_locate_url: "http://127.0.0.1:1/json".to_string(),
+        ..AppConfig::default()
+    };
+    let cities = config.cities.clone();
+
+    geolocation::apply(&mut config).await.unwrap();
+
+    assert_eq!(config.cities, cities);
+}
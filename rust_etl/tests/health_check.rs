@@ -0,0 +1,34 @@
+//! Needs a PostgreSQL database with `postgres/init.sql` applied; set
+//! `TEST_DATABASE_URL` to run these, otherwise they are skipped.
+
+use rust_etl::services::database::DatabaseService;
+
+mod common;
+
+#[tokio::test]
+async fn writable_check_leaves_no_rows_behind() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+
+    database.health_check_writable().await.unwrap();
+    database.health_check_writable().await.unwrap();
+
+    let pool = common::pool().await;
+    let probes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM weather_data WHERE id = -1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(probes, 0);
+}
+
+#[tokio::test]
+async fn read_only_database_fails_only_the_writable_check() {
+    let Some(url) = common::database_url() else { return };
+    let separator = if url.contains('?') { '&' } else { '?' };
+    let read_only = format!("{}{}options=-c%20default_transaction_read_only%3Don", url, separator);
+    let database = DatabaseService::new(&read_only).await.unwrap();
+
+    database.health_check().await.unwrap();
+    let error = database.health_check_writable().await.unwrap_err();
+    assert!(format!("{:#}", error).contains("read-only"), "{:#}", error);
+}
@@ -0,0 +1,39 @@
+//! Fetches from a stand-in API that gzips its responses when asked to.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::weather_service::WeatherService;
+
+mod common;
+use common::{current_weather, MockApi};
+
+fn service(base_url: String, http_compression: bool) -> WeatherService {
+    WeatherService::new(&AppConfig {
+        api_base_url: base_url,
+        api_keys: vec!["test-key".to_string()],
+        http_compression,
+        ..AppConfig::default()
+    })
+}
+
+#[tokio::test]
+async fn compressed_responses_are_requested_and_decoded() {
+    let body = current_weather("Montreal", 21.5);
+    let api = MockApi::gzipped(move |_, _| (200, body.clone())).await;
+
+    let data = service(api.url.clone(), true).fetch_weather("Montreal").await.unwrap();
+
+    assert_eq!(data.temperature, 21.5);
+    let accepted = api.received()[0].header("Accept-Encoding").unwrap().to_string();
+    assert!(accepted.contains("gzip") && accepted.contains("br"), "{}", accepted);
+}
+
+#[tokio::test]
+async fn compression_can_be_turned_off() {
+    let body = current_weather("Montreal", 21.5);
+    let api = MockApi::gzipped(move |_, _| (200, body.clone())).await;
+
+    let data = service(api.url.clone(), false).fetch_weather("Montreal").await.unwrap();
+
+    assert_eq!(data.temperature, 21.5);
+    assert_eq!(api.received()[0].header("Accept-Encoding"), None);
+}
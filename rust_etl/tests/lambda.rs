@@ -0,0 +1,52 @@
+//! Runs the Lambda handler against a canned OpenWeatherMap stand-in. Built
+//! only with `--features lambda`, and like the other database tests needs
+//! `TEST_DATABASE_URL`; otherwise the tests are skipped.
+#![cfg(feature = "lambda")]
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::lambda::Handler;
+
+mod common;
+use common::{current_weather, stored, unique_city, MockApi};
+
+fn config(database_url: String, api_url: String, cities: Vec<String>) -> AppConfig {
+    AppConfig {
+        database_url,
+        api_base_url: api_url,
+        api_keys: vec!["test-key".to_string()],
+        cities,
+        fetch_max_attempts: 1,
+        ..AppConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn each_invocation_runs_and_writes_one_cycle() {
+    let Some(database_url) = common::database_url() else { return };
+    let Some(database) = common::database().await else { return };
+    let city = unique_city("Lambda Test");
+    let api = MockApi::fixed(200, current_weather(&city, 7.5)).await;
+    let handler = Handler::new(config(database_url, api.url.clone(), vec![city.clone()])).await.unwrap();
+
+    for invocation in 1..=2 {
+        let summary = handler.invoke().await.unwrap();
+        assert_eq!((summary.report.fetched, summary.report.queued), (1, 1));
+        assert_eq!(stored(&database, &city).await, invocation);
+    }
+
+    let json = serde_json::to_value(handler.invoke().await.unwrap()).unwrap();
+    assert_eq!(json["queued"], 1);
+    assert_eq!(json["outcomes"][0]["city"], city.as_str());
+    assert_eq!(json["outcomes"][0]["status"], "queued");
+}
+
+#[tokio::test]
+async fn a_rejected_key_fails_the_invocation() {
+    let Some(database_url) = common::database_url() else { return };
+    let api = MockApi::fixed(401, r#"{"cod":401,"message":"Invalid API key"}"#).await;
+    let handler = Handler::new(config(database_url, api.url.clone(), vec![unique_city("Lambda Test")]))
+        .await
+        .unwrap();
+
+    assert!(handler.invoke().await.is_err());
+}
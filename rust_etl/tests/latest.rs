@@ -0,0 +1,61 @@
+#![cfg(feature = "server")]
+
+use rust_etl::models::weather::WeatherData;
+use rust_etl::server::latest::LatestObservation;
+use rust_etl::services::database::DatabaseService;
+use std::time::Duration;
+
+mod common;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn observed_at(timestamp: i64) -> WeatherData {
+    WeatherData { timestamp, ..common::fixture() }
+}
+
+#[test]
+fn age_is_measured_from_the_observation_time() {
+    let now = 1_792_152_000;
+
+    let fresh = LatestObservation::new(observed_at(now - 600), now, HOUR);
+    assert_eq!((fresh.age_seconds, fresh.is_stale), (600, false));
+
+    let stale = LatestObservation::new(observed_at(now - 3601), now, HOUR);
+    assert_eq!((stale.age_seconds, stale.is_stale), (3601, true));
+
+    let ahead = LatestObservation::new(observed_at(now + 30), now, HOUR);
+    assert_eq!((ahead.age_seconds, ahead.is_stale), (0, false));
+}
+
+#[test]
+fn freshness_is_serialized_alongside_the_observation() {
+    let now = 1_792_152_000;
+    let json = serde_json::to_value(LatestObservation::new(observed_at(now - 90), now, HOUR)).unwrap();
+
+    assert_eq!(json["city"], "Montreal");
+    assert_eq!(json["timestamp"], now - 90);
+    assert_eq!(json["age_seconds"], 90);
+    assert_eq!(json["is_stale"], false);
+}
+
+#[tokio::test]
+async fn repeated_timestamps_resolve_to_the_last_ingested_row() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.expect("connect to TEST_DATABASE_URL");
+    let city = common::unique_city("Latest Test");
+    let poll = |temperature| WeatherData {
+        city: Some(city.clone()),
+        temperature,
+        ..observed_at(1_792_152_000)
+    };
+
+    for temperature in [1.0, 2.0, 3.0] {
+        database.insert_weather_data(&poll(temperature)).await.unwrap();
+    }
+    database.insert_batch(&[poll(4.0), poll(5.0)]).await.unwrap();
+
+    let latest = database.get_latest_weather(&city).await.unwrap().unwrap();
+    assert_eq!(latest.temperature, 5.0);
+    let recent: Vec<f64> = database.get_recent(&city, 3).await.unwrap().iter().map(|row| row.temperature).collect();
+    assert_eq!(recent, vec![5.0, 4.0, 3.0]);
+}
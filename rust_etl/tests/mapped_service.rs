@@ -0,0 +1,85 @@
+//! The `mapped` provider against a stand-in JSON endpoint.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::provider::{ProviderKind, WeatherProvider};
+use std::collections::BTreeMap;
+
+mod common;
+use common::MockApi;
+
+const API_KEY: &str = "mapped-key-5678";
+
+fn mapped(url_template: String, fields: &[(&str, &str)]) -> AppConfig {
+    AppConfig {
+        mapped_provider_url: url_template,
+        mapped_provider_api_key: API_KEY.to_string(),
+        mapped_provider_fields: fields
+            .iter()
+            .map(|(field, path)| (field.to_string(), path.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+        ..AppConfig::default()
+    }
+}
+
+fn fields() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("city", "location.name"),
+        ("temperature", "current.temp"),
+        ("humidity", "current.rh"),
+        ("wind_speed", "current.wind"),
+        ("timestamp", "current.observed_at"),
+    ]
+}
+
+fn template(api: &MockApi) -> String {
+    format!("{}/v1/current?place={{city}}&key={{api_key}}&units={{units}}", api.url)
+}
+
+#[tokio::test]
+async fn fetches_and_maps_a_record() {
+    let body = serde_json::json!({
+        "location": { "name": "São Paulo" },
+        "current": {
+            "temp": 24.5,
+            "rh": 58,
+            "wind": 2.1,
+            "observed_at": (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339()
+        }
+    });
+    let api = MockApi::fixed(200, body.to_string()).await;
+    let provider = WeatherProvider::new(ProviderKind::Mapped, &mapped(template(&api), &fields())).unwrap();
+
+    let data = provider.fetch_weather("São Paulo").await.unwrap();
+
+    assert_eq!(data.city.as_deref(), Some("São Paulo"));
+    assert_eq!((data.temperature, data.humidity, data.wind_speed), (24.5, 58, 2.1));
+    assert_eq!(data.source.as_deref(), Some("mapped"));
+    let request = &api.received()[0];
+    assert_eq!(request.path(), "/v1/current");
+    assert_eq!(request.query("place").as_deref(), Some("São Paulo"));
+    assert_eq!(request.query("key").as_deref(), Some(API_KEY));
+    assert_eq!(request.query("units").as_deref(), Some("metric"));
+}
+
+#[tokio::test]
+async fn errors_do_not_contain_the_api_key() {
+    let api = MockApi::fixed(500, format!(r#"{{"error":"bad key {}"}}"#, API_KEY)).await;
+    let provider = WeatherProvider::new(ProviderKind::Mapped, &mapped(template(&api), &fields())).unwrap();
+    let error = provider.fetch_weather("Lisbon").await.unwrap_err();
+    assert!(!format!("{:#}", error).contains(API_KEY), "{:#}", error);
+
+    let closed_port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let url = format!("http://127.0.0.1:{}/v1/current?place={{city}}&key={{api_key}}", closed_port);
+    let provider = WeatherProvider::new(ProviderKind::Mapped, &mapped(url, &fields())).unwrap();
+    let error = provider.fetch_weather("Lisbon").await.unwrap_err();
+    assert!(!format!("{:#}", error).contains(API_KEY), "{:#}", error);
+}
+
+#[test]
+fn invalid_mapping_is_a_construction_error() {
+    let config = mapped("http://127.0.0.1:1/{city}".to_string(), &[("temperature", "current[")]);
+
+    let error = WeatherProvider::new(ProviderKind::Mapped, &config).err().unwrap();
+
+    assert!(error.to_string().contains("MAPPED_PROVIDER_FIELDS"), "{:#}", error);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "server")]
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::metrics::{CityLabels, Metrics};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn cities_past_the_cap_share_one_label() {
+    let labels = CityLabels::new(true, 2);
+    assert_eq!(labels.label("Montreal"), Some("Montreal"));
+    assert_eq!(labels.label("Toronto"), Some("Toronto"));
+    assert_eq!(labels.label("Vancouver"), Some("other"));
+    assert_eq!(labels.label("Montreal"), Some("Montreal"));
+
+    assert_eq!(CityLabels::new(true, 0).label("Vancouver"), Some("Vancouver"));
+    assert_eq!(CityLabels::new(false, 0).label("Montreal"), None);
+}
+
+/// Sends one counter per city through a StatsD client configured by
+/// `config` and returns the datagrams received.
+fn sent(config: AppConfig, cities: &[&str]) -> Vec<String> {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let metrics = Metrics::from_config(&AppConfig {
+        statsd_addr: Some(receiver.local_addr().unwrap().to_string()),
+        ..config
+    })
+    .unwrap();
+
+    let mut lines = Vec::new();
+    for city in cities {
+        metrics.incr("fetch.success", &[("city", city), ("provider", "owm")]);
+        let mut buffer = [0; 512];
+        let length = receiver.recv(&mut buffer).unwrap();
+        lines.push(String::from_utf8_lossy(&buffer[..length]).into_owned());
+    }
+    lines
+}
+
+#[test]
+fn city_tags_follow_the_label_settings() {
+    let capped = AppConfig {
+        metrics_max_cities: 1,
+        ..AppConfig::default()
+    };
+    assert_eq!(
+        sent(capped, &["Montreal", "Toronto"]),
+        [
+            "weather_etl.fetch.success:1|c|#city:Montreal,provider:owm",
+            "weather_etl.fetch.success:1|c|#city:other,provider:owm",
+        ]
+    );
+
+    let aggregated = AppConfig {
+        metrics_city_labels: false,
+        ..AppConfig::default()
+    };
+    assert_eq!(sent(aggregated, &["Montreal"]), ["weather_etl.fetch.success:1|c|#provider:owm"]);
+}
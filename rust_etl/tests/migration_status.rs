@@ -0,0 +1,20 @@
+//! Needs a PostgreSQL database migrated to the current schema; set
+//! `TEST_DATABASE_URL` to run these, otherwise they are skipped.
+
+use rust_etl::services::database::{DatabaseService, MigrationState, MIGRATOR};
+
+mod common;
+
+#[tokio::test]
+async fn migrated_database_has_nothing_pending() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.unwrap();
+    database.run_migrations().await.unwrap();
+
+    let statuses = database.migration_status().await.unwrap();
+
+    let versions: Vec<i64> = statuses.iter().map(|status| status.version).collect();
+    let expected: Vec<i64> = MIGRATOR.iter().map(|migration| migration.version).collect();
+    assert_eq!(versions, expected);
+    assert!(statuses.iter().all(|status| status.state == MigrationState::Applied), "{:?}", statuses);
+}
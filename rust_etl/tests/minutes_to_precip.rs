@@ -0,0 +1,45 @@
+use rust_etl::models::weather::OneCallResponse;
+use serde_json::json;
+
+const START: i64 = 1_792_152_000;
+
+/// A One Call response whose `minutely` block has one entry per minute with
+/// the given precipitation, in mm/h.
+fn nowcast(precipitation: &[f64]) -> OneCallResponse {
+    let minutely: Vec<_> = precipitation
+        .iter()
+        .enumerate()
+        .map(|(i, mm)| json!({ "dt": START + 60 * i as i64, "precipitation": mm }))
+        .collect();
+    serde_json::from_value(json!({
+        "lat": 45.5,
+        "lon": -73.57,
+        "current": { "dt": START, "uvi": 1.2 },
+        "minutely": minutely,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn counts_minutes_until_precipitation_starts() {
+    let mut minutes = vec![0.0; 60];
+    minutes[15..].fill(0.4);
+    assert_eq!(nowcast(&minutes).minutes_to_precip(), Some(15));
+}
+
+#[test]
+fn counts_minutes_until_precipitation_stops_as_negative() {
+    let mut minutes = vec![1.5; 60];
+    minutes[42..].fill(0.0);
+    assert_eq!(nowcast(&minutes).minutes_to_precip(), Some(-42));
+}
+
+#[test]
+fn no_transition_or_nowcast_is_none() {
+    assert_eq!(nowcast(&[0.0; 60]).minutes_to_precip(), None);
+    assert_eq!(nowcast(&[0.8; 60]).minutes_to_precip(), None);
+
+    let without: OneCallResponse =
+        serde_json::from_value(json!({ "lat": 45.5, "lon": -73.57, "current": { "dt": START } })).unwrap();
+    assert_eq!(without.minutes_to_precip(), None);
+}
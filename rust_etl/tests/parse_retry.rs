@@ -0,0 +1,41 @@
+//! Runs fetches against a stand-in API that serves a fixed sequence of
+//! bodies.
+
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::services::fetch_error::FetchError;
+use rust_etl::services::weather_service::WeatherService;
+
+mod common;
+use common::{current_weather, MockApi};
+
+fn service(base_url: String) -> WeatherService {
+    WeatherService::new(&AppConfig {
+        api_base_url: base_url,
+        api_keys: vec!["test-key".to_string()],
+        ..AppConfig::default()
+    })
+}
+
+#[tokio::test]
+async fn truncated_response_is_retried_once() {
+    let full = current_weather("Montreal", 21.5);
+    let truncated = full[..full.len() / 2].to_string();
+    let api = MockApi::sequence(vec![truncated, full]).await;
+
+    let data = service(api.url.clone()).fetch_weather("Montreal").await.unwrap();
+
+    assert_eq!(data.temperature, 21.5);
+    assert_eq!(api.received().len(), 2);
+}
+
+#[tokio::test]
+async fn schema_mismatch_is_not_retried() {
+    let api = MockApi::sequence(vec![r#"{"cod":200,"name":"Montreal"}"#.to_string()]).await;
+
+    let error = service(api.url.clone()).fetch_weather("Montreal").await.unwrap_err();
+
+    let fetch_error = error.downcast_ref::<FetchError>().unwrap();
+    assert!(matches!(fetch_error, FetchError::Parse { .. }));
+    assert!(!fetch_error.is_truncated());
+    assert_eq!(api.received().len(), 1);
+}
@@ -0,0 +1,66 @@
+use rust_etl::models::units::Units;
+use rust_etl::models::weather::{ApiResponse, WeatherData};
+use rust_etl::services::database::DatabaseService;
+use rust_etl::sinks::format::OutputFormat;
+
+mod common;
+
+fn response(main: serde_json::Value) -> ApiResponse {
+    serde_json::from_value(serde_json::json!({
+        "coord": { "lon": 6.87, "lat": 45.92 },
+        "weather": [{ "id": 800, "main": "Clear", "description": "clear sky", "icon": "01d" }],
+        "base": "stations",
+        "main": main,
+        "wind": { "speed": 2.1, "deg": 40 },
+        "clouds": { "all": 0 },
+        "dt": 1_792_108_800,
+        "sys": { "country": "FR" },
+        "timezone": 7200,
+        "id": 3025622,
+        "name": "Chamonix",
+        "cod": 200
+    }))
+    .unwrap()
+}
+
+#[test]
+fn sea_and_ground_level_are_kept_when_reported() {
+    let reported = response(serde_json::json!({
+        "temp": 4.0, "feels_like": 2.5, "humidity": 70, "pressure": 1021, "sea_level": 1021, "grnd_level": 880
+    }));
+    let data = WeatherData::from_api_response(&reported, Units::Metric);
+    assert_eq!(data.pressure, Some(1021));
+    assert_eq!((data.sea_level_pressure, data.ground_level_pressure), (Some(1021), Some(880)));
+
+    let plain = response(serde_json::json!({ "temp": 4.0, "feels_like": 2.5, "humidity": 70, "pressure": 1021 }));
+    let data = WeatherData::from_api_response(&plain, Units::Metric);
+    assert_eq!((data.sea_level_pressure, data.ground_level_pressure), (None, None));
+}
+
+#[test]
+fn sinks_write_both_levels() {
+    let data = WeatherData::builder()
+        .city("Chamonix")
+        .pressure(1021)
+        .pressure_levels(Some(1021), Some(880))
+        .build();
+
+    let line = OutputFormat::Influx.serializer().serialize(&data).unwrap();
+    assert!(line.contains("sea_level_pressure=1021i,ground_level_pressure=880i"), "{}", line);
+    let json: serde_json::Value = serde_json::from_str(&OutputFormat::Json.serializer().serialize(&data).unwrap()).unwrap();
+    assert_eq!(json["ground_level_pressure"], 880);
+}
+
+#[tokio::test]
+async fn both_levels_are_stored() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.expect("connect to TEST_DATABASE_URL");
+    let city = format!("Pressure Test {}", std::process::id());
+    let data = WeatherData::builder().city(&city).pressure(1021).pressure_levels(Some(1021), Some(880)).build();
+
+    database.insert_weather_data(&data).await.unwrap();
+    database.insert_batch(&[data.clone(), data]).await.unwrap();
+
+    let stored = database.get_latest_weather(&city).await.unwrap().unwrap();
+    assert_eq!((stored.sea_level_pressure, stored.ground_level_pressure), (Some(1021), Some(880)));
+}
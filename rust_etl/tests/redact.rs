@@ -0,0 +1,18 @@
+use rust_etl::utils::redact;
+
+#[test]
+fn secrets_are_masked_wherever_they_appear() {
+    let text = "GET /weather?appid=key-1 failed; retried with key-2 (key-1 again)";
+
+    let masked = redact::mask_secrets(text, ["key-1", "", "key-2"]);
+
+    assert_eq!(masked, "GET /weather?appid=**** failed; retried with **** (**** again)");
+}
+
+#[test]
+fn only_the_url_password_is_masked() {
+    let url = "postgres://etl:hunter2@db:5432/weather";
+
+    assert_eq!(redact::mask_url(url), "postgres://etl:****@db:5432/weather");
+    assert_eq!(redact::mask_url_in("password hunter2 rejected", url), "password **** rejected");
+}
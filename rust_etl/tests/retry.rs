@@ -0,0 +1,139 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_etl::utils::retry::{retry, retry_within, Jitter, RetryBudget, RetryPolicy};
+use std::cell::Cell;
+use std::time::Duration;
+
+fn policy(jitter: Jitter) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 5,
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(1000),
+        jitter,
+    }
+}
+
+#[test]
+fn backoff_doubles_and_caps() {
+    let policy = policy(Jitter::None);
+    let delays: Vec<u128> = (1..=6).map(|n| policy.backoff(n).as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+}
+
+#[test]
+fn no_jitter_uses_exact_backoff() {
+    let policy = policy(Jitter::None);
+    let mut rng = StdRng::seed_from_u64(1);
+    for retry in 1..=6 {
+        assert_eq!(policy.next_delay(retry, policy.base_delay, &mut rng), policy.backoff(retry));
+    }
+}
+
+#[test]
+fn full_jitter_stays_within_zero_and_backoff() {
+    let policy = policy(Jitter::Full);
+    let mut rng = StdRng::seed_from_u64(2);
+    for retry in 1..=6 {
+        for _ in 0..200 {
+            let delay = policy.next_delay(retry, policy.base_delay, &mut rng);
+            assert!(delay <= policy.backoff(retry));
+        }
+    }
+}
+
+#[test]
+fn equal_jitter_stays_within_half_and_full_backoff() {
+    let policy = policy(Jitter::Equal);
+    let mut rng = StdRng::seed_from_u64(3);
+    for retry in 1..=6 {
+        let backoff = policy.backoff(retry);
+        for _ in 0..200 {
+            let delay = policy.next_delay(retry, policy.base_delay, &mut rng);
+            assert!(delay >= backoff / 2 && delay <= backoff);
+        }
+    }
+}
+
+#[test]
+fn decorrelated_jitter_stays_within_base_and_cap() {
+    let policy = policy(Jitter::Decorrelated);
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut previous = policy.base_delay;
+    for retry in 1..=50 {
+        let delay = policy.next_delay(retry, previous, &mut rng);
+        assert!(delay >= policy.base_delay);
+        assert!(delay <= policy.max_delay);
+        assert!(delay <= previous * 3);
+        previous = delay;
+    }
+}
+
+#[tokio::test]
+async fn retry_stops_after_max_attempts() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: Jitter::None,
+    };
+    let calls = Cell::new(0);
+
+    let result: anyhow::Result<()> = retry(&policy, "test", |_| true, || {
+        calls.set(calls.get() + 1);
+        async { Err(anyhow::anyhow!("boom")) }
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.get(), 3);
+}
+
+#[tokio::test]
+async fn retry_does_not_retry_rejected_errors() {
+    let policy = RetryPolicy {
+        base_delay: Duration::ZERO,
+        ..RetryPolicy::default()
+    };
+    let calls = Cell::new(0);
+
+    let result: anyhow::Result<()> = retry(&policy, "test", |_| false, || {
+        calls.set(calls.get() + 1);
+        async { Err(anyhow::anyhow!("fatal")) }
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.get(), 1);
+}
+
+#[tokio::test]
+async fn retry_budget_is_shared_across_calls() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        jitter: Jitter::None,
+    };
+    let budget = RetryBudget::new(3);
+    let calls = Cell::new(0);
+
+    for _ in 0..3 {
+        let result: anyhow::Result<()> = retry_within(&policy, &budget, "test", |_| true, || {
+            calls.set(calls.get() + 1);
+            async { Err(anyhow::anyhow!("outage")) }
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    // 3 first attempts plus the 3 retries the budget allows
+    assert_eq!(calls.get(), 6);
+    assert!(budget.is_exhausted());
+}
+
+#[test]
+fn zero_retry_budget_is_unlimited() {
+    let budget = RetryBudget::new(0);
+    assert!((0..1000).all(|_| budget.try_take()));
+    assert!(!budget.is_exhausted());
+}
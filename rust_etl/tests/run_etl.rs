@@ -0,0 +1,145 @@
+//! Runs the library entry point against a canned OpenWeatherMap stand-in.
+//! Needs a PostgreSQL database with `postgres/init.sql` applied; set
+//! `TEST_DATABASE_URL` to run these, otherwise they are skipped.
+
+use futures_util::StreamExt;
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::etl::run_etl;
+use rust_etl::models::units::{Units, UnitsMismatchAction};
+use rust_etl::models::weather::WeatherDataBuilder;
+use rust_etl::services::database::DatabaseService;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+mod common;
+use common::{current_weather, unique_city, MockApi};
+
+#[tokio::test]
+async fn yields_each_cycle_until_shut_down() {
+    let Some(database_url) = common::database_url() else { return };
+    let city = unique_city("Library Test");
+    let api = MockApi::fixed(200, current_weather(&city, 4.0)).await;
+    let config = AppConfig {
+        database_url,
+        api_base_url: api.url.clone(),
+        api_keys: vec!["test-key".to_string()],
+        cities: vec![city.clone()],
+        interval: Duration::from_millis(200),
+        fetch_max_attempts: 1,
+        ..AppConfig::default()
+    };
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let results = run_etl(config, shutdown_rx).await.unwrap();
+    tokio::pin!(results);
+
+    for _ in 0..2 {
+        let result = results.next().await.expect("a cycle result").unwrap();
+        assert!(result.fatal.is_none());
+        assert_eq!(result.report.fetched, 1, "{:?}", result.cities);
+        assert_eq!(result.cities[0].city, city);
+    }
+
+    shutdown_tx.send(()).unwrap();
+    let remaining = tokio::time::timeout(Duration::from_secs(5), results.count()).await.unwrap();
+    assert!(remaining <= 1, "{}", remaining);
+}
+
+#[tokio::test]
+async fn ends_with_the_error_that_stopped_collection() {
+    let Some(database_url) = common::database_url() else { return };
+    let city = unique_city("Library Test");
+    let api = MockApi::fixed(500, "upstream unavailable").await;
+    let config = AppConfig {
+        database_url,
+        api_base_url: api.url.clone(),
+        api_keys: vec!["test-key".to_string()],
+        cities: vec![city],
+        interval: Duration::from_millis(50),
+        fetch_max_attempts: 1,
+        max_consecutive_failures: 2,
+        ..AppConfig::default()
+    };
+    let (_shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let results: Vec<_> = tokio::time::timeout(Duration::from_secs(5), run_etl(config, shutdown_rx).await.unwrap().collect())
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert!(results[..2].iter().all(|result| result.as_ref().is_ok_and(|result| result.report.failed == 1)));
+    let error = results[2].as_ref().unwrap_err();
+    assert!(error.to_string().contains("2 consecutive cycles failed"), "{:#}", error);
+}
+
+#[tokio::test]
+async fn normalizes_a_config_built_in_code() {
+    let Some(database_url) = common::database_url() else { return };
+    let city = unique_city("Library Test");
+    let api = MockApi::fixed(200, current_weather(&city, 4.0)).await;
+    let config = AppConfig {
+        database_url,
+        api_base_url: api.url.clone(),
+        api_key: "test-key".to_string(),
+        api_keys: Vec::new(),
+        city: city.clone(),
+        cities: Vec::new(),
+        insert_queue_capacity: 0,
+        fetch_max_attempts: 1,
+        ..AppConfig::default()
+    };
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let results = run_etl(config, shutdown_rx).await.unwrap();
+    tokio::pin!(results);
+
+    let result = results.next().await.expect("a cycle result").unwrap();
+    assert_eq!(result.cities[0].city, city);
+    assert_eq!(result.report.queued, 1, "{:?}", result.cities);
+    shutdown_tx.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), results.count()).await.unwrap();
+}
+
+#[tokio::test]
+async fn rejects_an_invalid_config_before_connecting() {
+    let config = AppConfig {
+        database_url: "postgres://etl@127.0.0.1:1/weather".to_string(),
+        api_keys: vec!["test-key".to_string()],
+        city: String::new(),
+        cities: Vec::new(),
+        ..AppConfig::default()
+    };
+
+    let error = run_etl(config, std::future::pending::<()>()).await.err().unwrap();
+
+    assert!(format!("{:#}", error).contains("must name at least one city"), "{:#}", error);
+}
+
+#[tokio::test]
+async fn only_configured_cities_are_checked_for_stored_units() {
+    let Some(database_url) = common::database_url() else { return };
+    let database = DatabaseService::new(&database_url).await.unwrap();
+    let (removed, configured) = (unique_city("Library Test"), unique_city("Library Test"));
+    let imperial = WeatherDataBuilder::from(common::observation(&removed)).units(Units::Imperial).build();
+    database.insert_weather_data(&imperial).await.unwrap();
+    let api = MockApi::fixed(200, current_weather(&configured, 4.0)).await;
+    let config = AppConfig {
+        database_url,
+        api_base_url: api.url.clone(),
+        api_keys: vec!["test-key".to_string()],
+        cities: vec![configured],
+        fetch_max_attempts: 1,
+        units_mismatch_action: UnitsMismatchAction::Refuse,
+        ..AppConfig::default()
+    };
+
+    let (_shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    assert!(run_etl(config.clone(), shutdown_rx).await.is_ok());
+
+    let config = AppConfig {
+        cities: vec![removed],
+        ..config
+    };
+    let (_shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    assert!(run_etl(config, shutdown_rx).await.is_err());
+}
@@ -0,0 +1,72 @@
+use rust_etl::models::units::Units;
+use rust_etl::services::day_extremes::{DayExtremes, DayRange};
+use rust_etl::services::runtime_state::{RuntimeState, STATE_VERSION};
+use rust_etl::services::smoothing::TemperatureEma;
+use rust_etl::services::storage_sampler::StorageSampler;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn state_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rust_etl_state_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn state() -> RuntimeState {
+    RuntimeState {
+        version: STATE_VERSION,
+        saved_at: 1_792_108_800,
+        units: Units::Metric,
+        temperature_ema: HashMap::from([("Montreal".to_string(), 11.25)]),
+        day_extremes: HashMap::from([("Montreal".to_string(), DayRange { index: 20_741, low: 4.0, high: 13.5 })]),
+        last_pressure: HashMap::from([("Montreal".to_string(), 1013)]),
+        storage_sampler: Default::default(),
+        utc_offsets: HashMap::from([("Montreal".to_string(), -14_400)]),
+    }
+}
+
+#[tokio::test]
+async fn saved_state_loads_back() {
+    let path = state_path("round_trip");
+    assert_eq!(RuntimeState::load(&path).await.unwrap(), None);
+
+    state().save(&path).await.unwrap();
+    assert_eq!(RuntimeState::load(&path).await.unwrap(), Some(state()));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn newer_or_invalid_files_are_errors() {
+    let path = state_path("newer");
+    RuntimeState { version: STATE_VERSION + 1, ..state() }.save(&path).await.unwrap();
+    let error = RuntimeState::load(&path).await.unwrap_err();
+    assert!(error.to_string().contains("state version"), "{}", error);
+
+    std::fs::write(&path, "{\"version\":").unwrap();
+    assert!(RuntimeState::load(&path).await.is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn restored_trackers_continue_where_they_left_off() {
+    let mut ema = TemperatureEma::new(0.5);
+    ema.restore(HashMap::from([("Montreal".to_string(), 10.0)]));
+    assert!(ema.is_seeded("Montreal"));
+    assert_eq!(ema.update("Montreal", 20.0), 15.0);
+
+    let mut extremes = DayExtremes::new();
+    extremes.restore(state().day_extremes);
+    assert_eq!(extremes.update("Montreal", 20_741 * 86_400 + 3600, 0, 9.0), (4.0, 13.5));
+
+    let mut sampler = StorageSampler::new(3, Duration::ZERO);
+    assert!(sampler.should_store("Montreal", 0));
+    sampler.record("Montreal", 0);
+    let mut restarted = StorageSampler::new(3, Duration::ZERO);
+    restarted.restore(sampler.snapshot());
+    for _ in 0..2 {
+        assert!(!restarted.should_store("Montreal", 0));
+        restarted.record("Montreal", 0);
+    }
+    assert!(restarted.should_store("Montreal", 0));
+}
@@ -0,0 +1,25 @@
+use rust_etl::utils::timezone::resolve;
+
+#[test]
+fn single_zone_countries_match_on_offset() {
+    assert_eq!(resolve("JP", 139.7, 32400), Some("Asia/Tokyo"));
+    assert_eq!(resolve("fr", 5.4, 7200), Some("Europe/Paris"));
+    assert_eq!(resolve("FR", 2.4, 0), None);
+}
+
+#[test]
+fn the_nearest_zone_with_the_offset_wins() {
+    // Montreal in summer: Toronto's daylight offset, not Halifax's standard one
+    assert_eq!(resolve("CA", -73.6, -14400), Some("America/Toronto"));
+    // Phoenix keeps -7 h all year, Denver only in winter
+    assert_eq!(resolve("US", -111.9, -25200), Some("America/Phoenix"));
+    assert_eq!(resolve("US", -104.9, -25200), Some("America/Denver"));
+    // Tenerife shares an offset with London, not with Madrid
+    assert_eq!(resolve("ES", -16.3, 0), Some("Atlantic/Canary"));
+}
+
+#[test]
+fn unknown_countries_are_unresolved() {
+    assert_eq!(resolve("ZZ", 0.0, 0), None);
+    assert_eq!(resolve("", 0.0, 0), None);
+}
@@ -0,0 +1,105 @@
+use rust_etl::config::app_config::AppConfig;
+use rust_etl::models::units::Units;
+use rust_etl::models::weather::{ComfortThresholds, InvalidWeatherData, WeatherData, WeatherDataBuilder};
+use rust_etl::services::database::DatabaseService;
+
+mod common;
+
+#[test]
+fn builder_defaults_to_a_metric_record_dated_now() {
+    let data = WeatherData::builder().city("Quebec").temperature(-5.0).build();
+
+    assert_eq!(data.city.as_deref(), Some("Quebec"));
+    assert_eq!(data.temperature, -5.0);
+    assert_eq!(data.units(), Units::Metric);
+    assert!((chrono::Utc::now().timestamp() - data.timestamp).abs() <= 1);
+    assert_eq!(data.pressure, None);
+    assert!(data.labels.is_empty());
+}
+
+#[test]
+fn builder_overrides_a_fixture() {
+    let data = WeatherDataBuilder::from(common::fixture())
+        .units(Units::Imperial)
+        .temperature(54.5)
+        .label("site", "roof")
+        .build();
+
+    assert_eq!(data.city.as_deref(), Some("Montreal"));
+    assert_eq!(data.units(), Units::Imperial);
+    assert_eq!(data.temperature, 54.5);
+    assert_eq!(data.weather_id, Some(803));
+    assert_eq!(data.labels.get("site").map(String::as_str), Some("roof"));
+}
+
+#[test]
+fn try_build_rejects_impossible_values() {
+    let valid = || WeatherData::builder().city("Quebec").temperature(-5.0).humidity(80);
+    assert!(valid().try_build(None, false).is_ok());
+
+    assert_eq!(WeatherData::builder().try_build(None, false).unwrap_err(), InvalidWeatherData::MissingCity);
+    assert_eq!(valid().humidity(120).try_build(None, false).unwrap_err(), InvalidWeatherData::Humidity(120));
+    assert_eq!(
+        valid().temperature(f64::NAN).try_build(None, false).unwrap_err(),
+        InvalidWeatherData::NotFinite("temperature")
+    );
+    assert_eq!(
+        valid().wind(-1.0, None).try_build(None, false).unwrap_err(),
+        InvalidWeatherData::NegativeWindSpeed(-1.0)
+    );
+    assert_eq!(
+        valid().wind(2.0, Some(400.0)).try_build(None, false).unwrap_err(),
+        InvalidWeatherData::WindDirection(400.0)
+    );
+    assert_eq!(valid().timestamp(0).try_build(None, false).unwrap_err(), InvalidWeatherData::Timestamp(0));
+}
+
+#[test]
+fn try_build_fills_computed_columns_and_created_at() {
+    let cold = || WeatherData::builder().city("Quebec").temperature(-10.0).humidity(80).wind(8.0, Some(270.0));
+    let config = AppConfig {
+        wind_chill: true,
+        comfort_very_cold: -15.0,
+        ..AppConfig::default()
+    };
+
+    let data = cold().try_build(config.comfort_thresholds().as_ref(), config.wind_chill).unwrap();
+
+    assert!(data.dew_point.is_some());
+    assert!(data.wind_chill.is_some());
+    let configured = data.comfort_category(&config.comfort_thresholds().unwrap()).to_string();
+    let default = data.comfort_category(&ComfortThresholds::default()).to_string();
+    assert_ne!(configured, default);
+    assert_eq!(data.comfort_category, Some(configured));
+    assert!((chrono::Utc::now() - data.created_at.unwrap()).num_seconds() <= 1);
+
+    let data = cold().try_build(None, false).unwrap();
+    assert!(data.dew_point.is_some());
+    assert_eq!((data.wind_chill, data.comfort_category), (None, None));
+}
+
+#[tokio::test]
+async fn hand_built_record_is_stored() {
+    let Some(url) = common::database_url() else { return };
+    let database = DatabaseService::new(&url).await.expect("connect to TEST_DATABASE_URL");
+    let city = format!("Builder Test {}", std::process::id());
+    let data = WeatherData::builder()
+        .city(&city)
+        .temperature(3.5)
+        .humidity(65)
+        .pressure(1008)
+        .wind(4.0, Some(90.0))
+        .source("manual")
+        .label("site", "roof")
+        .try_build(None, false)
+        .unwrap();
+
+    let row = database.insert_weather_data(&data).await.unwrap();
+    assert!(row.created_at.is_some());
+
+    let stored = database.get_latest_weather(&city).await.unwrap().unwrap();
+    assert_eq!((stored.temperature, stored.humidity, stored.pressure), (3.5, 65, Some(1008)));
+    assert_eq!(stored.source.as_deref(), Some("manual"));
+    assert_eq!(stored.dew_point, data.dew_point);
+    assert_eq!(stored.labels, data.labels);
+}
@@ -0,0 +1,43 @@
+use rust_etl::models::weather::WeatherData;
+use serde_json::json;
+
+fn reading(temperature: f64, wind_speed: f64, units: &str) -> WeatherData {
+    serde_json::from_value(json!({
+        "temperature": temperature,
+        "humidity": 60,
+        "wind_speed": wind_speed,
+        "timestamp": 0,
+        "units": units
+    }))
+    .unwrap()
+}
+
+#[test]
+fn matches_the_environment_canada_table() {
+    // -20 °C with a 30 km/h wind feels like -33 °C
+    let metric = reading(-20.0, 30.0 / 3.6, "metric").wind_chill().unwrap();
+    assert!((metric - -32.6).abs() < 0.05, "{}", metric);
+
+    // The same reading in °F and mph
+    let imperial = reading(-4.0, 30.0 / 1.609_344, "imperial").wind_chill().unwrap();
+    assert!((imperial - (-32.6 * 9.0 / 5.0 + 32.0)).abs() < 0.1, "{}", imperial);
+}
+
+#[test]
+fn undefined_when_mild_or_calm() {
+    assert_eq!(reading(10.5, 10.0, "metric").wind_chill(), None);
+    assert_eq!(reading(-15.0, 1.0, "metric").wind_chill(), None);
+    assert!(reading(10.0, 2.0, "metric").wind_chill().is_some());
+}
+
+#[test]
+fn stored_only_when_enabled() {
+    let mut data = reading(-10.0, 5.0, "metric");
+
+    data.apply_computed(None, false);
+    assert_eq!(data.wind_chill, None);
+
+    data.apply_computed(None, true);
+    assert_eq!(data.wind_chill, data.wind_chill());
+    assert!(data.wind_chill.is_some());
+}